@@ -27,6 +27,10 @@ pub struct Cli {
     /// Suppress non-error output
     #[arg(short, long, global = true, conflicts_with = "verbose")]
     pub quiet: bool,
+
+    /// Use this profile of .matterof.toml (or set MATTEROF_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
 }
 
 /// Available commands
@@ -44,14 +48,38 @@ pub enum Commands {
     Replace(ReplaceArgs),
     /// Query front matter with JSONPath
     Query(QueryArgs),
+    /// Summarize JSONPath matches across files (distinct, counts, group-by, sums)
+    Aggregate(AggregateArgs),
+    /// Export front matter as a table with one row per file
+    Export(ExportArgs),
+    /// Set front matter from CSV, TSV or JSON Lines rows, one row per file
+    Import(ImportArgs),
+    /// Derive front matter (title, description, word count, ...) from the markdown body
+    Derive(DeriveArgs),
     /// Initialize front matter in files
     Init(InitArgs),
+    /// Create a new markdown file from a template
+    New(NewArgs),
     /// Remove empty front matter blocks
     Clean(CleanArgs),
     /// Validate front matter syntax
     Validate(ValidateArgs),
     /// Format front matter (sort keys, normalize formatting)
     Format(FormatArgs),
+    /// Convert front matter to another format (YAML, TOML or JSON)
+    Convert(ConvertArgs),
+    /// Work with JSON Schemas for front matter
+    Schema(SchemaArgs),
+    /// Maintain the front matter index used by get, query and aggregate
+    Index(IndexArgs),
+    /// Apply a script of operations, writing each file once
+    Apply(ApplyArgs),
+    /// Compare the front matter of two files (exit 0: same, 1: different, 2: error)
+    Diff(DiffArgs),
+    /// Restore the files of a recorded run
+    Undo(UndoArgs),
+    /// List recorded runs that can be undone
+    History(HistoryArgs),
 }
 
 /// Common options for file operations
@@ -61,24 +89,75 @@ pub struct CommonFileOptions {
     pub files: Vec<PathBuf>,
 
     /// Follow symbolic links when processing directories
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_follow_links")]
     pub follow_links: bool,
 
+    /// Do not follow symbolic links, even if the configuration asks to
+    #[arg(long, overrides_with = "follow_links")]
+    pub no_follow_links: bool,
+
     /// Maximum depth for directory recursion
     #[arg(long)]
     pub max_depth: Option<usize>,
 
     /// Include hidden files (starting with .)
-    #[arg(long)]
+    #[arg(long, overrides_with = "no_include_hidden")]
     pub include_hidden: bool,
 
+    /// Skip hidden files, even if the configuration includes them
+    #[arg(long, overrides_with = "include_hidden")]
+    pub no_include_hidden: bool,
+
     /// Only process files with these extensions
     #[arg(long = "ext", value_name = "EXT")]
     pub extensions: Vec<String>,
 
-    /// Exclude files matching these patterns
+    /// Exclude files matching these patterns, relative to each path given (e.g. drafts, drafts/*.md)
     #[arg(long = "exclude", value_name = "PATTERN")]
     pub exclude_patterns: Vec<String>,
+
+    #[command(flatten)]
+    pub processing: ProcessingOptions,
+
+    #[command(flatten)]
+    pub errors: ErrorOptions,
+}
+
+/// Options for reading files and spreading the work over threads
+#[derive(Args, Debug, Clone, Default)]
+pub struct ProcessingOptions {
+    /// Largest file to read, in bytes (default: 10 MiB); commands that only
+    /// read front matter apply it to the front matter alone
+    #[arg(long, value_name = "BYTES")]
+    pub max_file_size: Option<usize>,
+
+    /// Number of files to process in parallel (default: one per CPU)
+    #[arg(short = 'j', long, value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Options for carrying on past files that fail
+#[derive(Args, Debug, Clone, Default)]
+pub struct ErrorOptions {
+    /// Keep processing the other files when some fail, then report every failure
+    #[arg(long)]
+    pub keep_going: bool,
+
+    /// Write a JSON report of the failed files to this file (`-` for stdout)
+    #[arg(long, value_name = "FILE", requires = "keep_going")]
+    pub error_report: Option<PathBuf>,
+}
+
+/// Options for re-running a command as files change
+#[derive(Args, Debug, Clone)]
+pub struct WatchOptions {
+    /// Keep running and re-evaluate files as they change (Ctrl-C to stop)
+    #[arg(long, conflicts_with = "error_report")]
+    pub watch: bool,
+
+    /// Milliseconds without changes before re-evaluating
+    #[arg(long, value_name = "MS", default_value = "200", requires = "watch")]
+    pub debounce: u64,
 }
 
 /// Common options for write operations
@@ -88,6 +167,14 @@ pub struct WriteOptions {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Lines of context around each change in diffs (default: 3)
+    #[arg(long, value_name = "N")]
+    pub diff_context: Option<usize>,
+
+    /// How --dry-run shows changes
+    #[arg(long, value_enum, default_value = "unified")]
+    pub diff_format: DiffFormat,
+
     /// Create backup files with this suffix
     #[arg(long, value_name = "SUFFIX")]
     pub backup_suffix: Option<String>,
@@ -104,13 +191,33 @@ pub struct WriteOptions {
     #[arg(long, value_name = "DIR")]
     pub output_dir: Option<PathBuf>,
 
-    /// Use atomic writes (default: true)
-    #[arg(long)]
+    /// Write files in place instead of atomically through a temporary file
+    #[arg(long, overrides_with = "atomic")]
     pub no_atomic: bool,
 
+    /// Write files atomically (the default), even if the configuration disables it
+    #[arg(long, overrides_with = "no_atomic")]
+    pub atomic: bool,
+
     /// Line ending style
     #[arg(long, value_enum)]
     pub line_endings: Option<LineEndingStyle>,
+
+    /// Stage all changes and write them all-or-nothing after every file succeeded
+    #[arg(long, overrides_with = "no_transactional")]
+    pub transactional: bool,
+
+    /// Write files one by one, even if the configuration asks for transactions
+    #[arg(long, overrides_with = "transactional")]
+    pub no_transactional: bool,
+
+    /// Record this run in the undo journal (.matterof/journal/) so that it can be undone
+    #[arg(long, overrides_with = "no_journal")]
+    pub journal: bool,
+
+    /// Do not record this run in the undo journal, even if the configuration asks to
+    #[arg(long, overrides_with = "journal")]
+    pub no_journal: bool,
 }
 
 /// Line ending styles for output
@@ -139,9 +246,13 @@ pub struct GetArgs {
     pub query: Option<String>,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// Output format
     #[arg(long, value_enum, default_value = "yaml")]
     pub format: OutputFormat,
@@ -149,6 +260,10 @@ pub struct GetArgs {
     /// Pretty print output
     #[arg(long)]
     pub pretty: bool,
+
+    /// Read every file instead of going through the front matter index
+    #[arg(long)]
+    pub no_index: bool,
 }
 
 /// Arguments for the set command
@@ -165,9 +280,13 @@ pub struct SetArgs {
     pub query: String,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// Value to set
     #[arg(short = 'V', long, value_name = "VALUE", required = true)]
     pub value: String,
@@ -191,9 +310,13 @@ pub struct AddArgs {
     pub query: String,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// Value to add
     #[arg(short = 'V', long, value_name = "VALUE", required = true)]
     pub value: String,
@@ -229,9 +352,13 @@ pub struct RemoveArgs {
     pub query: Option<String>,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// Remove empty parent objects after removal
     #[arg(long)]
     pub cleanup_empty: bool,
@@ -259,9 +386,13 @@ pub struct ReplaceArgs {
     pub query: String,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// New key name (for renaming keys - only works with single match)
     #[arg(long = "new-key", value_name = "KEY")]
     pub new_key: Option<String>,
@@ -290,20 +421,31 @@ pub struct QueryArgs {
     pub query: String,
 
     /// Disable automatic root prepending ($ or $.)
-    #[arg(long)]
+    #[arg(long, overrides_with = "auto_root")]
     pub no_auto_root: bool,
 
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
     /// Only count matches
     #[arg(long)]
     pub count: bool,
 
     /// Check if query matches exist (exit code 0 if exists, 1 if not)
-    #[arg(long)]
+    #[arg(long, conflicts_with = "watch")]
     pub exists: bool,
 
     /// Show both normalized paths and values
     #[arg(long)]
     pub with_values: bool,
+
+    /// Read every file instead of going through the front matter index
+    #[arg(long)]
+    pub no_index: bool,
+
+    #[command(flatten)]
+    pub watch: WatchOptions,
 }
 
 /// Arguments for the init command
@@ -319,11 +461,37 @@ pub struct InitArgs {
     #[arg(long = "default", value_name = "KEY=VALUE")]
     pub defaults: Vec<String>,
 
+    /// Fill in missing keys from a template with {{placeholder}} values (path or name)
+    #[arg(long, value_name = "TEMPLATE")]
+    pub template: Option<PathBuf>,
+
     /// Only initialize files that don't have front matter
     #[arg(long)]
     pub only_missing: bool,
 }
 
+/// Arguments for the new command
+#[derive(Args, Debug)]
+pub struct NewArgs {
+    /// Markdown file to create
+    pub file: PathBuf,
+
+    /// Template path, or name of a template in .matterof/templates/ or ~/.config/matterof/templates/
+    #[arg(short, long, value_name = "TEMPLATE")]
+    pub template: PathBuf,
+
+    /// Set these values before rendering the template (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    pub values: Vec<String>,
+
+    /// Overwrite the file if it already exists
+    #[arg(long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub write_options: WriteOptions,
+}
+
 /// Arguments for the clean command
 #[derive(Args, Debug)]
 pub struct CleanArgs {
@@ -345,12 +513,19 @@ pub struct ValidateArgs {
     pub files: CommonFileOptions,
 
     /// Exit with non-zero code on first validation error
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["keep_going", "watch"])]
     pub fail_fast: bool,
 
+    /// Also check front matter against this JSON Schema file
+    #[arg(long, value_name = "SCHEMA")]
+    pub schema: Option<PathBuf>,
+
     /// Output format for validation results
     #[arg(long, value_enum, default_value = "human")]
     pub format: ValidationFormat,
+
+    #[command(flatten)]
+    pub watch: WatchOptions,
 }
 
 /// Arguments for the format command
@@ -375,6 +550,326 @@ pub struct FormatArgs {
     pub remove_null: bool,
 }
 
+/// Arguments for the convert command
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    #[command(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Target front matter format
+    #[arg(long, value_enum)]
+    pub to: FrontMatterFormat,
+}
+
+/// Arguments for the apply command
+#[derive(Args, Debug)]
+pub struct ApplyArgs {
+    /// YAML script with an ordered list of set/add/remove/replace/rename operations
+    #[arg(value_name = "SCRIPT")]
+    pub script: PathBuf,
+
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    #[command(flatten)]
+    pub write_options: WriteOptions,
+}
+
+/// Arguments for the aggregate command
+#[derive(Args, Debug)]
+pub struct AggregateArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    /// JSONPath query whose matches are aggregated
+    #[arg(long, value_name = "JSONPATH", aliases = ["key", "jsonpath"])]
+    pub query: Option<String>,
+
+    /// Disable automatic root prepending ($ or $.)
+    #[arg(long, overrides_with = "auto_root")]
+    pub no_auto_root: bool,
+
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
+    /// List the distinct matched values
+    #[arg(long)]
+    pub distinct: bool,
+
+    /// Count how often each distinct value matches
+    #[arg(long)]
+    pub count_by: bool,
+
+    /// Aggregate separately per value matched by this JSONPath
+    #[arg(long, value_name = "JSONPATH")]
+    pub group_by: Option<String>,
+
+    /// Sum numeric matches
+    #[arg(long)]
+    pub sum: bool,
+
+    /// Smallest numeric match
+    #[arg(long)]
+    pub min: bool,
+
+    /// Largest numeric match
+    #[arg(long)]
+    pub max: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "yaml")]
+    pub format: AggregateFormat,
+
+    /// Read every file instead of going through the front matter index
+    #[arg(long)]
+    pub no_index: bool,
+}
+
+/// Arguments for the export command
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    /// Comma-separated JSONPath queries, one per column
+    #[arg(
+        long,
+        value_name = "JSONPATH,...",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub columns: Vec<String>,
+
+    /// Disable automatic root prepending ($ or $.)
+    #[arg(long, overrides_with = "auto_root")]
+    pub no_auto_root: bool,
+
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "csv")]
+    pub format: ExportFormat,
+
+    /// Separator joining array elements and multiple matches in one cell
+    #[arg(long, value_name = "SEP", default_value = ";")]
+    pub separator: String,
+
+    /// Header of the leading file path column
+    #[arg(long, value_name = "NAME", default_value = "path")]
+    pub path_column: String,
+
+    /// Leave out the file path column
+    #[arg(long, conflicts_with = "path_column")]
+    pub no_path_column: bool,
+
+    /// Write the table to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the import command
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Data file; each column header other than the key column is a JSONPath to set
+    #[arg(value_name = "DATA")]
+    pub data: PathBuf,
+
+    #[command(flatten)]
+    pub write_options: WriteOptions,
+
+    #[command(flatten)]
+    pub processing: ProcessingOptions,
+
+    #[command(flatten)]
+    pub errors: ErrorOptions,
+
+    /// Column holding the path of the file each row updates
+    #[arg(long, value_name = "NAME", default_value = "path")]
+    pub key_column: String,
+
+    /// Data format (default: from the file extension, else csv)
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+
+    /// Type of a column's values instead of inferring them (repeatable)
+    #[arg(short, long = "type", value_name = "COLUMN=TYPE")]
+    pub types: Vec<String>,
+
+    /// Separator between the elements of array cells
+    #[arg(long, value_name = "SEP", default_value = ";")]
+    pub separator: String,
+
+    /// Disable automatic root prepending ($ or $.)
+    #[arg(long, overrides_with = "auto_root")]
+    pub no_auto_root: bool,
+
+    /// Prepend $ or $. automatically, even if the configuration disables it
+    #[arg(long, overrides_with = "no_auto_root")]
+    pub auto_root: bool,
+}
+
+/// Arguments for the derive command
+#[derive(Args, Debug)]
+pub struct DeriveArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    #[command(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Value to derive as TARGET=EXTRACTOR, or EXTRACTOR to set the key of the same name (repeatable)
+    #[arg(
+        short,
+        long = "field",
+        value_name = "TARGET=EXTRACTOR",
+        required_unless_present = "list"
+    )]
+    pub fields: Vec<String>,
+
+    /// Only set targets that are missing or null
+    #[arg(long)]
+    pub only_missing: bool,
+
+    /// Reading speed used by the reading_time extractor
+    #[arg(long, value_name = "N", default_value_t = 200)]
+    pub words_per_minute: usize,
+
+    /// Longest description in characters for the description extractor
+    #[arg(long, value_name = "N", default_value_t = 160)]
+    pub description_length: usize,
+
+    /// strftime pattern for file and git dates (default: RFC 3339)
+    #[arg(long, value_name = "FORMAT")]
+    pub date_format: Option<String>,
+
+    /// List the available extractors and exit
+    #[arg(long)]
+    pub list: bool,
+}
+
+/// Arguments for the diff command
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Front matter to compare from
+    #[arg(value_name = "OLD")]
+    pub old: PathBuf,
+
+    /// Front matter to compare to
+    #[arg(value_name = "NEW")]
+    pub new: PathBuf,
+
+    /// Ignore differences at or below paths matching this JSONPath (repeatable)
+    #[arg(long = "ignore", value_name = "JSONPATH")]
+    pub ignore: Vec<String>,
+
+    /// Only report keys that are added, removed or moved, not changed values
+    #[arg(long)]
+    pub keys_only: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value = "human")]
+    pub format: DiffOutputFormat,
+}
+
+/// Arguments for the undo command
+#[derive(Args, Debug)]
+pub struct UndoArgs {
+    /// Run to undo (default: the most recent run not yet undone)
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: Option<String>,
+
+    /// Restore files even if they changed since the run
+    #[arg(long)]
+    pub force: bool,
+
+    /// Show what would be restored without touching files
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the history command
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Show only the most recent N runs
+    #[arg(short = 'n', long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// List the files of each run
+    #[arg(long)]
+    pub files: bool,
+}
+
+/// Arguments for the schema command
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommands,
+}
+
+/// Schema subcommands
+#[derive(Subcommand, Debug)]
+pub enum SchemaCommands {
+    /// Infer a draft JSON Schema from the front matter of existing files
+    Infer(SchemaInferArgs),
+}
+
+/// Arguments for the schema infer command
+#[derive(Args, Debug)]
+pub struct SchemaInferArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+
+    /// Write the schema to this file instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Largest number of distinct values to suggest as an enum
+    #[arg(long, value_name = "N", default_value = "10")]
+    pub max_enum: usize,
+}
+
+/// Arguments for the index command
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: IndexCommands,
+}
+
+/// Index subcommands
+#[derive(Subcommand, Debug)]
+pub enum IndexCommands {
+    /// Index the front matter of files from scratch
+    Build(IndexFilesArgs),
+    /// Re-index changed and new files, and drop deleted ones
+    Update(IndexFilesArgs),
+    /// Show which files changed since they were indexed
+    Status(IndexFilesArgs),
+}
+
+/// Arguments for the index subcommands
+#[derive(Args, Debug)]
+pub struct IndexFilesArgs {
+    #[command(flatten)]
+    pub files: CommonFileOptions,
+}
+
+/// Front matter serialization formats
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum FrontMatterFormat {
+    /// YAML between `---` fences
+    Yaml,
+    /// TOML between `+++` fences
+    Toml,
+    /// A bare JSON object at the top of the file
+    Json,
+}
+
 /// Value types for type conversion
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum ValueType {
@@ -403,6 +898,62 @@ pub enum OutputFormat {
     Internal,
 }
 
+/// Output formats for aggregate command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFormat {
+    /// YAML mapping
+    Yaml,
+    /// JSON object
+    Json,
+    /// Aligned text table
+    Table,
+}
+
+/// Output formats for export command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values
+    Csv,
+    /// Tab-separated values
+    Tsv,
+    /// One JSON object per line
+    Jsonl,
+    /// GitHub-flavored markdown table
+    MarkdownTable,
+}
+
+/// Input formats for import command
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// Diff styles for dry runs
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Unified line diff, applicable with git apply / patch -p1
+    #[default]
+    Unified,
+    /// One line per changed NormalizedPath
+    Semantic,
+    /// Path-level changes as one JSON object per file
+    Json,
+}
+
+/// Output formats for diff command
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum DiffOutputFormat {
+    /// One line per changed NormalizedPath
+    Human,
+    /// JSON object with the list of changes
+    Json,
+}
+
 /// Output formats for validate command
 #[derive(ValueEnum, Debug, Clone, Copy)]
 pub enum ValidationFormat {
@@ -410,11 +961,10 @@ pub enum ValidationFormat {
     Human,
     /// JSON format
     Json,
-    /// Simple format (just file paths)
+    /// Simple format (passing file paths, one `file: path: rule` line per schema violation)
     Simple,
 }
 
-
 impl From<LineEndingStyle> for matterof::io::LineEndings {
     fn from(style: LineEndingStyle) -> Self {
         match style {
@@ -425,6 +975,26 @@ impl From<LineEndingStyle> for matterof::io::LineEndings {
     }
 }
 
+impl From<matterof::io::LineEndings> for LineEndingStyle {
+    fn from(line_endings: matterof::io::LineEndings) -> Self {
+        match line_endings {
+            matterof::io::LineEndings::Unix => Self::Unix,
+            matterof::io::LineEndings::Windows => Self::Windows,
+            matterof::io::LineEndings::Preserve => Self::Preserve,
+        }
+    }
+}
+
+impl From<FrontMatterFormat> for matterof::core::FrontMatterFormat {
+    fn from(format: FrontMatterFormat) -> Self {
+        match format {
+            FrontMatterFormat::Yaml => Self::Yaml,
+            FrontMatterFormat::Toml => Self::Toml,
+            FrontMatterFormat::Json => Self::Json { fenced: false },
+        }
+    }
+}
+
 impl From<ValueType> for matterof::core::ValueType {
     fn from(vt: ValueType) -> Self {
         match vt {
@@ -491,12 +1061,18 @@ mod tests {
             "--dry-run",
             "--backup-suffix",
             ".bak",
+            "--transactional",
+            "--diff-context",
+            "1",
             "file.md",
         ];
         let cli = Cli::try_parse_from(args).unwrap();
 
         if let Commands::Set(set_args) = cli.command {
             assert!(set_args.write_options.dry_run);
+            assert!(set_args.write_options.transactional);
+            assert_eq!(set_args.write_options.diff_context, Some(1));
+            assert_eq!(set_args.write_options.diff_format, DiffFormat::Unified);
             assert_eq!(
                 set_args.write_options.backup_suffix,
                 Some(".bak".to_string())
@@ -506,6 +1082,308 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_convert_command() {
+        let args = vec!["matterof", "convert", "--to", "toml", "--dry-run", "docs/"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Convert(convert_args) = cli.command {
+            assert!(matches!(convert_args.to, FrontMatterFormat::Toml));
+            assert!(convert_args.write_options.dry_run);
+        } else {
+            panic!("Expected Convert command");
+        }
+
+        assert!(Cli::try_parse_from(["matterof", "convert", "file.md"]).is_err());
+    }
+
+    #[test]
+    fn test_apply_command() {
+        let args = vec![
+            "matterof",
+            "apply",
+            "migrate.yaml",
+            "a.md",
+            "b.md",
+            "--dry-run",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Apply(apply_args) = cli.command {
+            assert_eq!(apply_args.script, PathBuf::from("migrate.yaml"));
+            assert_eq!(
+                apply_args.files.files,
+                vec![PathBuf::from("a.md"), PathBuf::from("b.md")]
+            );
+            assert!(apply_args.write_options.dry_run);
+        } else {
+            panic!("Expected Apply command");
+        }
+    }
+
+    #[test]
+    fn test_diff_command() {
+        let args = vec![
+            "matterof",
+            "diff",
+            "en/post.md",
+            "zh/post.md",
+            "--ignore",
+            "title",
+            "--ignore",
+            "$.summary",
+            "--keys-only",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Diff(diff_args) = cli.command {
+            assert_eq!(diff_args.old, PathBuf::from("en/post.md"));
+            assert_eq!(diff_args.new, PathBuf::from("zh/post.md"));
+            assert_eq!(diff_args.ignore, vec!["title", "$.summary"]);
+            assert!(diff_args.keys_only);
+        } else {
+            panic!("Expected Diff command");
+        }
+    }
+
+    #[test]
+    fn test_aggregate_command() {
+        let args = vec![
+            "matterof",
+            "aggregate",
+            "--query",
+            "tags[*]",
+            "--count-by",
+            "--group-by",
+            "author",
+            "--format",
+            "table",
+            "content/",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Aggregate(aggregate_args) = cli.command {
+            assert_eq!(aggregate_args.query, Some("tags[*]".to_string()));
+            assert_eq!(aggregate_args.group_by, Some("author".to_string()));
+            assert!(aggregate_args.count_by);
+            assert!(!aggregate_args.distinct);
+            assert_eq!(aggregate_args.format, AggregateFormat::Table);
+        } else {
+            panic!("Expected Aggregate command");
+        }
+    }
+
+    #[test]
+    fn test_export_command() {
+        let args = vec![
+            "matterof",
+            "export",
+            "--columns",
+            "title,date,author.name",
+            "--columns",
+            "tags",
+            "--format",
+            "markdown-table",
+            "docs/",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Export(export_args) = cli.command {
+            assert_eq!(
+                export_args.columns,
+                vec!["title", "date", "author.name", "tags"]
+            );
+            assert_eq!(export_args.format, ExportFormat::MarkdownTable);
+            assert_eq!(export_args.separator, ";");
+            assert_eq!(export_args.path_column, "path");
+            assert!(!export_args.no_path_column);
+        } else {
+            panic!("Expected Export command");
+        }
+    }
+
+    #[test]
+    fn test_import_command() {
+        let args = vec![
+            "matterof",
+            "import",
+            "data.csv",
+            "--key-column",
+            "file",
+            "--type",
+            "tags=array",
+            "-t",
+            "weight=int",
+            "--dry-run",
+            "--transactional",
+            "-j",
+            "2",
+            "--max-file-size",
+            "4096",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Import(import_args) = cli.command {
+            assert_eq!(import_args.data, PathBuf::from("data.csv"));
+            assert_eq!(import_args.key_column, "file");
+            assert_eq!(import_args.types, vec!["tags=array", "weight=int"]);
+            assert!(import_args.format.is_none());
+            assert!(import_args.write_options.dry_run);
+            assert!(import_args.write_options.transactional);
+            assert_eq!(import_args.processing.jobs, Some(2));
+            assert_eq!(import_args.processing.max_file_size, Some(4096));
+        } else {
+            panic!("Expected Import command");
+        }
+    }
+
+    #[test]
+    fn test_new_command() {
+        let args = vec![
+            "matterof",
+            "new",
+            "posts/my-title.md",
+            "--template",
+            "post",
+            "--set",
+            "title=My Title",
+            "--force",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::New(new_args) = cli.command {
+            assert_eq!(new_args.file, PathBuf::from("posts/my-title.md"));
+            assert_eq!(new_args.template, PathBuf::from("post"));
+            assert_eq!(new_args.values, vec!["title=My Title"]);
+            assert!(new_args.force);
+        } else {
+            panic!("Expected New command");
+        }
+
+        assert!(Cli::try_parse_from(["matterof", "new", "post.md"]).is_err());
+    }
+
+    #[test]
+    fn test_derive_command() {
+        let args = vec![
+            "matterof",
+            "derive",
+            "--field",
+            "title",
+            "-f",
+            "summary=description",
+            "--only-missing",
+            "--words-per-minute",
+            "250",
+            "content/",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Derive(derive_args) = cli.command {
+            assert_eq!(derive_args.fields, vec!["title", "summary=description"]);
+            assert!(derive_args.only_missing);
+            assert_eq!(derive_args.words_per_minute, 250);
+            assert_eq!(derive_args.description_length, 160);
+            assert!(derive_args.date_format.is_none());
+        } else {
+            panic!("Expected Derive command");
+        }
+
+        assert!(Cli::try_parse_from(["matterof", "derive", "content/"]).is_err());
+        assert!(Cli::try_parse_from(["matterof", "derive", "--list"]).is_ok());
+    }
+
+    #[test]
+    fn test_undo_and_history_commands() {
+        let cli =
+            Cli::try_parse_from(["matterof", "undo", "20260101-000000-000", "--force"]).unwrap();
+        if let Commands::Undo(undo_args) = cli.command {
+            assert_eq!(undo_args.run_id.as_deref(), Some("20260101-000000-000"));
+            assert!(undo_args.force);
+            assert!(!undo_args.dry_run);
+        } else {
+            panic!("Expected Undo command");
+        }
+
+        let cli = Cli::try_parse_from(["matterof", "history", "-n", "5"]).unwrap();
+        if let Commands::History(history_args) = cli.command {
+            assert_eq!(history_args.limit, Some(5));
+        } else {
+            panic!("Expected History command");
+        }
+    }
+
+    #[test]
+    fn test_schema_infer_command() {
+        let args = vec!["matterof", "schema", "infer", "--max-enum", "5", "posts/"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Schema(SchemaArgs {
+            command: SchemaCommands::Infer(infer_args),
+        }) = cli.command
+        {
+            assert_eq!(infer_args.max_enum, 5);
+            assert_eq!(infer_args.files.files, vec![PathBuf::from("posts/")]);
+            assert!(infer_args.output.is_none());
+        } else {
+            panic!("Expected Schema Infer command");
+        }
+    }
+
+    #[test]
+    fn test_index_command() {
+        let args = vec!["matterof", "index", "build", "-j", "2", "content/"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Index(IndexArgs {
+            command: IndexCommands::Build(build_args),
+        }) = cli.command
+        {
+            assert_eq!(build_args.files.files, vec![PathBuf::from("content/")]);
+            assert_eq!(build_args.files.processing.jobs, Some(2));
+        } else {
+            panic!("Expected Index Build command");
+        }
+
+        let args = vec![
+            "matterof",
+            "query",
+            "--no-index",
+            "--query",
+            "tags",
+            "content/",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+        assert!(matches!(cli.command, Commands::Query(args) if args.no_index));
+    }
+
+    #[test]
+    fn test_watch_options() {
+        let args = vec![
+            "matterof",
+            "validate",
+            "--watch",
+            "--debounce",
+            "500",
+            "content/",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        if let Commands::Validate(validate_args) = cli.command {
+            assert!(validate_args.watch.watch);
+            assert_eq!(validate_args.watch.debounce, 500);
+        } else {
+            panic!("Expected Validate command");
+        }
+
+        // --debounce needs --watch, and --exists never finishes while watching
+        assert!(Cli::try_parse_from(["matterof", "validate", "--debounce", "10", "a.md"]).is_err());
+        let args = [
+            "matterof", "query", "--query", "title", "--watch", "--exists", "a.md",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
     #[test]
     fn test_file_options() {
         let args = vec![
@@ -520,6 +1398,8 @@ mod tests {
             "md",
             "--ext",
             "markdown",
+            "-j",
+            "4",
             "docs/",
         ];
         let cli = Cli::try_parse_from(args).unwrap();
@@ -527,6 +1407,7 @@ mod tests {
         if let Commands::Get(get_args) = cli.command {
             assert!(get_args.files.follow_links);
             assert_eq!(get_args.files.max_depth, Some(3));
+            assert_eq!(get_args.files.processing.jobs, Some(4));
             assert!(get_args.files.include_hidden);
             assert_eq!(
                 get_args.files.extensions,
@@ -2,82 +2,107 @@
 //!
 //! This module contains the implementation of all CLI commands, providing
 //! a clean separation between CLI argument parsing and core library operations.
+//! Each handler returns the exit status of the process, which is non-zero
+//! when the command ran to the end but the outcome asks for it, like files
+//! skipped with `--keep-going`.
 
 use crate::cli_bin::args::*;
-use log::{debug, info, warn};
+use crate::cli_bin::export::{Column, ExportTable};
+use crate::cli_bin::import::{DataFormat, ImportData, ImportOptions};
+use crate::cli_bin::report::FailureReport;
+use crate::cli_bin::script::Script;
+use crate::cli_bin::templates::load_template;
+use indexmap::IndexMap;
+use log::{debug, error, info, warn};
 use matterof::core::{
-    Document, FrontMatterValue, JsonMutator, JsonPathQuery, JsonPathQueryResult, KeyPath,
-    NormalizedPathUtils, ParsedPath, PathSegment, Query, YamlJsonConverter,
+    check_date_format, Aggregator, ChangeKind, DescriptionExtractor, Document, ExtractorRegistry,
+    FrontMatterDiff, FrontMatterValue, GroupSummary, JsonMutator, JsonPathQuery,
+    JsonPathQueryResult, KeyPath, NormalizedPathUtils, ParsedPath, PathSegment, Query,
+    ReadingTimeExtractor, SchemaInferrer, SchemaValidator, SchemaViolation, TemplateContext,
+    YamlJsonConverter,
 };
 use matterof::error::{MatterOfError, Result};
 use matterof::io::{
-    BackupOptions, FileResolver, FrontMatterReader, FrontMatterWriter, OutputOptions, ReaderConfig,
-    ResolverConfig, WriteOptions as LibWriteOptions, WriterConfig,
+    BackupOptions, BatchReport, EntryStatus, FileResolver, FileWatcher, FrontMatterIndex,
+    FrontMatterReader, FrontMatterWriter, IndexEntry, Journal, OutputOptions, ParallelExecutor,
+    ReaderConfig, ResolverConfig, Transaction, UndoOptions, WatchBatch,
+    WriteOptions as LibWriteOptions, WriteResult, WriterConfig,
 };
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
 
 /// Execute the get command
-pub fn get_command(args: GetArgs) -> Result<()> {
+pub fn get_command(args: GetArgs) -> Result<i32> {
     debug!("Executing get command with args: {:?}", args);
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
-    let mut results = BTreeMap::new();
+    // Use JSONPath query unless all front matter is requested
+    let jsonpath_query = match &args.query {
+        _ if args.all => None,
+        Some(query_str) if args.no_auto_root => {
+            Some(JsonPathQuery::new_with_options(query_str, false)?)
+        }
+        Some(query_str) => Some(JsonPathQuery::new(query_str)?),
+        None => {
+            return Err(MatterOfError::validation(
+                "Either --all or --query must be specified".to_string(),
+            ));
+        }
+    };
 
-    for file in &files {
+    let reader = IndexedReader::new(&args.files, args.no_index)?;
+    let mut report = FailureReport::new("get", &args.files.errors);
+    let found = report.process(&create_executor(&args.files.processing), &files, |file| {
         debug!("Processing file: {}", file.display());
 
-        let document = reader.read_file(file)?;
+        let document = reader.read(file)?;
+        let Some(front_matter) = document.front_matter() else {
+            return Ok(None);
+        };
+        let yaml_value = YamlJsonConverter::document_front_matter_to_yaml(front_matter);
 
-        if args.all {
+        let Some(jsonpath_query) = &jsonpath_query else {
             // Get all front matter
-            if let Some(front_matter) = document.front_matter() {
-                let yaml_value = YamlJsonConverter::document_front_matter_to_yaml(front_matter);
-                if files.len() == 1 {
-                    output_yaml_value(&yaml_value, &args.format, args.pretty)?;
-                    return Ok(());
-                } else {
-                    results.insert(file.to_string_lossy().to_string(), yaml_value);
-                }
-            }
-        } else if let Some(query_str) = &args.query {
-            // Use JSONPath query
-            let jsonpath_query = if args.no_auto_root {
-                JsonPathQuery::new_with_options(query_str, false)?
-            } else {
-                JsonPathQuery::new(query_str)?
-            };
-
-            if let Some(front_matter) = document.front_matter() {
-                let yaml_value = YamlJsonConverter::document_front_matter_to_yaml(front_matter);
-                let json_value = YamlJsonConverter::yaml_to_json(&yaml_value)?;
-                let located_results = jsonpath_query.query_located(&json_value);
-                let matches: Vec<_> = located_results
-                    .into_iter()
-                    .map(|(path, value)| (path, value.clone()))
-                    .collect();
+            return Ok(Some(GetResult::All(yaml_value)));
+        };
+        let json_value = YamlJsonConverter::yaml_to_json(&yaml_value)?;
+        let located_results = jsonpath_query.query_located(&json_value);
+        let matches: Vec<_> = located_results
+            .into_iter()
+            .map(|(path, value)| (path, value.clone()))
+            .collect();
 
-                let query_result = JsonPathQueryResult::new(jsonpath_query.clone(), matches);
+        let query_result = JsonPathQueryResult::new(jsonpath_query.clone(), matches);
+        Ok((!query_result.is_empty()).then_some(GetResult::Query(query_result)))
+    })?;
+    reader.finish();
 
-                if !query_result.is_empty() {
-                    if files.len() == 1 {
-                        output_jsonpath_result(&query_result, &args.format, args.pretty)?;
-                        return Ok(());
-                    } else {
-                        results.insert(file.to_string_lossy().to_string(), query_result.to_yaml()?);
-                    }
-                }
+    let mut results = BTreeMap::new();
+    for (file, result) in found {
+        match result {
+            Some(GetResult::All(yaml_value)) if files.len() == 1 => {
+                output_yaml_value(&yaml_value, &args.format, args.pretty)?;
+                return Ok(0);
             }
-        } else {
-            return Err(MatterOfError::validation(
-                "Either --all or --query must be specified".to_string(),
-            ));
+            Some(GetResult::Query(query_result)) if files.len() == 1 => {
+                output_jsonpath_result(&query_result, &args.format, args.pretty)?;
+                return Ok(0);
+            }
+            Some(GetResult::All(yaml_value)) => {
+                results.insert(file.to_string_lossy().to_string(), yaml_value);
+            }
+            Some(GetResult::Query(query_result)) => {
+                results.insert(file.to_string_lossy().to_string(), query_result.to_yaml()?);
+            }
+            None => {}
         }
     }
 
@@ -88,22 +113,28 @@ pub fn get_command(args: GetArgs) -> Result<()> {
         info!("No matching values found");
     }
 
-    Ok(())
+    report.finish()
+}
+
+/// What the get command found in one file
+enum GetResult {
+    All(serde_yaml::Value),
+    Query(JsonPathQueryResult),
 }
 
 /// Execute the set command
-pub fn set_command(args: SetArgs) -> Result<()> {
+pub fn set_command(args: SetArgs) -> Result<i32> {
     debug!("Executing set command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
 
     // Parse value
     let value = parse_cli_value(&args.value, args.type_.map(Into::into).as_ref())?;
@@ -115,50 +146,37 @@ pub fn set_command(args: SetArgs) -> Result<()> {
         JsonPathQuery::new(&args.query)?
     };
 
-    let mut processed_count = 0;
-
-    for file in files {
+    let mut report = FailureReport::new("set", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
         let mut document = if file.exists() {
-            reader.read_file(&file)?
+            reader.read_file(file)?
         } else {
             Document::empty()
         };
 
         let modified = set_jsonpath_value(&mut document, &jsonpath_query, &value)?;
+        Ok(modified.then_some(document))
+    })?;
 
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Updated: {}", file.display());
-
-                if let Some(diff) = result.diff {
-                    if args.write_options.dry_run {
-                        println!("{}", diff);
-                    }
-                }
-            }
-        }
-    }
-
-    info!("Processed {} files", processed_count);
-    Ok(())
+    batch.write_all(&executor, documents, &mut report)?;
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the query command
-pub fn query_command(args: QueryArgs) -> Result<()> {
+pub fn query_command(args: QueryArgs) -> Result<i32> {
     debug!("Executing query command with args: {:?}", args);
 
+    let watcher = create_watcher(&args.files, &args.watch)?;
     let files = resolve_files(&args.files)?;
-    if files.is_empty() {
+    if files.is_empty() && watcher.is_none() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
-
     // Create JSONPath query
     let jsonpath_query = if args.no_auto_root {
         JsonPathQuery::new_with_options(&args.query, false)?
@@ -166,29 +184,27 @@ pub fn query_command(args: QueryArgs) -> Result<()> {
         JsonPathQuery::new(&args.query)?
     };
 
-    let mut total_matches = 0;
-    let mut any_matches = false;
+    if let Some(watcher) = watcher {
+        return watch_query(&args, &jsonpath_query, &watcher, &files);
+    }
 
-    for file in &files {
-        debug!("Processing file: {}", file.display());
+    let reader = IndexedReader::new(&args.files, args.no_index)?;
+    let mut report = FailureReport::new("query", &args.files.errors);
+    let query_results =
+        report.process(&create_executor(&args.files.processing), &files, |file| {
+            debug!("Processing file: {}", file.display());
 
-        let document = reader.read_file(file)?;
+            query_front_matter(&jsonpath_query, &reader.read(file)?)
+        })?;
+    reader.finish();
 
-        // Convert front matter to JSON for JSONPath processing
-        let front_matter = document.front_matter();
-        if front_matter.is_none() {
-            continue;
-        }
-
-        let yaml_value = YamlJsonConverter::document_front_matter_to_yaml(front_matter.unwrap());
-        let json_value = YamlJsonConverter::yaml_to_json(&yaml_value)?;
-        let located_results = jsonpath_query.query_located(&json_value);
-        let matches: Vec<_> = located_results
-            .into_iter()
-            .map(|(path, value)| (path, value.clone()))
-            .collect();
+    let mut total_matches = 0;
+    let mut any_matches = false;
 
-        let query_result = JsonPathQueryResult::new(jsonpath_query.clone(), matches);
+    for (file, query_result) in query_results {
+        let Some(query_result) = query_result else {
+            continue;
+        };
 
         if !query_result.is_empty() {
             any_matches = true;
@@ -199,31 +215,9 @@ pub fn query_command(args: QueryArgs) -> Result<()> {
                 continue;
             } else if args.exists {
                 // Just check existence, exit early on first match
-                std::process::exit(0);
-            } else if args.with_values {
-                // Show normalized paths with values
-                if files.len() > 1 {
-                    println!("{}:", file.display());
-                }
-                for line in query_result.to_internal_format() {
-                    if files.len() > 1 {
-                        println!("  {}", line);
-                    } else {
-                        println!("{}", line);
-                    }
-                }
+                return Ok(0);
             } else {
-                // Show just normalized paths
-                if files.len() > 1 {
-                    println!("{}:", file.display());
-                }
-                for path in query_result.paths() {
-                    if files.len() > 1 {
-                        println!("  {}", path);
-                    } else {
-                        println!("{}", path);
-                    }
-                }
+                print_query_result(&file, &query_result, args.with_values, files.len() > 1);
             }
         }
     }
@@ -232,27 +226,396 @@ pub fn query_command(args: QueryArgs) -> Result<()> {
         println!("{}", total_matches);
     } else if args.exists {
         // If we reach here, no matches were found
-        std::process::exit(1);
+        return Ok(1);
     } else if !any_matches {
         debug!("No matching values found");
     }
 
-    Ok(())
+    report.finish()
+}
+
+/// Query the front matter of a document, if it has any
+fn query_front_matter(
+    query: &JsonPathQuery,
+    document: &Document,
+) -> Result<Option<JsonPathQueryResult>> {
+    // Convert front matter to JSON for JSONPath processing
+    let Some(front_matter) = document.front_matter() else {
+        return Ok(None);
+    };
+
+    let yaml_value = YamlJsonConverter::document_front_matter_to_yaml(front_matter);
+    let json_value = YamlJsonConverter::yaml_to_json(&yaml_value)?;
+    let matches: Vec<_> = query
+        .query_located(&json_value)
+        .into_iter()
+        .map(|(path, value)| (path, value.clone()))
+        .collect();
+
+    Ok(Some(JsonPathQueryResult::new(query.clone(), matches)))
+}
+
+/// Print the normalized paths of a file's matches, with their values if asked
+fn print_query_result(
+    file: &Path,
+    query_result: &JsonPathQueryResult,
+    with_values: bool,
+    show_file: bool,
+) {
+    let lines = if with_values {
+        query_result.to_internal_format()
+    } else {
+        query_result.paths().into_iter().cloned().collect()
+    };
+
+    if show_file {
+        println!("{}:", file.display());
+    }
+    for line in lines {
+        if show_file {
+            println!("  {}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Run a query over all files, then again over each file that changes
+///
+/// Files are always named. With `--count`, the total over all files is
+/// printed after every run.
+fn watch_query(
+    args: &QueryArgs,
+    query: &JsonPathQuery,
+    watcher: &FileWatcher,
+    files: &[PathBuf],
+) -> Result<i32> {
+    let reader = create_reader(&args.files.processing)?;
+    let executor = create_executor(&args.files.processing);
+    let mut counts = BTreeMap::new();
+
+    let mut evaluate = |changed: &[PathBuf], removed: &[PathBuf]| {
+        let results = executor.map(changed, |file| {
+            debug!("Processing file: {}", file.display());
+            query_front_matter(query, &reader.read_front_matter_document(file)?)
+        });
+
+        for (file, result) in changed.iter().zip(results) {
+            match result {
+                Ok(Some(query_result)) if !query_result.is_empty() => {
+                    counts.insert(file.clone(), query_result.len());
+                    if !args.count {
+                        print_query_result(file, &query_result, args.with_values, true);
+                    }
+                }
+                Ok(_) => {
+                    counts.remove(file);
+                }
+                Err(e) => {
+                    counts.remove(file);
+                    error!("{}: {}", file.display(), e);
+                }
+            }
+        }
+        for file in removed {
+            counts.remove(file);
+        }
+
+        if args.count {
+            println!("{}", counts.values().sum::<usize>());
+        }
+    };
+
+    evaluate(files, &[]);
+    watch_changes(watcher, |batch| {
+        evaluate(&batch.changed, &batch.removed);
+        Ok(())
+    })
+}
+
+/// Execute the aggregate command
+pub fn aggregate_command(args: AggregateArgs) -> Result<i32> {
+    debug!("Executing aggregate command with args: {:?}", args);
+
+    if args.query.is_none() && args.group_by.is_none() {
+        return Err(MatterOfError::validation(
+            "Either --query or --group-by must be specified".to_string(),
+        ));
+    }
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let new_query = |query: &str| {
+        if args.no_auto_root {
+            JsonPathQuery::new_with_options(query, false)
+        } else {
+            JsonPathQuery::new(query)
+        }
+    };
+    let mut aggregator = Aggregator::new(args.query.as_deref().map(new_query).transpose()?);
+    if let Some(group_by) = &args.group_by {
+        aggregator = aggregator.with_group_by(new_query(group_by)?);
+    }
+
+    let reader = IndexedReader::new(&args.files, args.no_index)?;
+    let mut report = FailureReport::new("aggregate", &args.files.errors);
+    let documents = report.process(&create_executor(&args.files.processing), &files, |file| {
+        debug!("Processing file: {}", file.display());
+        reader.read(file)
+    })?;
+    reader.finish();
+    for (_, document) in &documents {
+        aggregator.add_document(document)?;
+    }
+
+    let groups = if aggregator.is_grouped() {
+        aggregator.groups().into_iter().cloned().collect()
+    } else {
+        vec![aggregator.total()]
+    };
+
+    let numeric = args.sum || args.min || args.max;
+    let skipped: usize = groups.iter().map(|group| group.non_numeric).sum();
+    if numeric && skipped > 0 {
+        warn!(
+            "Ignored {} non-numeric match(es) in --sum/--min/--max",
+            skipped
+        );
+    }
+
+    match args.format {
+        AggregateFormat::Table => print_aggregate_table(&args, &groups),
+        AggregateFormat::Yaml | AggregateFormat::Json => {
+            let output = if aggregator.is_grouped() {
+                let mut mapping = serde_yaml::Mapping::new();
+                for group in &groups {
+                    mapping.insert(group_label(group).into(), aggregate_summary(&args, group));
+                }
+                serde_yaml::Value::Mapping(mapping)
+            } else {
+                aggregate_summary(&args, &groups[0])
+            };
+
+            // Both serializers keep the mapping's insertion order
+            if args.format == AggregateFormat::Yaml {
+                print!("{}", serde_yaml::to_string(&output)?);
+            } else {
+                let output = serde_json::to_string_pretty(&output)
+                    .map_err(|e| MatterOfError::validation(e.to_string()))?;
+                println!("{}", output);
+            }
+        }
+    }
+
+    info!("Aggregated {} files", files.len());
+    report.finish()
+}
+
+/// Execute the export command
+pub fn export_command(args: ExportArgs) -> Result<i32> {
+    debug!("Executing export command with args: {:?}", args);
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let columns = args
+        .columns
+        .iter()
+        .map(|query| Column::new(query, !args.no_auto_root))
+        .collect::<Result<Vec<_>>>()?;
+    let path_column = (!args.no_path_column).then(|| args.path_column.clone());
+    let mut table = ExportTable::new(columns, path_column);
+
+    let reader = create_reader(&args.files.processing)?;
+    let mut report = FailureReport::new("export", &args.files.errors);
+    let documents = report.process(&create_executor(&args.files.processing), &files, |file| {
+        debug!("Processing file: {}", file.display());
+        reader.read_front_matter_document(file)
+    })?;
+    for (file, document) in &documents {
+        table.add_document(file, document)?;
+    }
+
+    match &args.output {
+        Some(path) => {
+            let file = std::fs::File::create(path).map_err(MatterOfError::Io)?;
+            table.write(std::io::BufWriter::new(file), args.format, &args.separator)?;
+        }
+        None => table.write(std::io::stdout().lock(), args.format, &args.separator)?,
+    }
+
+    info!("Exported {} files", table.len());
+    report.finish()
+}
+
+/// Execute the import command
+pub fn import_command(args: ImportArgs) -> Result<i32> {
+    debug!("Executing import command with args: {:?}", args);
+
+    let types = args
+        .types
+        .iter()
+        .map(|hint| {
+            let (column, type_name) = hint.rsplit_once('=').ok_or_else(|| {
+                MatterOfError::validation(format!(
+                    "Invalid --type '{}', expected COLUMN=TYPE",
+                    hint
+                ))
+            })?;
+            let value_type = matterof::core::ValueType::from_name(type_name).ok_or_else(|| {
+                MatterOfError::validation(format!("Unknown value type: {}", type_name))
+            })?;
+            Ok((column.to_string(), value_type))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let options = ImportOptions {
+        key_column: args.key_column.clone(),
+        types,
+        separator: args.separator.clone(),
+        auto_root: !args.no_auto_root,
+    };
+    let format = args.format.map(|format| match format {
+        ImportFormat::Csv => DataFormat::Delimited(b','),
+        ImportFormat::Tsv => DataFormat::Delimited(b'\t'),
+        ImportFormat::Jsonl => DataFormat::Jsonl,
+    });
+
+    let data = ImportData::from_file(&args.data, format, &options)?;
+    if data.is_empty() {
+        warn!("No rows found in {}", args.data.display());
+        return Ok(0);
+    }
+    debug!("Loaded {} rows from {}", data.len(), args.data.display());
+
+    let reader = create_reader(&args.processing)?;
+    let writer = create_writer(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
+
+    let mut report = FailureReport::new("import", &args.errors);
+    let executor = create_executor(&args.processing);
+    let files: Vec<PathBuf> = data.files().into_iter().map(Path::to_path_buf).collect();
+    let documents = report.process(&executor, &files, |file| {
+        debug!("Processing file: {}", file.display());
+
+        if !file.exists() {
+            return Err(MatterOfError::file_not_found(file));
+        }
+        let mut document = reader.read_file(file)?;
+        let changed = data
+            .apply(file, &mut document)
+            .map_err(|e| MatterOfError::validation(format!("{}: {}", args.data.display(), e)))?;
+
+        Ok(changed.then_some(document))
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+    batch.finish()?;
+    report.finish()
+}
+
+/// Execute the derive command
+pub fn derive_command(args: DeriveArgs) -> Result<i32> {
+    debug!("Executing derive command");
+
+    let mut registry = ExtractorRegistry::new();
+    registry.register(DescriptionExtractor {
+        max_length: args.description_length,
+    });
+    registry.register(ReadingTimeExtractor {
+        words_per_minute: args.words_per_minute,
+    });
+    if let Some(format) = &args.date_format {
+        check_date_format(format)?;
+        registry.register_file_extractors(args.date_format.as_deref());
+    }
+
+    if args.list {
+        for extractor in registry.iter() {
+            println!("{:<22} {}", extractor.name(), extractor.description());
+        }
+        return Ok(0);
+    }
+
+    // Fields are TARGET=EXTRACTOR, or just EXTRACTOR for a key of the same name
+    let fields = args
+        .fields
+        .iter()
+        .map(|field| {
+            let (target, name) = field.rsplit_once('=').unwrap_or((field, field));
+            let extractor = registry.get(name).ok_or_else(|| {
+                let names: Vec<_> = registry.iter().map(|e| e.name()).collect();
+                MatterOfError::validation(format!(
+                    "Unknown extractor '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                ))
+            })?;
+            Ok((JsonPathQuery::new(target)?, extractor))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let reader = create_reader(&args.files.processing)?;
+    let writer = create_writer(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
+
+    let mut report = FailureReport::new("derive", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
+        debug!("Processing file: {}", file.display());
+
+        let mut document = reader.read_file(file)?;
+        let mut modified = false;
+
+        for (target, extractor) in &fields {
+            if args.only_missing {
+                let json = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+                if target.query(&json).iter().any(|value| !value.is_null()) {
+                    debug!("Keeping existing {}", target.original());
+                    continue;
+                }
+            }
+
+            match extractor.extract_file(&document, file)? {
+                Some(value) => modified |= assign_jsonpath_value(&mut document, target, &value)?,
+                None => debug!("Nothing to derive for {}", target.original()),
+            }
+        }
+
+        Ok(modified.then_some(document))
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the add command
-pub fn add_command(args: AddArgs) -> Result<()> {
+pub fn add_command(args: AddArgs) -> Result<i32> {
     debug!("Executing add command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
 
     // Create JSONPath query
     let jsonpath_query = if args.no_auto_root {
@@ -265,13 +628,13 @@ pub fn add_command(args: AddArgs) -> Result<()> {
     let value =
         FrontMatterValue::parse_from_string(&args.value, args.type_.map(Into::into).as_ref())?;
 
-    let mut processed_count = 0;
-
-    for file in files {
+    let mut report = FailureReport::new("add", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
         let mut document = if file.exists() {
-            reader.read_file(&file)?
+            reader.read_file(file)?
         } else {
             Document::empty()
         };
@@ -290,45 +653,35 @@ pub fn add_command(args: AddArgs) -> Result<()> {
             add_jsonpath_value(&mut document, &jsonpath_query, &value, None, args.index)?
         };
 
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Updated: {}", file.display());
+        Ok(modified.then_some(document))
+    })?;
 
-                if let Some(diff) = result.diff {
-                    if args.write_options.dry_run {
-                        println!("{}", diff);
-                    }
-                }
-            }
-        }
-    }
+    batch.write_all(&executor, documents, &mut report)?;
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the remove command
-pub fn remove_command(args: RemoveArgs) -> Result<()> {
+pub fn remove_command(args: RemoveArgs) -> Result<i32> {
     debug!("Executing remove command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
-
-    let mut processed_count = 0;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
 
-    for file in files {
+    let mut report = FailureReport::new("remove", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
-        let mut document = reader.read_file(&file)?;
+        let mut document = reader.read_file(file)?;
         let mut modified = false;
 
         if args.all {
@@ -344,7 +697,12 @@ pub fn remove_command(args: RemoveArgs) -> Result<()> {
                     JsonPathQuery::new(query_str)?
                 };
 
-                modified = remove_jsonpath_value(&mut document, &jsonpath_query, &args)?;
+                let options = RemoveOptions {
+                    range: args.range.as_deref(),
+                    force: args.force,
+                    cleanup_empty: args.cleanup_empty,
+                };
+                modified = remove_jsonpath_value(&mut document, &jsonpath_query, &options)?;
             }
 
             if args.cleanup_empty {
@@ -352,38 +710,28 @@ pub fn remove_command(args: RemoveArgs) -> Result<()> {
             }
         }
 
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Updated: {}", file.display());
+        Ok(modified.then_some(document))
+    })?;
 
-                if let Some(diff) = result.diff {
-                    if args.write_options.dry_run {
-                        println!("{}", diff);
-                    }
-                }
-            }
-        }
-    }
+    batch.write_all(&executor, documents, &mut report)?;
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the replace command
-pub fn replace_command(args: ReplaceArgs) -> Result<()> {
+pub fn replace_command(args: ReplaceArgs) -> Result<i32> {
     debug!("Executing replace command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
 
     // Create JSONPath query
     let jsonpath_query = if args.no_auto_root {
@@ -392,72 +740,78 @@ pub fn replace_command(args: ReplaceArgs) -> Result<()> {
         JsonPathQuery::new(&args.query)?
     };
 
-    let mut processed_count = 0;
+    // Parse values
+    let value_type = args.type_.map(Into::into);
+    let options = ReplaceOptions {
+        new_key: args.new_key.as_deref(),
+        new_value: args
+            .new_value
+            .as_deref()
+            .map(|v| FrontMatterValue::parse_from_string(v, value_type.as_ref()))
+            .transpose()?,
+        old_value: args
+            .old_value
+            .as_deref()
+            .map(|v| FrontMatterValue::parse_from_string(v, value_type.as_ref()))
+            .transpose()?,
+    };
 
-    for file in files {
+    let mut report = FailureReport::new("replace", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
         let mut document = if file.exists() {
-            reader.read_file(&file)?
+            reader.read_file(file)?
         } else {
             Document::empty()
         };
 
-        let modified = replace_jsonpath_value(&mut document, &jsonpath_query, &args)?;
-
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Updated: {}", file.display());
+        let modified = replace_jsonpath_value(&mut document, &jsonpath_query, &options)?;
+        Ok(modified.then_some(document))
+    })?;
 
-                if let Some(diff) = result.diff {
-                    if args.write_options.dry_run {
-                        println!("{}", diff);
-                    }
-                }
-            }
-        }
-    }
+    batch.write_all(&executor, documents, &mut report)?;
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the init command
-pub fn init_command(args: InitArgs) -> Result<()> {
+pub fn init_command(args: InitArgs) -> Result<i32> {
     debug!("Executing init command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Initialized")?;
 
     // Parse default values
     let defaults = parse_default_values(&args.defaults)?;
+    let template = args.template.as_deref().map(load_template).transpose()?;
 
-    let mut processed_count = 0;
-
-    for file in files {
+    let mut report = FailureReport::new("init", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
         let mut document = if file.exists() {
-            reader.read_file(&file)?
+            reader.read_file(file)?
         } else {
             Document::empty()
         };
 
         let needs_init = !document.has_front_matter();
         if args.only_missing && document.has_front_matter() {
-            continue;
+            return Ok(None);
         }
 
-        if needs_init || !defaults.is_empty() {
+        if needs_init || !defaults.is_empty() || template.is_some() {
             document.ensure_front_matter();
 
             // Add default values
@@ -467,38 +821,77 @@ pub fn init_command(args: InitArgs) -> Result<()> {
                 }
             }
 
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Initialized: {}", file.display());
+            // Fill in what the template has and the file lacks
+            if let Some(template) = &template {
+                let rendered = template.render(&TemplateContext::new(file), &document)?;
+                document.merge_missing_front_matter(&rendered);
             }
+
+            return Ok(Some(document));
         }
+        Ok(None)
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
+}
+
+/// Execute the new command
+pub fn new_command(args: NewArgs) -> Result<i32> {
+    debug!("Executing new command");
+
+    if args.file.exists() && !args.force {
+        return Err(MatterOfError::validation(format!(
+            "{} already exists (use --force to overwrite it)",
+            args.file.display()
+        )));
     }
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    let template = load_template(&args.template)?;
+
+    // Values given on the command line win over the template's
+    let mut document = Document::empty();
+    for (key_path, value) in parse_default_values(&args.values)? {
+        document.set(&key_path, value)?;
+    }
+    let rendered = template.render(&TemplateContext::new(&args.file), &document)?;
+    document.merge_missing_front_matter(&rendered);
+    document.set_body(rendered.body().to_string());
+
+    let writer = create_writer(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Created")?;
+    if args.force {
+        batch.write(&document, &args.file)?;
+    } else {
+        batch.create(&document, &args.file)?;
+    }
+
+    batch.finish()?;
+    Ok(0)
 }
 
 /// Execute the clean command
-pub fn clean_command(args: CleanArgs) -> Result<()> {
+pub fn clean_command(args: CleanArgs) -> Result<i32> {
     debug!("Executing clean command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
     let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Cleaned")?;
 
-    let mut processed_count = 0;
-
-    for file in files {
+    let mut report = FailureReport::new("clean", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
         debug!("Processing file: {}", file.display());
 
-        let mut document = reader.read_file(&file)?;
+        let mut document = reader.read_file(file)?;
         let mut modified = false;
 
         if document.has_front_matter() {
@@ -524,138 +917,637 @@ pub fn clean_command(args: CleanArgs) -> Result<()> {
             }
         }
 
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Cleaned: {}", file.display());
-            }
-        }
-    }
+        Ok(modified.then_some(document))
+    })?;
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
 }
 
 /// Execute the validate command
-pub fn validate_command(args: ValidateArgs) -> Result<()> {
+pub fn validate_command(args: ValidateArgs) -> Result<i32> {
     debug!("Executing validate command");
 
+    let watcher = create_watcher(&args.files, &args.watch)?;
     let files = resolve_files(&args.files)?;
-    if files.is_empty() {
+    if files.is_empty() && watcher.is_none() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
-    let mut validation_results = Vec::new();
-    let mut error_count = 0;
-
-    for file in files {
+    let reader = create_reader(&args.files.processing)?;
+    let schema = args
+        .schema
+        .as_ref()
+        .map(SchemaValidator::from_file)
+        .transpose()?;
+    let validate = |file: &PathBuf| {
         debug!("Validating file: {}", file.display());
 
-        let result = reader.read_file(&file);
-        match result {
-            Ok(document) => {
-                if let Err(validation_error) = document.validate() {
-                    if args.fail_fast {
-                        return Err(MatterOfError::validation(format!(
-                            "Validation failed for {}: {}",
-                            file.display(),
-                            validation_error
-                        )));
-                    }
-                    validation_results.push((file.clone(), Err(validation_error.clone())));
-                    error_count += 1;
-                } else {
-                    validation_results.push((file.clone(), Ok(())));
+        reader
+            .read_front_matter_document(file)
+            .and_then(|document| {
+                document.validate()?;
+                match &schema {
+                    Some(schema) => schema.validate_document(&document),
+                    None => Ok(Vec::new()),
                 }
+            })
+    };
+    let executor = create_executor(&args.files.processing);
+
+    if let Some(watcher) = watcher {
+        // Failing files are reported as they change, never ending the run
+        let mut failing = BTreeSet::new();
+        let mut evaluate = |changed: &[PathBuf], removed: &[PathBuf]| -> Result<()> {
+            let results: Vec<_> = changed
+                .iter()
+                .cloned()
+                .zip(executor.map(changed, validate))
+                .collect();
+            if !results.is_empty() {
+                output_validation_results(&results, &args.format)?;
             }
-            Err(error) => {
-                if args.fail_fast {
-                    return Err(MatterOfError::validation(format!(
-                        "Failed to read {}: {}",
-                        file.display(),
-                        error
-                    )));
+
+            for (file, result) in results {
+                if matches!(result, Ok(violations) if violations.is_empty()) {
+                    failing.remove(&file);
+                } else {
+                    failing.insert(file);
                 }
-                validation_results.push((file.clone(), Err(error)));
-                error_count += 1;
             }
+            for file in removed {
+                failing.remove(file);
+            }
+
+            match failing.len() {
+                0 => info!("All files pass validation"),
+                count => warn!("{} files failing validation", count),
+            }
+            Ok(())
+        };
+
+        evaluate(&files, &[])?;
+        return watch_changes(&watcher, |batch| evaluate(&batch.changed, &batch.removed));
+    }
+
+    let results = executor.map(&files, validate);
+    let mut report = FailureReport::new("validate", &args.files.errors);
+    report.add_processed(files.len());
+    let mut validation_results = Vec::new();
+    let mut error_count = 0;
+
+    for (file, result) in files.into_iter().zip(results) {
+        let failed = !matches!(&result, Ok(violations) if violations.is_empty());
+        if failed && args.fail_fast {
+            let reason = match &result {
+                Ok(violations) => violations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                Err(error) => error.to_string(),
+            };
+            return Err(MatterOfError::validation(format!(
+                "Validation failed for {}: {}",
+                file.display(),
+                reason
+            )));
         }
+        if failed {
+            error_count += 1;
+            let failure = match &result {
+                Ok(violations) => {
+                    MatterOfError::validation(format!("{} schema violation(s)", violations.len()))
+                }
+                Err(error) => error.clone(),
+            };
+            report.record(&file, failure);
+        }
+        validation_results.push((file, result));
     }
 
     // Output results
     output_validation_results(&validation_results, &args.format)?;
 
-    if error_count > 0 {
+    if error_count > 0 && !report.keep_going() {
         return Err(MatterOfError::validation(format!(
             "{} files failed validation",
             error_count
         )));
     }
+    let status = report.finish()?;
+    if status != 0 {
+        return Ok(status);
+    }
 
     info!("All {} files passed validation", validation_results.len());
-    Ok(())
+    Ok(0)
 }
 
 /// Execute the format command
-pub fn format_command(args: FormatArgs) -> Result<()> {
+pub fn format_command(args: FormatArgs) -> Result<i32> {
     debug!("Executing format command");
 
     let files = resolve_files(&args.files)?;
     if files.is_empty() {
         warn!("No files found to process");
-        return Ok(());
+        return Ok(0);
+    }
+
+    let reader = create_reader(&args.files.processing)?;
+    // Formatting is the one operation that re-renders the front matter from scratch
+    let writer = create_writer_with_config(
+        &args.write_options,
+        WriterConfig {
+            preserve_formatting: false,
+            indent: args.indent,
+            ..create_writer_config(&args.write_options)
+        },
+    )?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Formatted")?;
+
+    let mut report = FailureReport::new("format", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
+        debug!("Processing file: {}", file.display());
+
+        let mut document = reader.read_file(file)?;
+        let mut modified = false;
+
+        if document.has_front_matter() {
+            if args.sort_keys {
+                let mut sorted: IndexMap<_, _> = document
+                    .front_matter()
+                    .map(|fm| {
+                        fm.iter()
+                            .map(|(k, v)| {
+                                (
+                                    k.clone(),
+                                    FrontMatterValue::new(sort_yaml_keys(v.as_inner())),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                sorted.sort_keys();
+                document.replace_front_matter(sorted);
+                document.discard_source();
+            }
+
+            if args.remove_null {
+                // Remove null values
+                let query = Query::new()
+                    .and_custom(|_key, value| value.is_null())
+                    .combine_with(matterof::core::CombineMode::Any);
+
+                let null_matches = document.query(&query);
+                let keys_to_remove: Vec<_> = null_matches.matches().keys().cloned().collect();
+                for key_path in keys_to_remove {
+                    document.remove(&key_path)?;
+                }
+            }
+
+            modified = true; // Always consider formatting as a modification
+        }
+
+        Ok(modified.then_some(document))
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
+}
+
+/// Execute the convert command
+pub fn convert_command(args: ConvertArgs) -> Result<i32> {
+    debug!("Executing convert command");
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let reader = create_reader(&args.files.processing)?;
+    let writer = create_writer(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Converted")?;
+    let target: matterof::core::FrontMatterFormat = args.to.into();
+
+    // Convert everything up front so one unrepresentable file leaves the tree untouched
+    let mut report = FailureReport::new("convert", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
+        debug!("Converting file: {}", file.display());
+
+        let mut document = reader.read_file(file)?;
+        if !document.has_front_matter() || document.format() == target {
+            return Ok(None);
+        }
+
+        document.convert_to(target).map_err(|e| {
+            MatterOfError::validation(format!(
+                "Cannot convert {} to {}: {}",
+                file.display(),
+                target,
+                e
+            ))
+        })?;
+        Ok(Some(document))
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
+}
+
+/// Execute the apply command
+pub fn apply_command(args: ApplyArgs) -> Result<i32> {
+    debug!("Executing apply command");
+
+    let script = Script::from_file(&args.script)?;
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let reader = create_reader(&args.files.processing)?;
+    let writer = create_writer(&args.write_options)?;
+    let mut batch = BatchWriter::new(&writer, &args.write_options, "Updated")?;
+
+    let mut report = FailureReport::new("apply", &args.files.errors);
+    let executor = create_executor(&args.files.processing);
+    let documents = report.process(&executor, &files, |file| {
+        debug!("Processing file: {}", file.display());
+
+        let mut document = reader.read_file(file)?;
+        let changed = script
+            .apply(&mut document)
+            .map_err(|e| MatterOfError::validation(format!("{}: {}", file.display(), e)))?;
+
+        debug!(
+            "{} of {} operations changed {}",
+            changed,
+            script.len(),
+            file.display()
+        );
+        Ok((changed > 0).then_some(document))
+    })?;
+
+    batch.write_all(&executor, documents, &mut report)?;
+
+    batch.finish()?;
+    report.finish()
+}
+
+/// Execute the diff command
+pub fn diff_command(args: DiffArgs) -> Result<i32> {
+    debug!("Executing diff command");
+
+    // Bodies are never compared
+    let reader = FrontMatterReader::new();
+    let old = reader.read_front_matter_document(&args.old)?;
+    let new = reader.read_front_matter_document(&args.new)?;
+
+    let mut diff = FrontMatterDiff::between(&old, &new);
+
+    // Ignored paths are resolved in both documents, so removed and added keys match too
+    let mut ignored = Vec::new();
+    for query in &args.ignore {
+        let query = JsonPathQuery::new(query)?;
+        for document in [&old, &new] {
+            let json = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+            ignored.extend(
+                query
+                    .query_located(&json)
+                    .into_iter()
+                    .map(|(path, _)| NormalizedPathUtils::to_string(&path)),
+            );
+        }
+    }
+    diff.ignore_paths(&ignored);
+    if args.keys_only {
+        diff.retain(|change| change.kind != ChangeKind::Changed);
+    }
+
+    match args.format {
+        DiffOutputFormat::Human => {
+            for change in &diff {
+                println!("{}", change);
+            }
+        }
+        DiffOutputFormat::Json => {
+            let output = serde_json::json!({
+                "old": args.old.display().to_string(),
+                "new": args.new.display().to_string(),
+                "changes": diff,
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&output)
+                    .map_err(|e| MatterOfError::validation(e.to_string()))?
+            );
+        }
+    }
+
+    if !diff.is_empty() {
+        info!(
+            "{} difference(s) between {} and {}",
+            diff.len(),
+            args.old.display(),
+            args.new.display()
+        );
+        return Ok(1);
+    }
+
+    Ok(0)
+}
+
+/// Execute the undo command
+pub fn undo_command(args: UndoArgs) -> Result<i32> {
+    debug!("Executing undo command");
+
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let journal = Journal::discover(&cwd);
+    let run = match &args.run_id {
+        Some(id) => journal.run(id)?,
+        None => journal.latest()?.ok_or_else(|| {
+            MatterOfError::validation(format!(
+                "Nothing to undo in journal {} (runs are recorded with --journal)",
+                journal.dir().display()
+            ))
+        })?,
+    };
+
+    let options = UndoOptions {
+        force: args.force,
+        dry_run: args.dry_run,
+    };
+    let report = journal.undo(&run, options)?;
+
+    for path in &report.conflicts {
+        warn!("Overwriting changes made since the run: {}", path.display());
+    }
+    let verb = if args.dry_run {
+        "Would restore"
+    } else {
+        "Restored"
+    };
+    for path in &report.restored {
+        info!("{}: {}", verb, path.display());
+    }
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    for path in &report.removed {
+        info!("{}: {}", verb, path.display());
+    }
+
+    info!(
+        "Undid run {} ({} files)",
+        run.id,
+        report.restored.len() + report.removed.len()
+    );
+    Ok(0)
+}
+
+/// Execute the history command
+pub fn history_command(args: HistoryArgs) -> Result<i32> {
+    debug!("Executing history command");
+
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let runs = Journal::discover(&cwd).runs()?;
+    let skip = args
+        .limit
+        .map(|limit| runs.len().saturating_sub(limit))
+        .unwrap_or(0);
+
+    for run in runs.iter().skip(skip).rev() {
+        println!(
+            "{}  {}  {} file(s){}  {}",
+            run.id,
+            run.timestamp,
+            run.files.len(),
+            if run.is_undone() { " (undone)" } else { "" },
+            run.command.join(" ")
+        );
+        if args.files {
+            for file in &run.files {
+                println!("    {}", file.path.display());
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+/// Execute the schema command
+pub fn schema_command(args: SchemaArgs) -> Result<i32> {
+    match args.command {
+        SchemaCommands::Infer(args) => schema_infer_command(args),
+    }
+}
+
+/// Execute the schema infer command
+fn schema_infer_command(args: SchemaInferArgs) -> Result<i32> {
+    debug!("Executing schema infer command");
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let reader = create_reader(&args.files.processing)?;
+    let mut inferrer = SchemaInferrer::new().with_max_enum(args.max_enum);
+
+    let mut report = FailureReport::new("schema infer", &args.files.errors);
+    let documents = report.process(&create_executor(&args.files.processing), &files, |file| {
+        debug!("Processing file: {}", file.display());
+        reader.read_front_matter_document(file)
+    })?;
+    for (file, document) in &documents {
+        inferrer.add_document(file.display().to_string(), document);
+    }
+
+    let output = serde_json::to_string_pretty(&inferrer.to_schema())
+        .map_err(|e| MatterOfError::validation(e.to_string()))?;
+    match &args.output {
+        Some(path) => std::fs::write(path, output + "\n").map_err(MatterOfError::Io)?,
+        None => println!("{}", output),
+    }
+
+    for disagreement in inferrer.disagreements() {
+        warn!("{}", disagreement);
+    }
+
+    info!("Inferred schema from {} files", inferrer.document_count());
+    report.finish()
+}
+
+/// Execute the index command
+pub fn index_command(args: IndexArgs) -> Result<i32> {
+    match args.command {
+        IndexCommands::Build(args) => index_build_command(args),
+        IndexCommands::Update(args) => index_update_command(args),
+        IndexCommands::Status(args) => index_status_command(args),
+    }
+}
+
+/// Execute the index build command
+fn index_build_command(args: IndexFilesArgs) -> Result<i32> {
+    debug!("Executing index build command");
+
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
     }
 
-    let reader = create_reader(&args.files)?;
-    let writer = create_writer(&args.write_options)?;
-    let write_options = create_write_options(&args.write_options)?;
+    let reader = create_reader(&args.files.processing)?;
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let mut index = FrontMatterIndex::discover(&cwd);
+    let mut report = FailureReport::new("index build", &args.files.errors);
+    let entries = report.process(&create_executor(&args.files.processing), &files, |file| {
+        debug!("Indexing file: {}", file.display());
+        IndexEntry::from_file(&reader, file)
+    })?;
 
-    let mut processed_count = 0;
+    for (file, entry) in entries {
+        index.insert(&file, entry);
+    }
+    index.save()?;
+
+    info!(
+        "Indexed {} files in {}",
+        index.len(),
+        index.path().display()
+    );
+    report.finish()
+}
 
-    for file in files {
-        debug!("Processing file: {}", file.display());
+/// Execute the index update command
+fn index_update_command(args: IndexFilesArgs) -> Result<i32> {
+    debug!("Executing index update command");
 
-        let mut document = reader.read_file(&file)?;
-        let mut modified = false;
+    let files = resolve_files(&args.files)?;
+    let reader = create_reader(&args.files.processing)?;
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let mut index = FrontMatterIndex::discover(&cwd).load()?;
+    let mut report = FailureReport::new("index update", &args.files.errors);
+    let updates = report.process(&create_executor(&args.files.processing), &files, |file| {
+        index.read(&reader, file).map(|(_, update)| update)
+    })?;
 
-        if document.has_front_matter() {
-            if args.remove_null {
-                // Remove null values
-                let query = Query::new()
-                    .and_custom(|_key, value| value.is_null())
-                    .combine_with(matterof::core::CombineMode::Any);
+    let mut refreshed = 0;
+    for (file, update) in updates {
+        if let Some(entry) = update {
+            debug!("Re-indexed: {}", file.display());
+            index.insert(&file, entry);
+            refreshed += 1;
+        }
+    }
+    let removed = index.remove_deleted();
+    for file in &removed {
+        debug!("Dropped deleted file: {}", file.display());
+    }
+    index.save()?;
+
+    info!(
+        "Re-indexed {} of {} files, dropped {} deleted files",
+        refreshed,
+        files.len(),
+        removed.len()
+    );
+    report.finish()
+}
 
-                let null_matches = document.query(&query);
-                let keys_to_remove: Vec<_> = null_matches.matches().keys().cloned().collect();
-                for key_path in keys_to_remove {
-                    document.remove(&key_path)?;
-                }
-            }
+/// Execute the index status command
+fn index_status_command(args: IndexFilesArgs) -> Result<i32> {
+    debug!("Executing index status command");
 
-            // Note: Key sorting and indentation would be handled by the writer's YAML formatter
-            // This is a simplified implementation
-            modified = true; // Always consider formatting as a modification
-        }
+    let files = resolve_files(&args.files)?;
+    if files.is_empty() {
+        warn!("No files found to process");
+        return Ok(0);
+    }
+
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let index = FrontMatterIndex::discover(&cwd).load()?;
+    if !index.exists() {
+        info!(
+            "No index at {}; create one with `matterof index build`",
+            index.path().display()
+        );
+    }
+
+    let mut report = FailureReport::new("index status", &args.files.errors);
+    let statuses = report.process(&create_executor(&args.files.processing), &files, |file| {
+        index.status(file)
+    })?;
 
-        if modified {
-            let result = writer.write_file(&document, &file, Some(write_options.clone()))?;
-            if result.modified {
-                processed_count += 1;
-                info!("Formatted: {}", file.display());
+    let (mut stale, mut missing) = (0, 0);
+    for (file, status) in &statuses {
+        match status {
+            EntryStatus::Fresh => {}
+            EntryStatus::Stale => {
+                stale += 1;
+                println!("stale    {}", index.key(file));
+            }
+            EntryStatus::Missing => {
+                missing += 1;
+                println!("new      {}", index.key(file));
             }
         }
     }
 
-    info!("Processed {} files", processed_count);
-    Ok(())
+    // Every path is printed relative to the project root, like the index keys
+    let deleted: Vec<_> = index.files().filter(|file| !file.is_file()).collect();
+    for file in &deleted {
+        println!("deleted  {}", index.key(file));
+    }
+
+    info!(
+        "{} fresh, {} stale, {} new, {} deleted",
+        statuses.len() - stale - missing,
+        stale,
+        missing,
+        deleted.len()
+    );
+    report.finish()
 }
 
 // Helper functions
 
-fn resolve_files(file_options: &CommonFileOptions) -> Result<Vec<std::path::PathBuf>> {
+/// Recursively sort the keys of all nested mappings
+fn sort_yaml_keys(value: &serde_yaml::Value) -> serde_yaml::Value {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(k, _)| serde_yaml::to_string(k).unwrap_or_default());
+            serde_yaml::Value::Mapping(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_yaml_keys(v)))
+                    .collect(),
+            )
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            serde_yaml::Value::Sequence(seq.iter().map(sort_yaml_keys).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+fn create_resolver(file_options: &CommonFileOptions) -> FileResolver {
     let config = ResolverConfig {
         follow_links: file_options.follow_links,
         max_depth: file_options.max_depth,
@@ -669,8 +1561,11 @@ fn resolve_files(file_options: &CommonFileOptions) -> Result<Vec<std::path::Path
         ..Default::default()
     };
 
-    let resolver = FileResolver::with_config(config);
-    let resolved = resolver.resolve_paths(&file_options.files)?;
+    FileResolver::with_config(config)
+}
+
+fn resolve_files(file_options: &CommonFileOptions) -> Result<Vec<std::path::PathBuf>> {
+    let resolved = create_resolver(file_options).resolve_paths(&file_options.files)?;
 
     Ok(resolved
         .into_iter()
@@ -678,18 +1573,148 @@ fn resolve_files(file_options: &CommonFileOptions) -> Result<Vec<std::path::Path
         .collect())
 }
 
-fn create_reader(_file_options: &CommonFileOptions) -> Result<FrontMatterReader> {
+/// Watcher for `--watch`, started before the first run so that no change is
+/// missed while it runs
+fn create_watcher(
+    file_options: &CommonFileOptions,
+    watch: &WatchOptions,
+) -> Result<Option<FileWatcher>> {
+    if !watch.watch {
+        return Ok(None);
+    }
+
+    let watcher = FileWatcher::new(&file_options.files, create_resolver(file_options))?
+        .with_debounce(Duration::from_millis(watch.debounce));
+    Ok(Some(watcher))
+}
+
+/// Hand every batch of changes to `f`, until interrupted
+fn watch_changes(
+    watcher: &FileWatcher,
+    mut f: impl FnMut(&WatchBatch) -> Result<()>,
+) -> Result<i32> {
+    info!("Watching for changes (Ctrl-C to stop)");
+    loop {
+        let batch = watcher.next_batch()?;
+        for file in &batch.removed {
+            info!("Removed: {}", file.display());
+        }
+        f(&batch)?;
+    }
+}
+
+/// Reads front matter for get, query and aggregate, going through the
+/// project's front matter index when one has been built
+struct IndexedReader {
+    reader: FrontMatterReader,
+    index: Option<FrontMatterIndex>,
+    /// Entries of files parsed again, stored when the command is done
+    updates: Mutex<Vec<(PathBuf, IndexEntry)>>,
+}
+
+impl IndexedReader {
+    fn new(file_options: &CommonFileOptions, no_index: bool) -> Result<Self> {
+        let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+        let index = FrontMatterIndex::discover(&cwd);
+        let index = if no_index || !index.exists() {
+            None
+        } else {
+            match index.load() {
+                Ok(index) => Some(index),
+                Err(e) => {
+                    warn!("Ignoring the front matter index: {}", e);
+                    None
+                }
+            }
+        };
+
+        Ok(Self {
+            reader: create_reader(&file_options.processing)?,
+            index,
+            updates: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Front matter of a file, with an empty body
+    fn read(&self, file: &Path) -> Result<Document> {
+        let Some(index) = &self.index else {
+            return self.reader.read_front_matter_document(file);
+        };
+
+        let (document, update) = index.read(&self.reader, file)?;
+        if let Some(entry) = update {
+            self.updates
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((file.to_path_buf(), entry));
+        }
+        Ok(document)
+    }
+
+    /// Store the entries of files that were parsed again
+    ///
+    /// Entries of deleted files are dropped whenever the index is rewritten.
+    fn finish(self) {
+        let Some(mut index) = self.index else {
+            return;
+        };
+        let updates = self
+            .updates
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        if updates.is_empty() {
+            return;
+        }
+
+        debug!("Refreshing {} index entries", updates.len());
+        for (file, entry) in updates {
+            index.insert(&file, entry);
+        }
+        for file in index.remove_deleted() {
+            debug!("Dropped deleted file: {}", file.display());
+        }
+        if let Err(e) = index.save() {
+            warn!("Cannot update the front matter index: {}", e);
+        }
+    }
+}
+
+fn create_executor(processing: &ProcessingOptions) -> ParallelExecutor {
+    ParallelExecutor::new(processing.jobs)
+}
+
+fn create_reader(processing: &ProcessingOptions) -> Result<FrontMatterReader> {
     let config = ReaderConfig {
         preserve_original: false, // We don't need original content for most operations
         validate_on_read: true,
-        max_file_size: Some(10 * 1024 * 1024), // 10MB limit
+        // 10MB limit unless configured otherwise
+        max_file_size: Some(processing.max_file_size.unwrap_or(10 * 1024 * 1024)),
     };
 
     Ok(FrontMatterReader::with_config(config))
 }
 
 fn create_writer(write_options: &WriteOptions) -> Result<FrontMatterWriter> {
-    let config = WriterConfig {
+    create_writer_with_config(write_options, create_writer_config(write_options))
+}
+
+/// Create a writer, recording the run in the undo journal when it writes files
+fn create_writer_with_config(
+    write_options: &WriteOptions,
+    config: WriterConfig,
+) -> Result<FrontMatterWriter> {
+    let writer = FrontMatterWriter::with_config(config);
+    if !write_options.journal || write_options.dry_run || write_options.stdout {
+        return Ok(writer);
+    }
+
+    let cwd = std::env::current_dir().map_err(MatterOfError::Io)?;
+    let recorder = Journal::discover(&cwd).start_run(std::env::args().collect())?;
+    Ok(writer.with_journal(recorder))
+}
+
+fn create_writer_config(write_options: &WriteOptions) -> WriterConfig {
+    WriterConfig {
         backup_enabled: write_options.backup_suffix.is_some() || write_options.backup_dir.is_some(),
         backup_suffix: write_options.backup_suffix.clone(),
         backup_dir: write_options.backup_dir.clone(),
@@ -699,9 +1724,10 @@ fn create_writer(write_options: &WriteOptions) -> Result<FrontMatterWriter> {
             .line_endings
             .map(Into::into)
             .unwrap_or(matterof::io::LineEndings::Preserve),
-    };
-
-    Ok(FrontMatterWriter::with_config(config))
+        preserve_formatting: true,
+        indent: 2,
+        diff_context: write_options.diff_context.unwrap_or(3),
+    }
 }
 
 fn create_write_options(write_options: &WriteOptions) -> Result<LibWriteOptions> {
@@ -730,8 +1756,254 @@ fn create_write_options(write_options: &WriteOptions) -> Result<LibWriteOptions>
     })
 }
 
+/// Writes the documents of a command one by one, or stages them for a single
+/// all-or-nothing commit when `--transactional` is given
+struct BatchWriter<'a> {
+    writer: &'a FrontMatterWriter,
+    options: LibWriteOptions,
+    transaction: Option<Transaction<'a>>,
+    report: BatchReport,
+    /// Log label for modified files, e.g. "Updated"
+    verb: &'static str,
+    /// How dry runs show changes
+    diff_format: DiffFormat,
+}
+
+impl<'a> BatchWriter<'a> {
+    fn new(
+        writer: &'a FrontMatterWriter,
+        write_options: &WriteOptions,
+        verb: &'static str,
+    ) -> Result<Self> {
+        Ok(Self {
+            writer,
+            options: create_write_options(write_options)?,
+            transaction: write_options.transactional.then(|| writer.transaction()),
+            report: BatchReport::new(),
+            verb,
+            diff_format: write_options.diff_format,
+        })
+    }
+
+    /// Whether dry runs print the writer's unified diff
+    fn prints_unified_diff(&self) -> bool {
+        self.options.dry_run && self.diff_format == DiffFormat::Unified
+    }
+
+    /// Write (or stage) a document
+    fn write(&mut self, document: &Document, file: &Path) -> Result<()> {
+        if self.options.dry_run && self.diff_format != DiffFormat::Unified {
+            print_semantic_diff(file, document, self.diff_format)?;
+        }
+
+        match &mut self.transaction {
+            Some(transaction) => transaction.stage(document, file, Some(self.options.clone())),
+            None => {
+                let result = self
+                    .writer
+                    .write_file(document, file, Some(self.options.clone()))?;
+                log_write_result(self.verb, file, &result, self.prints_unified_diff());
+                self.report.push(file, result);
+                Ok(())
+            }
+        }
+    }
+
+    /// Write (or stage) the documents that changed, in order
+    ///
+    /// Files without a document are unchanged and skipped. The others are
+    /// written concurrently unless they join a transaction or a dry run
+    /// prints path-level changes; either way they are logged in order. A
+    /// failing file does not stop the others; failures go to the report.
+    fn write_all(
+        &mut self,
+        executor: &ParallelExecutor,
+        documents: Vec<(PathBuf, Option<Document>)>,
+        report: &mut FailureReport,
+    ) -> Result<()> {
+        let documents: Vec<_> = documents
+            .into_iter()
+            .filter_map(|(file, document)| Some((file, document?)))
+            .collect();
+
+        let semantic_dry_run = self.options.dry_run && self.diff_format != DiffFormat::Unified;
+        if self.transaction.is_some() || semantic_dry_run {
+            let results: Vec<_> = documents
+                .iter()
+                .map(|(file, document)| (file.clone(), self.write(document, file)))
+                .collect();
+            report.collect(results)?;
+
+            // With --keep-going a transaction still writes all files or none
+            if report.has_failures() {
+                if let Some(transaction) = self.transaction.take() {
+                    transaction.rollback();
+                    error!("Rolled back the transaction: no file was written");
+                }
+            }
+            return Ok(());
+        }
+
+        let (writer, options) = (self.writer, &self.options);
+        let results = executor.map(&documents, |(file, document)| {
+            writer.write_file(document, file, Some(options.clone()))
+        });
+
+        let print_diff = self.prints_unified_diff();
+        let mut failures = Vec::new();
+        for ((file, _), result) in documents.into_iter().zip(results) {
+            match result {
+                Ok(result) => {
+                    log_write_result(self.verb, &file, &result, print_diff);
+                    self.report.push(file, result);
+                }
+                Err(error) => failures.push((file, Err::<(), _>(error))),
+            }
+        }
+        report.collect(failures).map(drop)
+    }
+
+    /// Write a document to a new file, failing if it already exists
+    ///
+    /// Creating a file never joins the transaction: there is nothing to roll back.
+    fn create(&mut self, document: &Document, file: &Path) -> Result<()> {
+        if self.options.dry_run && self.diff_format != DiffFormat::Unified {
+            print_semantic_diff(file, document, self.diff_format)?;
+        }
+
+        let result = self
+            .writer
+            .create_file(document, file, Some(self.options.clone()))?;
+        log_write_result(self.verb, file, &result, self.prints_unified_diff());
+        self.report.push(file, result);
+        Ok(())
+    }
+
+    /// Commit the transaction, if any, and log the batch summary
+    fn finish(self) -> Result<BatchReport> {
+        let print_diff = self.prints_unified_diff();
+        let report = match self.transaction {
+            Some(transaction) => {
+                let staged = transaction.staged_count();
+                let report = transaction.commit()?;
+                for (file, result) in &report.results {
+                    log_write_result(self.verb, file, result, print_diff);
+                }
+                if staged > 0 {
+                    info!("Committed {} files in one transaction", staged);
+                }
+                report
+            }
+            None => self.report,
+        };
+
+        info!("Processed {} files", report.modified_count());
+        let backups = report.backup_paths().count();
+        if backups > 0 {
+            info!("Created {} backup files", backups);
+        }
+        if let Some(journal) = self.writer.journal() {
+            if journal.recorded_count() > 0 {
+                info!(
+                    "Recorded run {}; revert it with `matterof undo {}`",
+                    journal.run_id(),
+                    journal.run_id()
+                );
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Log a modified file, printing its unified diff if requested
+fn log_write_result(verb: &str, file: &Path, result: &WriteResult, print_diff: bool) {
+    if !result.modified {
+        return;
+    }
+    info!("{}: {}", verb, file.display());
+    if print_diff {
+        if let Some(diff) = &result.diff {
+            println!("{}", diff);
+        }
+    }
+}
+
+/// Print the path-level changes a dry run would make to a file
+fn print_semantic_diff(file: &Path, document: &Document, format: DiffFormat) -> Result<()> {
+    let original = if file.exists() {
+        FrontMatterReader::new().read_file(file)?
+    } else {
+        Document::empty()
+    };
+    let diff = FrontMatterDiff::between(&original, document);
+
+    match format {
+        DiffFormat::Json => {
+            let output = serde_json::json!({
+                "file": file.display().to_string(),
+                "changes": diff,
+            });
+            println!("{}", output);
+        }
+        _ if diff.is_empty() => {}
+        _ => {
+            println!("{}:", file.display());
+            for change in &diff {
+                println!("  {}", change);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Set a value at every match, creating simple key paths like `author.name` when missing
+///
+/// Unlike [`set_jsonpath_value`], matches that already hold the value do not
+/// count as a change.
+pub(crate) fn assign_jsonpath_value(
+    document: &mut Document,
+    jsonpath_query: &JsonPathQuery,
+    new_value: &FrontMatterValue,
+) -> Result<bool> {
+    let json_value = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+    let new_json_value = YamlJsonConverter::front_matter_to_json(new_value)?;
+    let matches = jsonpath_query.query(&json_value);
+
+    if matches.is_empty() {
+        return match creatable_key_path(jsonpath_query) {
+            Some(key_path) => document.set(&key_path, new_value.clone()).map(|_| true),
+            None => Ok(false),
+        };
+    }
+    if matches.iter().all(|current| **current == new_json_value) {
+        return Ok(false);
+    }
+    set_jsonpath_value(document, jsonpath_query, new_value)
+}
+
+/// Key path of a query made only of dotted names, which can be created when missing
+fn creatable_key_path(jsonpath_query: &JsonPathQuery) -> Option<KeyPath> {
+    let original = jsonpath_query.original();
+    let name = match original.strip_prefix("$.") {
+        Some(name) => name,
+        None if jsonpath_query.was_auto_prepended() => original,
+        None => return None,
+    };
+    let simple = name.split('.').all(|part| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    });
+    if simple {
+        KeyPath::parse(name).ok()
+    } else {
+        None
+    }
+}
+
 /// Set a value in a document using JSONPath
-fn set_jsonpath_value(
+pub(crate) fn set_jsonpath_value(
     document: &mut Document,
     jsonpath_query: &JsonPathQuery,
     new_value: &FrontMatterValue,
@@ -754,7 +2026,7 @@ fn set_jsonpath_value(
     // Collect the path strings to avoid borrowing issues
     let path_strings: Vec<String> = located_results
         .into_iter()
-        .map(|(path, _)| path.to_string())
+        .map(|(path, _)| NormalizedPathUtils::to_string(&path))
         .collect();
 
     // Now work with a fresh mutable copy of the JSON
@@ -769,17 +2041,28 @@ fn set_jsonpath_value(
     // Convert back to YAML and update document
     let updated_yaml = YamlJsonConverter::json_to_yaml(&json_value)?;
     let updated_front_matter = YamlJsonConverter::yaml_to_document_front_matter(&updated_yaml)?;
-    *document = Document::new(Some(updated_front_matter), document.body().to_string());
+    document.replace_front_matter(updated_front_matter);
 
     Ok(true)
 }
 
+/// Options for removing values with JSONPath
+#[derive(Debug, Default)]
+pub(crate) struct RemoveOptions<'a> {
+    /// Range of array elements to remove ("start:end")
+    pub range: Option<&'a str>,
+    /// Confirm bulk removal
+    pub force: bool,
+    /// Remove empty parent containers afterwards
+    pub cleanup_empty: bool,
+}
+
 /// Set a JSON value at a specific normalized path string
 /// Remove a value using JSONPath semantics
-fn remove_jsonpath_value(
+pub(crate) fn remove_jsonpath_value(
     document: &mut Document,
     jsonpath_query: &JsonPathQuery,
-    args: &RemoveArgs,
+    args: &RemoveOptions,
 ) -> Result<bool> {
     // Ensure document has front matter
     document.ensure_front_matter();
@@ -797,7 +2080,7 @@ fn remove_jsonpath_value(
     }
 
     // Handle range removal for arrays if specified
-    if let Some(range_str) = &args.range {
+    if let Some(range_str) = args.range {
         return remove_array_range(document, jsonpath_query, range_str);
     }
 
@@ -815,7 +2098,7 @@ fn remove_jsonpath_value(
     // Sort them in reverse order to remove from deepest paths first
     let mut path_strings: Vec<String> = located_results
         .into_iter()
-        .map(|(path, _)| path.to_string())
+        .map(|(path, _)| NormalizedPathUtils::to_string(&path))
         .collect();
     path_strings.sort_by_key(|a| std::cmp::Reverse(a.len()));
 
@@ -840,7 +2123,7 @@ fn remove_jsonpath_value(
         // Convert back to YAML and update document
         let updated_yaml = YamlJsonConverter::json_to_yaml(&json_value)?;
         let updated_front_matter = YamlJsonConverter::yaml_to_document_front_matter(&updated_yaml)?;
-        *document = Document::new(Some(updated_front_matter), document.body().to_string());
+        document.replace_front_matter(updated_front_matter);
     }
 
     Ok(any_removed)
@@ -888,7 +2171,7 @@ fn remove_array_range(
     let mut any_removed = false;
 
     for (path, value) in located_results {
-        let path_string = path.to_string();
+        let path_string = NormalizedPathUtils::to_string(&path);
 
         if !value.is_array() {
             return Err(MatterOfError::InvalidQuery {
@@ -935,7 +2218,7 @@ fn remove_array_range(
     if any_removed {
         let updated_yaml = YamlJsonConverter::json_to_yaml(&json_value)?;
         let updated_front_matter = YamlJsonConverter::yaml_to_document_front_matter(&updated_yaml)?;
-        *document = Document::new(Some(updated_front_matter), document.body().to_string());
+        document.replace_front_matter(updated_front_matter);
     }
 
     Ok(any_removed)
@@ -971,7 +2254,7 @@ fn cleanup_empty_containers(json_value: &mut serde_json::Value) -> Result<()> {
 }
 
 /// Add a value using JSONPath semantics
-fn add_jsonpath_value(
+pub(crate) fn add_jsonpath_value(
     document: &mut Document,
     jsonpath_query: &JsonPathQuery,
     new_value: &FrontMatterValue,
@@ -998,13 +2281,13 @@ fn add_jsonpath_value(
             JsonMutator::set_at_path(&mut json_value, &base_path, serde_json::json!({}))?;
 
             // Add the new property
-            let new_path = format!("{}['{}']", base_path, key);
+            let new_path = NormalizedPathUtils::child_path(&base_path, key);
             JsonMutator::set_at_path(&mut json_value, &new_path, new_json_value)?;
         } else {
             // Collect path strings to avoid borrowing issues
             let path_strings: Vec<String> = located_results
                 .into_iter()
-                .map(|(path, _)| path.to_string())
+                .map(|(path, _)| NormalizedPathUtils::to_string(&path))
                 .collect();
 
             // Validate that all targets are objects
@@ -1024,7 +2307,7 @@ fn add_jsonpath_value(
 
             // Add the new property to all matching objects
             for path_string in path_strings {
-                let new_path = format!("{}['{}']", path_string, key);
+                let new_path = NormalizedPathUtils::child_path(&path_string, key);
                 JsonMutator::set_at_path(&mut json_value, &new_path, new_json_value.clone())?;
             }
         }
@@ -1061,7 +2344,7 @@ fn add_jsonpath_value(
                             ),
                         });
                     }
-                    Ok(path.to_string())
+                    Ok(NormalizedPathUtils::to_string(&path))
                 })
                 .collect::<Result<Vec<_>>>()?;
 
@@ -1108,7 +2391,7 @@ fn add_jsonpath_value(
     // Convert back to YAML and update document
     let updated_yaml = YamlJsonConverter::json_to_yaml(&json_value)?;
     let updated_front_matter = YamlJsonConverter::yaml_to_document_front_matter(&updated_yaml)?;
-    *document = Document::new(Some(updated_front_matter), document.body().to_string());
+    document.replace_front_matter(updated_front_matter);
 
     Ok(true)
 }
@@ -1144,11 +2427,22 @@ fn get_value_at_path<'a>(
     Ok(current)
 }
 
+/// Options for replacing values or renaming keys with JSONPath
+#[derive(Debug, Default)]
+pub(crate) struct ReplaceOptions<'a> {
+    /// New key name for renames
+    pub new_key: Option<&'a str>,
+    /// New value to set
+    pub new_value: Option<FrontMatterValue>,
+    /// Only replace values equal to this one
+    pub old_value: Option<FrontMatterValue>,
+}
+
 /// Replace values or rename keys using JSONPath semantics
-fn replace_jsonpath_value(
+pub(crate) fn replace_jsonpath_value(
     document: &mut Document,
     jsonpath_query: &JsonPathQuery,
-    args: &ReplaceArgs,
+    args: &ReplaceOptions,
 ) -> Result<bool> {
     // Ensure document has front matter
     document.ensure_front_matter();
@@ -1179,16 +2473,12 @@ fn replace_jsonpath_value(
     // Collect path information for processing
     let mut operations = Vec::new();
     for (path, current_value) in located_results {
-        let path_string = path.to_string();
+        let path_string = NormalizedPathUtils::to_string(&path);
 
         // Determine if we should process this value
-        let should_replace = if let Some(old_value_str) = &args.old_value {
+        let should_replace = if let Some(old_value) = &args.old_value {
             // Only replace if current value matches old_value
-            let old_value = FrontMatterValue::parse_from_string(
-                old_value_str,
-                args.type_.map(Into::into).as_ref(),
-            )?;
-            let old_json_value = YamlJsonConverter::front_matter_to_json(&old_value)?;
+            let old_json_value = YamlJsonConverter::front_matter_to_json(old_value)?;
             *current_value == old_json_value
         } else {
             // Replace all matches
@@ -1221,36 +2511,15 @@ fn replace_jsonpath_value(
                 JsonMutator::remove_at_path(&mut json_value, &path_string)?;
 
                 // Create the new path by replacing the last segment
-                let parent_path = if parsed_path.segments.len() > 1 {
-                    let parent_segments = &parsed_path.segments[..parsed_path.segments.len() - 1];
-                    format!(
-                        "${}",
-                        parent_segments
-                            .iter()
-                            .map(|seg| match seg {
-                                PathSegment::Property(key) => format!("['{}']", key),
-                                PathSegment::Index(idx) => format!("[{}]", idx),
-                                PathSegment::Append => "[-]".to_string(),
-                            })
-                            .collect::<String>()
-                    )
-                } else {
-                    "$".to_string()
-                };
-
-                let new_path = if parent_path == "$" {
-                    format!("$['{}']", new_key)
-                } else {
-                    format!("{}['{}']", parent_path, new_key)
-                };
+                let parent = &parsed_path.segments[..parsed_path.segments.len() - 1];
+                let parent_path = NormalizedPathUtils::format_segments(parent);
+                let mut segments = parent.to_vec();
+                segments.push(PathSegment::Property(new_key.to_string()));
+                let new_path = NormalizedPathUtils::format_segments(&segments);
 
                 // Set the value at the new location
-                let value_to_set = if let Some(new_value_str) = &args.new_value {
-                    let new_value = FrontMatterValue::parse_from_string(
-                        new_value_str,
-                        args.type_.map(Into::into).as_ref(),
-                    )?;
-                    YamlJsonConverter::front_matter_to_json(&new_value)?
+                let value_to_set = if let Some(new_value) = &args.new_value {
+                    YamlJsonConverter::front_matter_to_json(new_value)?
                 } else {
                     current_value.clone()
                 };
@@ -1267,13 +2536,9 @@ fn replace_jsonpath_value(
                     ),
                 });
             }
-        } else if let Some(new_value_str) = &args.new_value {
+        } else if let Some(new_value) = &args.new_value {
             // This is a value replacement operation
-            let new_value = FrontMatterValue::parse_from_string(
-                new_value_str,
-                args.type_.map(Into::into).as_ref(),
-            )?;
-            let new_json_value = YamlJsonConverter::front_matter_to_json(&new_value)?;
+            let new_json_value = YamlJsonConverter::front_matter_to_json(new_value)?;
 
             JsonMutator::set_at_path(&mut json_value, &path_string, new_json_value)?;
             any_modified = true;
@@ -1290,7 +2555,7 @@ fn replace_jsonpath_value(
         // Convert back to YAML and update document
         let updated_yaml = YamlJsonConverter::json_to_yaml(&json_value)?;
         let updated_front_matter = YamlJsonConverter::yaml_to_document_front_matter(&updated_yaml)?;
-        *document = Document::new(Some(updated_front_matter), document.body().to_string());
+        document.replace_front_matter(updated_front_matter);
     }
 
     Ok(any_modified)
@@ -1323,6 +2588,150 @@ fn parse_default_values(defaults: &[String]) -> Result<Vec<(KeyPath, FrontMatter
     Ok(result)
 }
 
+/// Summary of one aggregate group, with the statistics requested on the command line
+fn aggregate_summary(args: &AggregateArgs, group: &GroupSummary) -> serde_yaml::Value {
+    let mut summary = serde_yaml::Mapping::new();
+    summary.insert("files".into(), group.files.into());
+    if args.query.is_some() {
+        summary.insert("matches".into(), group.matches.into());
+    }
+    if args.distinct {
+        let values = group.distinct().into_iter().cloned().collect();
+        summary.insert("distinct".into(), serde_yaml::Value::Sequence(values));
+    }
+    if args.count_by {
+        let mut counts = serde_yaml::Mapping::new();
+        for (value, count) in group.counts() {
+            counts.insert(value_label(value).into(), count.into());
+        }
+        summary.insert("counts".into(), serde_yaml::Value::Mapping(counts));
+    }
+
+    let stats = &group.numeric;
+    if args.sum {
+        summary.insert("sum".into(), stats.to_value(stats.sum));
+    }
+    if args.min {
+        let min = stats.min.map(|min| stats.to_value(min));
+        summary.insert("min".into(), min.unwrap_or(serde_yaml::Value::Null));
+    }
+    if args.max {
+        let max = stats.max.map(|max| stats.to_value(max));
+        summary.insert("max".into(), max.unwrap_or(serde_yaml::Value::Null));
+    }
+    serde_yaml::Value::Mapping(summary)
+}
+
+/// Print aggregate groups as a table; with --count-by, one row per distinct value
+fn print_aggregate_table(args: &AggregateArgs, groups: &[GroupSummary]) {
+    let grouped = args.group_by.is_some();
+    let mut headers = Vec::new();
+    if grouped {
+        headers.push("group");
+    }
+
+    let mut rows = Vec::new();
+    if args.count_by {
+        headers.extend(["value", "count"]);
+        for group in groups {
+            for (value, count) in group.counts() {
+                let mut row = Vec::new();
+                if grouped {
+                    row.push(group_label(group));
+                }
+                row.extend([value_label(value), count.to_string()]);
+                rows.push(row);
+            }
+        }
+    } else {
+        headers.push("files");
+        if args.query.is_some() {
+            headers.push("matches");
+        }
+        if args.distinct {
+            headers.push("distinct");
+        }
+        for (enabled, name) in [(args.sum, "sum"), (args.min, "min"), (args.max, "max")] {
+            if enabled {
+                headers.push(name);
+            }
+        }
+
+        for group in groups {
+            let mut row = Vec::new();
+            if grouped {
+                row.push(group_label(group));
+            }
+            row.push(group.files.to_string());
+            if args.query.is_some() {
+                row.push(group.matches.to_string());
+            }
+            if args.distinct {
+                let values: Vec<_> = group.distinct().into_iter().map(value_label).collect();
+                row.push(values.join(", "));
+            }
+            let stats = &group.numeric;
+            let stat = |value: Option<f64>| {
+                value
+                    .map(|value| value_label(&stats.to_value(value)))
+                    .unwrap_or_default()
+            };
+            if args.sum {
+                row.push(stat(Some(stats.sum)));
+            }
+            if args.min {
+                row.push(stat(stats.min));
+            }
+            if args.max {
+                row.push(stat(stats.max));
+            }
+            rows.push(row);
+        }
+    }
+
+    render_table(&headers, &rows);
+}
+
+/// Print rows as left-aligned columns separated by two spaces
+fn render_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: Vec<&str>| {
+        let line: Vec<_> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(headers.to_vec());
+    for row in rows {
+        print_row(row.iter().map(String::as_str).collect());
+    }
+}
+
+/// Label of an aggregate group; documents the group-by query did not match are `(none)`
+fn group_label(group: &GroupSummary) -> String {
+    group
+        .key
+        .as_ref()
+        .map(value_label)
+        .unwrap_or_else(|| "(none)".to_string())
+}
+
+/// Display a matched value on one line: strings as-is, everything else as JSON
+fn value_label(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(s) => s.clone(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
 fn output_jsonpath_result(
     result: &JsonPathQueryResult,
     format: &OutputFormat,
@@ -1415,14 +2824,26 @@ fn output_multiple_yaml_results(
 }
 
 fn output_validation_results(
-    results: &[(std::path::PathBuf, Result<()>)],
+    results: &[(std::path::PathBuf, Result<Vec<SchemaViolation>>)],
     format: &ValidationFormat,
 ) -> Result<()> {
     match format {
         ValidationFormat::Human => {
             for (path, result) in results {
                 match result {
-                    Ok(()) => println!("{}: ✓ OK", path.display()),
+                    Ok(violations) if violations.is_empty() => {
+                        println!("{}: ✓ OK", path.display())
+                    }
+                    Ok(violations) => {
+                        println!(
+                            "{}: ✗ {} schema violation(s)",
+                            path.display(),
+                            violations.len()
+                        );
+                        for violation in violations {
+                            println!("  {}", violation);
+                        }
+                    }
                     Err(error) => println!("{}: ✗ ERROR - {}", path.display(), error),
                 }
             }
@@ -1431,10 +2852,23 @@ fn output_validation_results(
             let json_results: Vec<serde_json::Value> = results
                 .iter()
                 .map(|(path, result)| {
+                    let violations: Vec<serde_json::Value> = result
+                        .iter()
+                        .flatten()
+                        .map(|v| {
+                            serde_json::json!({
+                                "path": v.path,
+                                "rule": v.rule,
+                                "message": v.message,
+                                "schema_path": v.schema_path,
+                            })
+                        })
+                        .collect();
                     serde_json::json!({
                         "file": path.to_string_lossy(),
-                        "valid": result.is_ok(),
-                        "error": if let Err(e) = result { Some(e.to_string()) } else { None }
+                        "valid": matches!(result, Ok(v) if v.is_empty()),
+                        "error": if let Err(e) = result { Some(e.to_string()) } else { None },
+                        "violations": violations,
                     })
                 })
                 .collect();
@@ -1450,8 +2884,14 @@ fn output_validation_results(
         }
         ValidationFormat::Simple => {
             for (path, result) in results {
-                if result.is_ok() {
-                    println!("{}", path.display());
+                match result {
+                    Ok(violations) if violations.is_empty() => println!("{}", path.display()),
+                    Ok(violations) => {
+                        for violation in violations {
+                            println!("{}: {}: {}", path.display(), violation.path, violation.rule);
+                        }
+                    }
+                    Err(_) => {}
                 }
             }
         }
@@ -1459,3 +2899,85 @@ fn output_validation_results(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn diff_args(old: &Path, new: &Path) -> DiffArgs {
+        DiffArgs {
+            old: old.to_path_buf(),
+            new: new.to_path_buf(),
+            ignore: Vec::new(),
+            keys_only: false,
+            format: DiffOutputFormat::Human,
+        }
+    }
+
+    #[test]
+    fn test_diff_exit_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let a = temp_dir.path().join("a.md");
+        let b = temp_dir.path().join("b.md");
+        let c = temp_dir.path().join("c.md");
+        fs::write(&a, "---\ntitle: Hello\n---\nOne body\n").unwrap();
+        fs::write(&b, "---\ntitle: Hello\n---\nAnother body\n").unwrap();
+        fs::write(&c, "---\ntitle: Bye\n---\nOne body\n").unwrap();
+
+        // Bodies are ignored
+        assert_eq!(diff_command(diff_args(&a, &b)).unwrap(), 0);
+        assert_eq!(diff_command(diff_args(&a, &c)).unwrap(), 1);
+        // main exits with 2 for errors of the diff command
+        assert!(diff_command(diff_args(&a, &temp_dir.path().join("missing.md"))).is_err());
+    }
+
+    #[test]
+    fn test_index_status_without_files() {
+        let cli = Cli::try_parse_from(["matterof", "index", "status"]).unwrap();
+        let Commands::Index(IndexArgs {
+            command: IndexCommands::Status(args),
+        }) = cli.command
+        else {
+            panic!("Expected Index Status command");
+        };
+
+        // Like `index build`, nothing is reported when no files are given
+        assert!(args.files.files.is_empty());
+        assert_eq!(index_status_command(args).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_template_order() {
+        let template = matterof::core::Template::parse("title: T\ndate: D\nauthor: A\n").unwrap();
+        let context = TemplateContext::new("post.md");
+        let reader = FrontMatterReader::new();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("post.md");
+        let write = |document: &Document| {
+            FrontMatterWriter::new()
+                .write_file(document, &path, None)
+                .unwrap();
+            fs::read_to_string(&path).unwrap()
+        };
+
+        let mut document = reader
+            .parse_content("---\nzz: 1 # kept\n---\nBody\n", None)
+            .unwrap();
+        let rendered = template.render(&context, &document).unwrap();
+        document.merge_missing_front_matter(&rendered);
+        assert_eq!(
+            write(&document),
+            "---\nzz: 1 # kept\ntitle: T\ndate: D\nauthor: A\n---\nBody\n"
+        );
+
+        let mut document = Document::body_only("Body\n".to_string());
+        document.merge_missing_front_matter(&rendered);
+        assert_eq!(
+            write(&document),
+            "---\ntitle: T\ndate: D\nauthor: A\n---\nBody\n"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Applying project settings to parsed command-line arguments
+//!
+//! Arguments given on the command line always win. Whatever they leave
+//! unset (an empty list, an absent flag or option) is filled from the
+//! `MATTEROF_*` environment variables, then from `.matterof.toml` and the
+//! selected profile; the rest keeps the built-in defaults.
+//!
+//! A flag is only ever turned on by a setting, so each flag a setting can
+//! turn on has a negating flag (`--no-transactional`, `--atomic`,
+//! `--auto-root`, ...) that keeps the setting from applying.
+
+use crate::cli_bin::args::{
+    Commands, CommonFileOptions, IndexCommands, ProcessingOptions, SchemaCommands, WriteOptions,
+};
+use log::debug;
+use matterof::error::{MatterOfError, Result};
+use matterof::io::{
+    FileSettings, ProjectConfig, QuerySettings, ReadSettings, Settings, WriteSettings, CONFIG_FILE,
+};
+use std::path::Path;
+
+/// Settings from the configuration file found from `cwd`, with the profile
+/// and the environment on top
+pub fn load_settings(cwd: &Path, profile: Option<&str>) -> Result<Settings> {
+    let env_profile = std::env::var("MATTEROF_PROFILE").ok();
+    let profile = profile.or(env_profile.as_deref().filter(|name| !name.is_empty()));
+
+    let file_settings = match ProjectConfig::discover(cwd)? {
+        Some(config) => {
+            debug!("Using configuration {}", config.path().display());
+            config.settings(profile)?
+        }
+        None => match profile {
+            Some(name) => {
+                return Err(MatterOfError::validation(format!(
+                    "Profile '{}' requested, but no {} was found",
+                    name, CONFIG_FILE
+                )));
+            }
+            None => Settings::default(),
+        },
+    };
+
+    Ok(file_settings.overlay(Settings::from_env()?))
+}
+
+impl CommonFileOptions {
+    /// Fill the options not given on the command line
+    pub fn apply_settings(&mut self, files: &FileSettings, read: &ReadSettings) {
+        if self.extensions.is_empty() {
+            self.extensions = files.ext.clone().unwrap_or_default();
+        }
+        if self.exclude_patterns.is_empty() {
+            self.exclude_patterns = files.exclude.clone().unwrap_or_default();
+        }
+        if !self.no_follow_links {
+            self.follow_links |= files.follow_links.unwrap_or(false);
+        }
+        if !self.no_include_hidden {
+            self.include_hidden |= files.include_hidden.unwrap_or(false);
+        }
+        self.max_depth = self.max_depth.or(files.max_depth);
+        self.processing.apply_settings(files, read);
+    }
+}
+
+impl ProcessingOptions {
+    /// Fill the options not given on the command line
+    pub fn apply_settings(&mut self, files: &FileSettings, read: &ReadSettings) {
+        self.max_file_size = self.max_file_size.or(read.max_file_size);
+        self.jobs = self.jobs.or(files.jobs);
+    }
+}
+
+impl WriteOptions {
+    /// Fill the options not given on the command line
+    pub fn apply_settings(&mut self, write: &WriteSettings) {
+        if self.backup_suffix.is_none() {
+            self.backup_suffix = write.backup_suffix.clone();
+        }
+        if self.backup_dir.is_none() {
+            self.backup_dir = write.backup_dir.clone();
+        }
+        if !self.atomic {
+            self.no_atomic |= write.no_atomic.unwrap_or(false);
+        }
+        self.line_endings = self.line_endings.or(write.line_endings.map(Into::into));
+        self.diff_context = self.diff_context.or(write.diff_context);
+        if !self.no_transactional {
+            self.transactional |= write.transactional.unwrap_or(false);
+        }
+        if !self.no_journal {
+            self.journal |= write.journal.unwrap_or(false);
+        }
+    }
+}
+
+fn apply_no_auto_root(no_auto_root: &mut bool, auto_root: bool, query: &QuerySettings) {
+    if !auto_root {
+        *no_auto_root |= query.no_auto_root.unwrap_or(false);
+    }
+}
+
+/// Fill every option of a command that was not given on the command line
+pub fn apply_settings(command: &mut Commands, settings: &Settings) {
+    let Settings {
+        files,
+        read,
+        write,
+        query,
+    } = settings;
+
+    match command {
+        Commands::Get(args) => {
+            args.files.apply_settings(files, read);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Set(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Add(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Remove(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Replace(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Query(args) => {
+            args.files.apply_settings(files, read);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Aggregate(args) => {
+            args.files.apply_settings(files, read);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Export(args) => {
+            args.files.apply_settings(files, read);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Import(args) => {
+            args.processing.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+            apply_no_auto_root(&mut args.no_auto_root, args.auto_root, query);
+        }
+        Commands::Derive(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::Init(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::New(args) => args.write_options.apply_settings(write),
+        Commands::Clean(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::Validate(args) => args.files.apply_settings(files, read),
+        Commands::Format(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::Convert(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::Schema(args) => match &mut args.command {
+            SchemaCommands::Infer(args) => args.files.apply_settings(files, read),
+        },
+        Commands::Index(args) => match &mut args.command {
+            IndexCommands::Build(args)
+            | IndexCommands::Update(args)
+            | IndexCommands::Status(args) => args.files.apply_settings(files, read),
+        },
+        Commands::Apply(args) => {
+            args.files.apply_settings(files, read);
+            args.write_options.apply_settings(write);
+        }
+        Commands::Diff(_) | Commands::Undo(_) | Commands::History(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli_bin::args::Cli;
+    use clap::Parser;
+
+    fn settings() -> Settings {
+        let config = r#"
+            files = { ext = ["mdx"], exclude = ["drafts"] }
+            write = { backup_dir = ".backups", diff_context = 1 }
+            query = { no_auto_root = true }
+        "#;
+        toml_edit::de::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn test_command_line_wins() {
+        let mut cli = Cli::try_parse_from([
+            "matterof",
+            "set",
+            "--query",
+            "title",
+            "--value",
+            "x",
+            "--exclude",
+            "archive",
+            "--diff-context",
+            "5",
+            "content/",
+        ])
+        .unwrap();
+        apply_settings(&mut cli.command, &settings());
+
+        let Commands::Set(args) = cli.command else {
+            panic!("Expected Set command");
+        };
+        assert_eq!(args.files.extensions, vec!["mdx"]);
+        assert_eq!(args.files.exclude_patterns, vec!["archive"]);
+        assert_eq!(args.write_options.backup_dir, Some(".backups".into()));
+        assert_eq!(args.write_options.diff_context, Some(5));
+        assert!(args.no_auto_root);
+    }
+
+    #[test]
+    fn test_import_reads_like_other_commands() {
+        let config = "files = { jobs = 3 }\nread = { max_file_size = 4096 }";
+        let settings: Settings = toml_edit::de::from_str(config).unwrap();
+        let mut cli = Cli::try_parse_from(["matterof", "import", "data.csv", "-j", "1"]).unwrap();
+        apply_settings(&mut cli.command, &settings);
+
+        let Commands::Import(args) = cli.command else {
+            panic!("Expected Import command");
+        };
+        assert_eq!(args.processing.jobs, Some(1));
+        assert_eq!(args.processing.max_file_size, Some(4096));
+    }
+
+    #[test]
+    fn test_journal_is_opt_in() {
+        let write_options = |args: &[&str]| {
+            let mut argv = vec!["matterof", "set", "--query", "title", "--value", "x"];
+            argv.extend_from_slice(args);
+            argv.push("post.md");
+            let mut cli = Cli::try_parse_from(argv).unwrap();
+            let settings: Settings = toml_edit::de::from_str("write = { journal = true }").unwrap();
+            apply_settings(&mut cli.command, &settings);
+            let Commands::Set(args) = cli.command else {
+                panic!("Expected Set command");
+            };
+            args.write_options
+        };
+
+        assert!(
+            !Cli::try_parse_from(["matterof", "set", "--query", "t", "--value", "x", "a.md"])
+                .map(|cli| matches!(cli.command, Commands::Set(args) if args.write_options.journal))
+                .unwrap()
+        );
+        assert!(write_options(&[]).journal);
+        assert!(!write_options(&["--no-journal"]).journal);
+    }
+
+    #[test]
+    fn test_negating_flags_win() {
+        let config = r#"
+            files = { follow_links = true, include_hidden = true }
+            write = { no_atomic = true, transactional = true }
+            query = { no_auto_root = true }
+        "#;
+        let settings: Settings = toml_edit::de::from_str(config).unwrap();
+        let parse = |flags: &[&str]| {
+            let mut argv = vec!["matterof", "set", "--query", "title", "--value", "x"];
+            argv.extend_from_slice(flags);
+            argv.push("post.md");
+            let mut cli = Cli::try_parse_from(argv).unwrap();
+            apply_settings(&mut cli.command, &settings);
+            let Commands::Set(args) = cli.command else {
+                panic!("Expected Set command");
+            };
+            args
+        };
+
+        let args = parse(&[]);
+        assert!(args.files.follow_links && args.files.include_hidden);
+        assert!(args.write_options.no_atomic && args.write_options.transactional);
+        assert!(args.no_auto_root);
+
+        let args = parse(&[
+            "--no-follow-links",
+            "--no-include-hidden",
+            "--atomic",
+            "--no-transactional",
+            "--auto-root",
+        ]);
+        assert!(!args.files.follow_links && !args.files.include_hidden);
+        assert!(!args.write_options.no_atomic && !args.write_options.transactional);
+        assert!(!args.no_auto_root);
+
+        // The last of a flag and its negation wins
+        let args = parse(&["--no-transactional", "--transactional"]);
+        assert!(args.write_options.transactional);
+    }
+}
@@ -0,0 +1,260 @@
+//! Tabular export of front matter for the export command
+//!
+//! Every file becomes one row and every column is a JSONPath query, so
+//! `--columns title,date,author.name,tags` yields a spreadsheet-friendly
+//! table. Columns matching several values (or an array) are joined with a
+//! separator in the text formats and kept as JSON arrays in JSON Lines.
+
+use crate::cli_bin::args::ExportFormat;
+use matterof::core::{Document, JsonPathQuery, JsonPathQueryResult, YamlJsonConverter};
+use matterof::error::{MatterOfError, Result};
+use serde_json::Value as JsonValue;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A column of the export: its header and the query producing its cells
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub query: JsonPathQuery,
+}
+
+impl Column {
+    /// Create a column headed by the query as written
+    pub fn new(query: &str, auto_root: bool) -> Result<Self> {
+        Ok(Self {
+            name: query.to_string(),
+            query: JsonPathQuery::new_with_options(query, auto_root)?,
+        })
+    }
+}
+
+/// Query results of every column for one file
+#[derive(Debug, Clone)]
+struct Row {
+    path: PathBuf,
+    cells: Vec<JsonPathQueryResult>,
+}
+
+/// One row per file, one column per query
+#[derive(Debug, Clone)]
+pub struct ExportTable {
+    columns: Vec<Column>,
+    path_column: Option<String>,
+    rows: Vec<Row>,
+}
+
+impl ExportTable {
+    /// Create an empty table; `path_column` names the leading file path column, if any
+    pub fn new(columns: Vec<Column>, path_column: Option<String>) -> Self {
+        Self {
+            columns,
+            path_column,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Number of rows (files) in the table
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Add the row of a document
+    pub fn add_document(&mut self, path: &Path, document: &Document) -> Result<()> {
+        let json = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+        let cells = self
+            .columns
+            .iter()
+            .map(|column| {
+                let matches = column
+                    .query
+                    .query_located(&json)
+                    .into_iter()
+                    .map(|(path, value)| (path, value.clone()))
+                    .collect();
+                JsonPathQueryResult::new(column.query.clone(), matches)
+            })
+            .collect();
+
+        self.rows.push(Row {
+            path: path.to_path_buf(),
+            cells,
+        });
+        Ok(())
+    }
+
+    /// Write the table in `format`, joining multiple values with `separator`
+    pub fn write<W: Write>(&self, out: W, format: ExportFormat, separator: &str) -> Result<()> {
+        match format {
+            ExportFormat::Csv => self.write_delimited(out, b',', separator),
+            ExportFormat::Tsv => self.write_delimited(out, b'\t', separator),
+            ExportFormat::Jsonl => self.write_jsonl(out),
+            ExportFormat::MarkdownTable => self.write_markdown(out, separator),
+        }
+    }
+
+    fn headers(&self) -> Vec<&str> {
+        self.path_column
+            .iter()
+            .chain(self.columns.iter().map(|column| &column.name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn text_rows<'a>(&'a self, separator: &'a str) -> impl Iterator<Item = Vec<String>> + 'a {
+        self.rows.iter().map(move |row| {
+            let path = self
+                .path_column
+                .as_ref()
+                .map(|_| row.path.display().to_string());
+            path.into_iter()
+                .chain(row.cells.iter().map(|cell| cell_text(cell, separator)))
+                .collect()
+        })
+    }
+
+    fn write_delimited<W: Write>(&self, out: W, delimiter: u8, separator: &str) -> Result<()> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(out);
+        writer.write_record(self.headers()).map_err(csv_error)?;
+        for row in self.text_rows(separator) {
+            writer.write_record(&row).map_err(csv_error)?;
+        }
+        writer.flush().map_err(MatterOfError::Io)
+    }
+
+    fn write_jsonl<W: Write>(&self, mut out: W) -> Result<()> {
+        for row in &self.rows {
+            // A YAML mapping serializes through serde_json in insertion order
+            let mut object = serde_yaml::Mapping::new();
+            if let Some(name) = &self.path_column {
+                object.insert(name.as_str().into(), row.path.display().to_string().into());
+            }
+            for (column, cell) in self.columns.iter().zip(&row.cells) {
+                let value = match cell.values().as_slice() {
+                    [] => JsonValue::Null,
+                    [value] => (*value).clone(),
+                    values => JsonValue::Array(values.iter().map(|v| (*v).clone()).collect()),
+                };
+                object.insert(
+                    column.name.as_str().into(),
+                    YamlJsonConverter::json_to_yaml(&value)?,
+                );
+            }
+            let line = serde_json::to_string(&object)
+                .map_err(|e| MatterOfError::validation(e.to_string()))?;
+            writeln!(out, "{}", line).map_err(MatterOfError::Io)?;
+        }
+        Ok(())
+    }
+
+    fn write_markdown<W: Write>(&self, mut out: W, separator: &str) -> Result<()> {
+        let headers: Vec<_> = self.headers().into_iter().map(markdown_cell).collect();
+        writeln!(out, "| {} |", headers.join(" | ")).map_err(MatterOfError::Io)?;
+        let rule = vec!["---"; headers.len()];
+        writeln!(out, "| {} |", rule.join(" | ")).map_err(MatterOfError::Io)?;
+        for row in self.text_rows(separator) {
+            let cells: Vec<_> = row.iter().map(|cell| markdown_cell(cell)).collect();
+            writeln!(out, "| {} |", cells.join(" | ")).map_err(MatterOfError::Io)?;
+        }
+        Ok(())
+    }
+}
+
+/// Text of a cell: every match, with arrays flattened, joined by `separator`
+fn cell_text(result: &JsonPathQueryResult, separator: &str) -> String {
+    let mut parts = Vec::new();
+    for value in result.values() {
+        match value {
+            JsonValue::Array(items) => parts.extend(items.iter().map(scalar_text)),
+            other => parts.push(scalar_text(other)),
+        }
+    }
+    parts.join(separator)
+}
+
+/// Strings as-is, null as nothing, everything else as compact JSON
+fn scalar_text(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Escape a cell so it stays inside its markdown table column
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn csv_error(error: csv::Error) -> MatterOfError {
+    MatterOfError::Io(error.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matterof::io::FrontMatterReader;
+
+    fn table() -> ExportTable {
+        let columns = ["title", "author.name", "tags"]
+            .iter()
+            .map(|query| Column::new(query, true).unwrap())
+            .collect();
+        let mut table = ExportTable::new(columns, Some("path".to_string()));
+
+        let reader = FrontMatterReader::new();
+        for (path, content) in [
+            (
+                "a.md",
+                "---\ntitle: Hello, world\nauthor: { name: Ann }\ntags: [rust, cli]\n---\n",
+            ),
+            ("b.md", "---\ntitle: A | B\n---\n"),
+        ] {
+            let document = reader.parse_content(content, None).unwrap();
+            table.add_document(Path::new(path), &document).unwrap();
+        }
+        table
+    }
+
+    fn render(format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        table().write(&mut out, format, ";").unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_csv_and_tsv() {
+        assert_eq!(
+            render(ExportFormat::Csv),
+            "path,title,author.name,tags\na.md,\"Hello, world\",Ann,rust;cli\nb.md,A | B,,\n"
+        );
+        assert_eq!(
+            render(ExportFormat::Tsv).lines().nth(1),
+            Some("a.md\tHello, world\tAnn\trust;cli")
+        );
+    }
+
+    #[test]
+    fn test_jsonl() {
+        assert_eq!(
+            render(ExportFormat::Jsonl),
+            concat!(
+                r#"{"path":"a.md","title":"Hello, world","author.name":"Ann","tags":["rust","cli"]}"#,
+                "\n",
+                r#"{"path":"b.md","title":"A | B","author.name":null,"tags":null}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let output = render(ExportFormat::MarkdownTable);
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines[0], "| path | title | author.name | tags |");
+        assert_eq!(lines[1], "| --- | --- | --- | --- |");
+        assert_eq!(lines[3], "| b.md | A \\| B |  |  |");
+    }
+}
@@ -0,0 +1,461 @@
+//! Bulk front matter updates from tabular data for the import command
+//!
+//! The reverse of export: every row names a file in its key column and every
+//! other column header is a JSONPath query to set in that file. Cells are
+//! typed through `FrontMatterValue::parse_from_string`, optionally guided by
+//! per-column type hints. A delimited cell without a hint is split on the
+//! separator when the value it replaces is an array, and empty cells leave
+//! the file untouched, so a sheet produced by `matterof export` can be
+//! imported back as-is.
+
+use crate::cli_bin::commands::assign_jsonpath_value;
+use matterof::core::{Document, FrontMatterValue, JsonPathQuery, ValueType, YamlJsonConverter};
+use matterof::error::{MatterOfError, Result};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Formats of import data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Delimited text with a header row
+    Delimited(u8),
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl DataFormat {
+    /// Guess the format from the file extension, defaulting to CSV
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("tsv") | Some("tab") => Self::Delimited(b'\t'),
+            Some("jsonl") | Some("ndjson") => Self::Jsonl,
+            _ => Self::Delimited(b','),
+        }
+    }
+}
+
+/// How rows are mapped to files and cells to values
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Column holding the file path of each row
+    pub key_column: String,
+    /// Type hints by column header
+    pub types: Vec<(String, ValueType)>,
+    /// Separator between the elements of array cells
+    pub separator: String,
+    /// Prepend `$.` to column headers that are not JSONPath queries yet
+    pub auto_root: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            key_column: "path".to_string(),
+            types: Vec::new(),
+            separator: ";".to_string(),
+            auto_root: true,
+        }
+    }
+}
+
+/// A column to set: its header, compiled query and type hint
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    query: JsonPathQuery,
+    type_hint: Option<ValueType>,
+}
+
+impl Column {
+    fn new(name: &str, options: &ImportOptions) -> Result<Self> {
+        let type_hint = options
+            .types
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, value_type)| *value_type);
+
+        Ok(Self {
+            name: name.to_string(),
+            query: JsonPathQuery::new_with_options(name, options.auto_root)?,
+            type_hint,
+        })
+    }
+
+    /// Type a cell of this column
+    fn value(&self, cell: &str, separator: &str) -> Result<FrontMatterValue> {
+        match &self.type_hint {
+            Some(ValueType::Array) => split_array(cell, separator),
+            hint => FrontMatterValue::parse_from_string(cell, hint.as_ref()),
+        }
+    }
+}
+
+/// A typed cell of a row
+#[derive(Debug, Clone)]
+struct Cell {
+    column: usize,
+    value: FrontMatterValue,
+    /// Text of a delimited cell without a type hint, split when it replaces an array
+    text: Option<String>,
+}
+
+/// Values to set in one file
+#[derive(Debug, Clone)]
+pub struct ImportRow {
+    /// File the row applies to
+    pub path: PathBuf,
+    /// Line of the row in the data file, for error messages
+    pub line: usize,
+    cells: Vec<Cell>,
+}
+
+/// Rows of import data with their columns
+#[derive(Debug, Clone)]
+pub struct ImportData {
+    columns: Vec<Column>,
+    rows: Vec<ImportRow>,
+    separator: String,
+}
+
+impl ImportData {
+    /// Load import data from a file
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+        format: Option<DataFormat>,
+        options: &ImportOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(MatterOfError::file_not_found(path));
+        }
+        let content = fs::read_to_string(path).map_err(MatterOfError::Io)?;
+        let format = format.unwrap_or_else(|| DataFormat::from_path(path));
+        Self::parse(&content, format, options)
+            .map_err(|e| MatterOfError::validation(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse import data in `format`
+    pub fn parse(content: &str, format: DataFormat, options: &ImportOptions) -> Result<Self> {
+        let data = match format {
+            DataFormat::Delimited(delimiter) => Self::parse_delimited(content, delimiter, options),
+            DataFormat::Jsonl => Self::parse_jsonl(content, options),
+        }?;
+
+        // Type hints for columns that do not exist are most likely typos
+        for (name, _) in &options.types {
+            if !data.columns.iter().any(|column| &column.name == name) {
+                return Err(MatterOfError::validation(format!(
+                    "--type given for unknown column '{}'",
+                    name
+                )));
+            }
+        }
+        Ok(data)
+    }
+
+    fn parse_delimited(content: &str, delimiter: u8, options: &ImportOptions) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(content.as_bytes());
+        let headers = reader.headers().map_err(csv_error)?.clone();
+
+        let key_index = headers
+            .iter()
+            .position(|header| header == options.key_column)
+            .ok_or_else(|| missing_key_column(&options.key_column))?;
+        let mut columns = Vec::new();
+        let mut indices = Vec::new();
+        for (index, header) in headers.iter().enumerate() {
+            if index != key_index {
+                columns.push(Column::new(header, options)?);
+                indices.push(index);
+            }
+        }
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            let line = record.position().map_or(0, |pos| pos.line() as usize);
+            let path = record.get(key_index).unwrap_or_default();
+            if path.is_empty() {
+                return Err(row_error(line, "empty key column"));
+            }
+
+            let mut cells = Vec::new();
+            for (column_index, (column, index)) in columns.iter().zip(&indices).enumerate() {
+                let cell = record.get(*index).unwrap_or_default();
+                if cell.is_empty() {
+                    continue;
+                }
+                let value = column
+                    .value(cell, &options.separator)
+                    .map_err(|e| row_error(line, &format!("column '{}': {}", column.name, e)))?;
+                cells.push(Cell {
+                    column: column_index,
+                    value,
+                    text: column.type_hint.is_none().then(|| cell.to_string()),
+                });
+            }
+            rows.push(ImportRow {
+                path: PathBuf::from(path),
+                line,
+                cells,
+            });
+        }
+
+        Ok(Self {
+            columns,
+            rows,
+            separator: options.separator.clone(),
+        })
+    }
+
+    fn parse_jsonl(content: &str, options: &ImportOptions) -> Result<Self> {
+        let mut data = Self {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            separator: options.separator.clone(),
+        };
+
+        for (index, text) in content.lines().enumerate() {
+            let line = index + 1;
+            if text.trim().is_empty() {
+                continue;
+            }
+            let object: serde_json::Map<String, JsonValue> = serde_json::from_str(text)
+                .map_err(|e| row_error(line, &format!("invalid JSON object: {}", e)))?;
+
+            let path = match object.get(&options.key_column) {
+                Some(JsonValue::String(path)) if !path.is_empty() => PathBuf::from(path),
+                Some(_) => return Err(row_error(line, "key column is not a path")),
+                None => return Err(missing_key_column(&options.key_column)),
+            };
+
+            let mut cells = Vec::new();
+            for (name, value) in &object {
+                if name == &options.key_column || value.is_null() {
+                    continue;
+                }
+                let column_index = match data.columns.iter().position(|c| &c.name == name) {
+                    Some(column_index) => column_index,
+                    None => {
+                        data.columns.push(Column::new(name, options)?);
+                        data.columns.len() - 1
+                    }
+                };
+                let column = &data.columns[column_index];
+
+                // JSON values are already typed; only strings honor type hints
+                let value = match value {
+                    JsonValue::String(s) if column.type_hint.is_some() => {
+                        column.value(s, &options.separator)
+                    }
+                    other => YamlJsonConverter::json_to_front_matter(other),
+                }
+                .map_err(|e| row_error(line, &format!("column '{}': {}", name, e)))?;
+                cells.push(Cell {
+                    column: column_index,
+                    value,
+                    text: None,
+                });
+            }
+            data.rows.push(ImportRow { path, line, cells });
+        }
+
+        Ok(data)
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Files named by the rows, in first-seen order
+    pub fn files(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = Vec::new();
+        for row in &self.rows {
+            if !files.contains(&row.path.as_path()) {
+                files.push(&row.path);
+            }
+        }
+        files
+    }
+
+    /// Apply every row naming `path` to its document, returning whether it changed
+    pub fn apply(&self, path: &Path, document: &mut Document) -> Result<bool> {
+        let mut changed = false;
+        for row in self.rows.iter().filter(|row| row.path == path) {
+            for cell in &row.cells {
+                let column = &self.columns[cell.column];
+                let error = |e: MatterOfError| {
+                    row_error(row.line, &format!("column '{}': {}", column.name, e))
+                };
+
+                let split;
+                let value = match &cell.text {
+                    Some(text) if holds_array(document, &column.query).map_err(error)? => {
+                        split = split_array(text, &self.separator).map_err(error)?;
+                        &split
+                    }
+                    _ => &cell.value,
+                };
+                changed |= assign_jsonpath_value(document, &column.query, value).map_err(error)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Whether `query` matches values in `document` and all of them are arrays
+fn holds_array(document: &Document, query: &JsonPathQuery) -> Result<bool> {
+    let json = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+    let matches = query.query(&json);
+    Ok(!matches.is_empty() && matches.iter().all(|value| value.is_array()))
+}
+
+/// Type the elements of an array cell joined by `separator`
+fn split_array(cell: &str, separator: &str) -> Result<FrontMatterValue> {
+    cell.split(separator)
+        .map(|part| FrontMatterValue::parse_from_string(part.trim(), None))
+        .collect::<Result<Vec<_>>>()
+        .map(FrontMatterValue::array)
+}
+
+fn missing_key_column(name: &str) -> MatterOfError {
+    MatterOfError::validation(format!("Key column '{}' not found", name))
+}
+
+fn row_error(line: usize, message: &str) -> MatterOfError {
+    MatterOfError::validation(format!("line {}: {}", line, message))
+}
+
+fn csv_error(error: csv::Error) -> MatterOfError {
+    MatterOfError::validation(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matterof::core::KeyPath;
+    use matterof::io::FrontMatterReader;
+
+    fn document(content: &str) -> Document {
+        FrontMatterReader::new()
+            .parse_content(content, None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_csv_with_type_hints() {
+        let options = ImportOptions {
+            types: vec![
+                ("tags".to_string(), ValueType::Array),
+                ("zip".to_string(), ValueType::String),
+            ],
+            ..Default::default()
+        };
+        let data = ImportData::parse(
+            "path,title,weight,tags,zip,author.name\na.md,\"Hello, world\",3,rust;cli,0123,Ann\nb.md,,,,,\n",
+            DataFormat::Delimited(b','),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data.files(), vec![Path::new("a.md"), Path::new("b.md")]);
+
+        let mut doc = document("---\ntitle: Old\ntags: [x]\n---\nBody");
+        assert!(data.apply(Path::new("a.md"), &mut doc).unwrap());
+
+        let get = |key: &str| doc.get(&KeyPath::parse(key).unwrap());
+        assert_eq!(get("title").unwrap().as_string(), Some("Hello, world"));
+        assert_eq!(get("weight").unwrap().as_int(), Some(3));
+        assert_eq!(get("tags.1").unwrap().as_string(), Some("cli"));
+        assert_eq!(get("zip").unwrap().as_string(), Some("0123"));
+        assert_eq!(get("author.name").unwrap().as_string(), Some("Ann"));
+
+        // Empty cells leave the document alone
+        let mut doc = document("---\ntitle: Keep\n---\n");
+        assert!(!data.apply(Path::new("b.md"), &mut doc).unwrap());
+    }
+
+    #[test]
+    fn test_import_jsonl() {
+        let data = ImportData::parse(
+            "{\"path\":\"a.md\",\"draft\":false,\"tags\":[\"rust\"],\"title\":null}\n",
+            DataFormat::Jsonl,
+            &ImportOptions::default(),
+        )
+        .unwrap();
+
+        let mut doc = document("---\ntitle: Kept\ndraft: true\n---\n");
+        assert!(data.apply(Path::new("a.md"), &mut doc).unwrap());
+        let get = |key: &str| doc.get(&KeyPath::parse(key).unwrap());
+        assert_eq!(get("draft").unwrap().as_bool(), Some(false));
+        assert_eq!(get("tags.0").unwrap().as_string(), Some("rust"));
+        assert_eq!(get("title").unwrap().as_string(), Some("Kept"));
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        use crate::cli_bin::args::ExportFormat;
+        use crate::cli_bin::export::{Column as ExportColumn, ExportTable};
+
+        let original = document("---\ntitle: Post\ntags: [a, b]\nweight: 2\n---\n");
+        let columns = ["title", "tags", "weight"]
+            .iter()
+            .map(|query| ExportColumn::new(query, true).unwrap())
+            .collect();
+        let mut table = ExportTable::new(columns, Some("path".to_string()));
+        table.add_document(Path::new("a.md"), &original).unwrap();
+        let mut csv = Vec::new();
+        table.write(&mut csv, ExportFormat::Csv, ";").unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+
+        // Re-importing the unchanged export is a no-op
+        let options = ImportOptions::default();
+        let format = DataFormat::Delimited(b',');
+        let data = ImportData::parse(&csv, format, &options).unwrap();
+        let mut doc = original.clone();
+        assert!(!data.apply(Path::new("a.md"), &mut doc).unwrap());
+
+        // Edited cells of array columns stay arrays
+        let edited = csv.replace("a;b", "a;b;z");
+        let data = ImportData::parse(&edited, format, &options).unwrap();
+        assert!(data.apply(Path::new("a.md"), &mut doc).unwrap());
+        let get = |key: &str| doc.get(&KeyPath::parse(key).unwrap());
+        assert_eq!(
+            get("tags").unwrap().as_array().map(|tags| tags.len()),
+            Some(3)
+        );
+        assert_eq!(get("tags.2").unwrap().as_string(), Some("z"));
+        assert_eq!(get("title").unwrap().as_string(), Some("Post"));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let options = ImportOptions::default();
+        let csv = DataFormat::Delimited(b',');
+        assert!(ImportData::parse("file,title\na.md,x\n", csv, &options).is_err());
+
+        let options = ImportOptions {
+            types: vec![("weight".to_string(), ValueType::Int)],
+            ..Default::default()
+        };
+        let err = ImportData::parse("path,weight\na.md,1\nb.md,heavy\n", csv, &options)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 3"), "{}", err);
+
+        let options = ImportOptions {
+            types: vec![("wieght".to_string(), ValueType::Int)],
+            ..Default::default()
+        };
+        assert!(ImportData::parse("path,weight\n", csv, &options).is_err());
+    }
+}
@@ -5,5 +5,11 @@
 
 pub mod args;
 pub mod commands;
+pub mod config;
+pub mod export;
+pub mod import;
+pub mod report;
+pub mod script;
+pub mod templates;
 
 // Re-exports are not needed since main.rs imports directly from submodules
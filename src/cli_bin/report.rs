@@ -0,0 +1,225 @@
+//! Per-file failures of batch commands run with `--keep-going`
+//!
+//! Without `--keep-going` a command still visits every file, but any failure
+//! stops it before it writes or prints anything. With it, failing files are
+//! left out and the command finishes with the rest; the failures are then
+//! summarized on stderr, optionally written as a JSON report, and the exit
+//! status follows the worst [`ErrorSeverity`] among them.
+
+use crate::cli_bin::args::ErrorOptions;
+use log::error;
+use matterof::error::{ErrorSeverity, MatterOfError, Result};
+use matterof::io::{collect_results, ParallelExecutor};
+use std::path::{Path, PathBuf};
+
+/// Exit status of a run whose worst failure has this severity
+pub fn exit_code(severity: ErrorSeverity) -> i32 {
+    match severity {
+        ErrorSeverity::Low => 1,
+        ErrorSeverity::Medium => 2,
+        ErrorSeverity::High => 3,
+        ErrorSeverity::Critical => 4,
+    }
+}
+
+/// Files a command failed on, and how many it was given
+#[derive(Debug)]
+pub struct FailureReport {
+    command: &'static str,
+    options: ErrorOptions,
+    processed: usize,
+    failures: Vec<(PathBuf, MatterOfError)>,
+}
+
+impl FailureReport {
+    /// Create an empty report for a command
+    pub fn new(command: &'static str, options: &ErrorOptions) -> Self {
+        Self {
+            command,
+            options: options.clone(),
+            processed: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Whether failing files are skipped rather than failing the command
+    pub fn keep_going(&self) -> bool {
+        self.options.keep_going
+    }
+
+    /// Run `f` on every file, returning each file that succeeded with its result
+    pub fn process<R, F>(
+        &mut self,
+        executor: &ParallelExecutor,
+        files: &[PathBuf],
+        f: F,
+    ) -> Result<Vec<(PathBuf, R)>>
+    where
+        R: Send,
+        F: Fn(&PathBuf) -> Result<R> + Sync,
+    {
+        self.processed += files.len();
+        let results = executor.map(files, f);
+        self.collect(files.iter().cloned().zip(results))
+    }
+
+    /// Keep the files that succeeded
+    ///
+    /// With `--keep-going` the failures are recorded; otherwise they are all
+    /// returned, as [`collect_results`] does.
+    pub fn collect<R>(
+        &mut self,
+        results: impl IntoIterator<Item = (PathBuf, Result<R>)>,
+    ) -> Result<Vec<(PathBuf, R)>> {
+        if !self.keep_going() {
+            return collect_results(
+                results
+                    .into_iter()
+                    .map(|(file, result)| result.map(|value| (file, value)))
+                    .collect(),
+            );
+        }
+
+        let mut succeeded = Vec::new();
+        for (file, result) in results {
+            match result {
+                Ok(value) => succeeded.push((file, value)),
+                Err(error) => self.record(file, error),
+            }
+        }
+        Ok(succeeded)
+    }
+
+    /// Record a failed file
+    pub fn record(&mut self, file: impl Into<PathBuf>, error: MatterOfError) {
+        self.failures.push((file.into(), error));
+    }
+
+    /// Count files checked outside of [`FailureReport::process`]
+    pub fn add_processed(&mut self, count: usize) {
+        self.processed += count;
+    }
+
+    /// Whether any file failed
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// The most severe failure, if any
+    pub fn worst_severity(&self) -> Option<ErrorSeverity> {
+        self.failures
+            .iter()
+            .map(|(_, error)| error.severity())
+            .max()
+    }
+
+    /// The report as JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        let failures: Vec<_> = self
+            .failures
+            .iter()
+            .map(|(file, error)| {
+                serde_json::json!({
+                    "file": file.to_string_lossy(),
+                    "error": error.to_string(),
+                    "severity": error.severity().to_string(),
+                    "recoverable": error.is_recoverable(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "command": self.command,
+            "processed": self.processed,
+            "failed": self.failures.len(),
+            "worst_severity": self.worst_severity().map(|severity| severity.to_string()),
+            "failures": failures,
+        })
+    }
+
+    /// Summarize the failures and write the JSON report, if requested
+    ///
+    /// Returns the exit status of the worst failure, or 0 when there is none.
+    pub fn finish(self) -> Result<i32> {
+        if let Some(path) = &self.options.error_report {
+            self.write_json(path)?;
+        }
+        let Some(worst) = self.worst_severity() else {
+            return Ok(0);
+        };
+
+        error!(
+            "{} of {} files failed:",
+            self.failures.len(),
+            self.processed
+        );
+        for (file, failure) in &self.failures {
+            error!("  {}: [{}] {}", file.display(), failure.severity(), failure);
+        }
+        Ok(exit_code(worst))
+    }
+
+    fn write_json(&self, path: &Path) -> Result<()> {
+        let output = serde_json::to_string_pretty(&self.to_json())
+            .map_err(|e| MatterOfError::validation(e.to_string()))?;
+        if path == Path::new("-") {
+            println!("{}", output);
+            Ok(())
+        } else {
+            std::fs::write(path, output + "\n").map_err(MatterOfError::Io)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(keep_going: bool) -> ErrorOptions {
+        ErrorOptions {
+            keep_going,
+            error_report: None,
+        }
+    }
+
+    fn read(file: &PathBuf) -> Result<usize> {
+        match file.to_str() {
+            Some("missing.md") => Err(MatterOfError::file_not_found(file)),
+            Some("bad.md") => Err(MatterOfError::validation("bad")),
+            _ => Ok(file.as_os_str().len()),
+        }
+    }
+
+    #[test]
+    fn test_keep_going_records_failures() {
+        let files: Vec<PathBuf> = ["a.md", "bad.md", "bb.md", "missing.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let executor = ParallelExecutor::new(Some(2));
+
+        let mut report = FailureReport::new("get", &options(true));
+        let succeeded = report.process(&executor, &files, read).unwrap();
+        assert_eq!(
+            succeeded,
+            vec![(PathBuf::from("a.md"), 4), (PathBuf::from("bb.md"), 5)]
+        );
+        assert_eq!(report.worst_severity(), Some(ErrorSeverity::Critical));
+        assert_eq!(exit_code(ErrorSeverity::Critical), 4);
+
+        let json = report.to_json();
+        assert_eq!(json["processed"], 4);
+        assert_eq!(json["failed"], 2);
+        assert_eq!(json["failures"][0]["file"], "bad.md");
+        assert_eq!(json["failures"][0]["severity"], "LOW");
+        assert_eq!(json["worst_severity"], "CRITICAL");
+        assert_eq!(report.finish().unwrap(), 4);
+
+        // Without --keep-going, the failures fail the command
+        let mut report = FailureReport::new("get", &options(false));
+        assert!(matches!(
+            report.process(&executor, &files, read),
+            Err(MatterOfError::Multiple { errors }) if errors.len() == 2
+        ));
+        assert_eq!(report.worst_severity(), None);
+    }
+}
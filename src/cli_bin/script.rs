@@ -0,0 +1,318 @@
+//! Operation scripts for the apply command
+//!
+//! A script is a YAML list of operations that are applied in order to each
+//! document in memory, so every file is written (and diffed, and backed up)
+//! only once:
+//!
+//! ```yaml
+//! - set: { query: draft, value: false }
+//! - add: { query: tags, value: migrated }
+//! - replace: { query: status, old_value: wip, value: draft }
+//! - rename: { query: categories, to: tags }
+//! - remove: { query: legacy, cleanup_empty: true }
+//! ```
+
+use crate::cli_bin::commands::{
+    add_jsonpath_value, remove_jsonpath_value, replace_jsonpath_value, set_jsonpath_value,
+    RemoveOptions, ReplaceOptions,
+};
+use log::debug;
+use matterof::core::{Document, FrontMatterValue, JsonPathQuery, ValueType};
+use matterof::error::{MatterOfError, Result};
+use serde::Deserialize;
+use serde_yaml::Value as YamlValue;
+use std::fs;
+use std::path::Path;
+
+/// A single script operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// Set the value at every match
+    Set(SetOperation),
+    /// Append to arrays, or add a key to objects
+    Add(AddOperation),
+    /// Remove every match
+    Remove(RemoveOperation),
+    /// Replace the value at every match
+    Replace(ReplaceOperation),
+    /// Rename the key of a single match
+    Rename(RenameOperation),
+}
+
+/// Arguments of a `set` operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetOperation {
+    pub query: String,
+    pub value: YamlValue,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+}
+
+/// Arguments of an `add` operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddOperation {
+    pub query: String,
+    pub value: YamlValue,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+    /// Key to add when the target is an object
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Index to insert at instead of appending
+    #[serde(default)]
+    pub index: Option<usize>,
+}
+
+/// Arguments of a `remove` operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoveOperation {
+    pub query: String,
+    /// Range of array elements to remove ("start:end")
+    #[serde(default)]
+    pub range: Option<String>,
+    #[serde(default)]
+    pub cleanup_empty: bool,
+}
+
+/// Arguments of a `replace` operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplaceOperation {
+    pub query: String,
+    pub value: YamlValue,
+    #[serde(default, rename = "type")]
+    pub type_: Option<String>,
+    /// Only replace values equal to this one
+    #[serde(default)]
+    pub old_value: Option<YamlValue>,
+}
+
+/// Arguments of a `rename` operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenameOperation {
+    pub query: String,
+    /// New key name
+    pub to: String,
+}
+
+impl Operation {
+    /// Name of the operation as written in scripts
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Set(_) => "set",
+            Self::Add(_) => "add",
+            Self::Remove(_) => "remove",
+            Self::Replace(_) => "replace",
+            Self::Rename(_) => "rename",
+        }
+    }
+
+    /// JSONPath query the operation targets
+    pub fn query(&self) -> &str {
+        match self {
+            Self::Set(op) => &op.query,
+            Self::Add(op) => &op.query,
+            Self::Remove(op) => &op.query,
+            Self::Replace(op) => &op.query,
+            Self::Rename(op) => &op.query,
+        }
+    }
+
+    /// Apply the operation to a document, returning whether anything changed
+    fn apply(&self, document: &mut Document, query: &JsonPathQuery) -> Result<bool> {
+        match self {
+            Self::Set(op) => {
+                let value = typed_value(&op.value, op.type_.as_deref())?;
+                set_jsonpath_value(document, query, &value)
+            }
+            Self::Add(op) => {
+                let value = typed_value(&op.value, op.type_.as_deref())?;
+                add_jsonpath_value(document, query, &value, op.key.as_deref(), op.index)
+            }
+            Self::Remove(op) => {
+                let options = RemoveOptions {
+                    range: op.range.as_deref(),
+                    force: true,
+                    cleanup_empty: op.cleanup_empty,
+                };
+                let modified = remove_jsonpath_value(document, query, &options)?;
+                if op.cleanup_empty {
+                    document.clean_empty_front_matter();
+                }
+                Ok(modified)
+            }
+            Self::Replace(op) => {
+                let options = ReplaceOptions {
+                    new_value: Some(typed_value(&op.value, op.type_.as_deref())?),
+                    old_value: op
+                        .old_value
+                        .as_ref()
+                        .map(|v| typed_value(v, op.type_.as_deref()))
+                        .transpose()?,
+                    ..Default::default()
+                };
+                replace_jsonpath_value(document, query, &options)
+            }
+            Self::Rename(op) => {
+                let options = ReplaceOptions {
+                    new_key: Some(&op.to),
+                    ..Default::default()
+                };
+                replace_jsonpath_value(document, query, &options)
+            }
+        }
+    }
+}
+
+/// An ordered list of operations with their compiled queries
+#[derive(Debug, Clone)]
+pub struct Script {
+    operations: Vec<(Operation, JsonPathQuery)>,
+}
+
+impl Script {
+    /// Parse a script from YAML text
+    pub fn parse(content: &str) -> Result<Self> {
+        let invalid =
+            |e: &dyn std::fmt::Display| MatterOfError::validation(format!("Invalid script: {}", e));
+        // serde_yaml expects `!tag` syntax for enums, so the single-key maps
+        // are deserialized through a JSON value instead
+        let value: YamlValue = serde_yaml::from_str(content).map_err(|e| invalid(&e))?;
+        let value = serde_json::to_value(value).map_err(|e| invalid(&e))?;
+        let operations: Vec<Operation> = serde_json::from_value(value).map_err(|e| invalid(&e))?;
+
+        let operations = operations
+            .into_iter()
+            .map(|op| {
+                let query = JsonPathQuery::new(op.query())?;
+                Ok((op, query))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { operations })
+    }
+
+    /// Load a script from a YAML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(MatterOfError::file_not_found(path));
+        }
+        let content = fs::read_to_string(path).map_err(MatterOfError::Io)?;
+        Self::parse(&content)
+    }
+
+    /// Number of operations in the script
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Apply all operations in order, returning how many changed the document
+    pub fn apply(&self, document: &mut Document) -> Result<usize> {
+        let mut changed = 0;
+        for (index, (operation, query)) in self.operations.iter().enumerate() {
+            let modified = operation.apply(document, query).map_err(|e| {
+                MatterOfError::validation(format!(
+                    "Operation {} ({} {}) failed: {}",
+                    index + 1,
+                    operation.name(),
+                    operation.query(),
+                    e
+                ))
+            })?;
+            debug!(
+                "Operation {} ({} {}): {}",
+                index + 1,
+                operation.name(),
+                operation.query(),
+                if modified { "modified" } else { "no change" }
+            );
+            if modified {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Convert a script value, parsing strings with the optional type hint
+fn typed_value(value: &YamlValue, type_name: Option<&str>) -> Result<FrontMatterValue> {
+    let Some(type_name) = type_name else {
+        return Ok(FrontMatterValue::new(value.clone()));
+    };
+    let value_type = ValueType::from_name(type_name)
+        .ok_or_else(|| MatterOfError::validation(format!("Unknown value type: {}", type_name)))?;
+
+    match value {
+        YamlValue::String(s) => FrontMatterValue::parse_from_string(s, Some(&value_type)),
+        other => Ok(FrontMatterValue::new(other.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matterof::core::KeyPath;
+    use matterof::io::FrontMatterReader;
+
+    fn document(content: &str) -> Document {
+        FrontMatterReader::new()
+            .parse_content(content, None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script = Script::parse(
+            "- set: { query: draft, value: false }\n- rename: { query: cats, to: categories }\n",
+        )
+        .unwrap();
+        assert_eq!(script.len(), 2);
+
+        assert!(Script::parse("- frobnicate: { query: x }\n").is_err());
+        assert!(Script::parse("- set: { query: x }\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_in_order() {
+        let script = Script::parse(
+            r#"
+- set: { query: draft, value: false }
+- add: { query: tags, value: migrated }
+- replace: { query: status, old_value: wip, value: draft }
+- rename: { query: cats, to: categories }
+- remove: { query: legacy }
+- set: { query: weight, value: "5", type: int }
+- set: { query: missing, value: 1 }
+"#,
+        )
+        .unwrap();
+
+        let mut doc = document(
+            "---\ndraft: true\ntags: [rust]\nstatus: wip\ncats: [a]\nlegacy: x\nweight: 1\n---\nBody",
+        );
+        assert_eq!(script.apply(&mut doc).unwrap(), 6);
+
+        let get = |key: &str| doc.get(&KeyPath::parse(key).unwrap());
+        assert_eq!(get("draft").unwrap().as_bool(), Some(false));
+        assert_eq!(get("tags.1").unwrap().as_string(), Some("migrated"));
+        assert_eq!(get("status").unwrap().as_string(), Some("draft"));
+        assert!(get("cats").is_none());
+        assert_eq!(get("categories.0").unwrap().as_string(), Some("a"));
+        assert!(get("legacy").is_none());
+        assert_eq!(get("weight").unwrap().as_int(), Some(5));
+    }
+
+    #[test]
+    fn test_failed_operation_is_reported() {
+        let script = Script::parse("- add: { query: title, value: x }\n").unwrap();
+        let err = script
+            .apply(&mut document("---\ntitle: Hello\n---\n"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Operation 1 (add title)"));
+    }
+}
@@ -0,0 +1,113 @@
+//! Template lookup for the init and new commands
+//!
+//! `--template` takes a path, or the name of a template in a template
+//! directory: `.matterof/templates/` of the project (the nearest ancestor
+//! holding a `.matterof` directory), then `matterof/templates/` in the user's
+//! config directory (`$XDG_CONFIG_HOME`, by default `~/.config`). Names may
+//! leave out the extension. Markdown templates carry a body skeleton below
+//! their front matter; YAML and JSON templates only hold front matter.
+
+use matterof::core::Template;
+use matterof::error::{MatterOfError, Result};
+use matterof::io::journal::STATE_DIR;
+use matterof::io::FrontMatterReader;
+use std::path::{Path, PathBuf};
+
+/// Extensions tried, in order, for template names given without one
+const TEMPLATE_EXTENSIONS: &[&str] = &["md", "markdown", "yaml", "yml", "json"];
+
+/// Directories searched for named templates, most specific first
+pub fn template_dirs(cwd: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(root) = cwd.ancestors().find(|dir| dir.join(STATE_DIR).is_dir()) {
+        dirs.push(root.join(STATE_DIR).join("templates"));
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    if let Some(config_dir) = config_dir {
+        dirs.push(config_dir.join("matterof").join("templates"));
+    }
+    dirs
+}
+
+/// Find the file of a template given as a path or a name
+pub fn resolve_template(spec: &Path, dirs: &[PathBuf]) -> Result<PathBuf> {
+    if spec.is_file() {
+        return Ok(spec.to_path_buf());
+    }
+
+    for dir in dirs {
+        let candidate = dir.join(spec);
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+        if candidate.extension().is_none() {
+            for extension in TEMPLATE_EXTENSIONS {
+                let candidate = candidate.with_extension(extension);
+                if candidate.is_file() {
+                    return Ok(candidate);
+                }
+            }
+        }
+    }
+
+    let searched: Vec<_> = dirs.iter().map(|dir| dir.display().to_string()).collect();
+    Err(MatterOfError::validation(format!(
+        "Template '{}' not found (searched {})",
+        spec.display(),
+        searched.join(", ")
+    )))
+}
+
+/// Load a template given as a path or a name
+pub fn load_template(spec: &Path) -> Result<Template> {
+    let cwd = std::env::current_dir()?;
+    let path = resolve_template(spec, &template_dirs(&cwd))?;
+    if FrontMatterReader::is_markdown_file(&path) {
+        let document = FrontMatterReader::new().read_file(&path)?;
+        Ok(Template::from_document(&document))
+    } else {
+        Template::from_file(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        let user = temp_dir.path().join("user");
+        fs::create_dir_all(project.join(".matterof/templates")).unwrap();
+        fs::create_dir_all(&user).unwrap();
+        fs::write(project.join(".matterof/templates/post.md"), "---\n---\n").unwrap();
+        fs::write(user.join("post.yaml"), "{}").unwrap();
+        fs::write(user.join("note.yml"), "{}").unwrap();
+
+        let mut dirs = template_dirs(&project.join("content/posts"));
+        assert_eq!(dirs[0], project.join(".matterof/templates"));
+        dirs.truncate(1);
+        dirs.push(user.clone());
+
+        // The project directory wins, extensions are optional
+        assert_eq!(
+            resolve_template(Path::new("post"), &dirs).unwrap(),
+            project.join(".matterof/templates/post.md")
+        );
+        assert_eq!(
+            resolve_template(Path::new("post.yaml"), &dirs).unwrap(),
+            user.join("post.yaml")
+        );
+        assert_eq!(
+            resolve_template(Path::new("note"), &dirs).unwrap(),
+            user.join("note.yml")
+        );
+        assert!(resolve_template(Path::new("missing"), &dirs).is_err());
+    }
+}
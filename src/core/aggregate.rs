@@ -0,0 +1,275 @@
+//! Cross-file aggregation of JSONPath matches
+//!
+//! An [`Aggregator`] runs a JSONPath query over the front matter of many
+//! documents and summarizes the matches: how many there are, their distinct
+//! values with counts, and sum/min/max of the numeric ones. With a group-by
+//! query, documents are bucketed by the values that query matches (a post
+//! with two authors counts for both) and each bucket is summarized on its own.
+
+use crate::core::{Document, JsonPathQuery, YamlJsonConverter};
+use crate::error::Result;
+use serde_yaml::Value as YamlValue;
+use std::cmp::Ordering;
+
+/// Summary of numeric matches
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumericStats {
+    /// Number of numeric matches
+    pub count: usize,
+    pub sum: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Whether every numeric match was an integer
+    pub integral: bool,
+}
+
+impl NumericStats {
+    fn observe(&mut self, number: &serde_yaml::Number) {
+        let Some(value) = number.as_f64() else {
+            return;
+        };
+        self.integral = (self.count == 0 || self.integral) && !number.is_f64();
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+
+    /// Render a statistic as an integer when all inputs were integers
+    pub fn to_value(&self, value: f64) -> YamlValue {
+        if (self.integral || self.count == 0)
+            && value.fract() == 0.0
+            && value.abs() < i64::MAX as f64
+        {
+            YamlValue::Number((value as i64).into())
+        } else {
+            YamlValue::Number(value.into())
+        }
+    }
+}
+
+/// Aggregated matches of one group (or of all documents without grouping)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupSummary {
+    /// Group value, `None` for documents the group-by query did not match
+    /// (and for the single group when there is no group-by query)
+    pub key: Option<YamlValue>,
+    /// Number of documents in the group
+    pub files: usize,
+    /// Number of query matches
+    pub matches: usize,
+    /// Distinct matched values with their counts, in first-seen order
+    pub values: Vec<(YamlValue, usize)>,
+    /// Statistics of the numeric matches
+    pub numeric: NumericStats,
+    /// Number of matches that were not numbers
+    pub non_numeric: usize,
+}
+
+impl GroupSummary {
+    fn new(key: Option<YamlValue>) -> Self {
+        Self {
+            key,
+            ..Default::default()
+        }
+    }
+
+    fn observe(&mut self, value: YamlValue) {
+        self.matches += 1;
+        match &value {
+            YamlValue::Number(number) => self.numeric.observe(number),
+            _ => self.non_numeric += 1,
+        }
+        match self.values.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => self.values.push((value, 1)),
+        }
+    }
+
+    /// Distinct matched values, sorted
+    pub fn distinct(&self) -> Vec<&YamlValue> {
+        let mut values: Vec<_> = self.values.iter().map(|(value, _)| value).collect();
+        values.sort_by(|a, b| compare_values(a, b));
+        values
+    }
+
+    /// Distinct matched values with counts, most frequent first
+    pub fn counts(&self) -> Vec<(&YamlValue, usize)> {
+        let mut counts: Vec<_> = self.values.iter().map(|(v, c)| (v, *c)).collect();
+        counts.sort_by(|(a, ca), (b, cb)| cb.cmp(ca).then_with(|| compare_values(a, b)));
+        counts
+    }
+}
+
+/// Aggregates JSONPath matches across documents
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    query: Option<JsonPathQuery>,
+    group_by: Option<JsonPathQuery>,
+    groups: Vec<GroupSummary>,
+}
+
+impl Aggregator {
+    /// Aggregate the matches of `query`; without a query only documents are counted
+    pub fn new(query: Option<JsonPathQuery>) -> Self {
+        Self {
+            query,
+            group_by: None,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Bucket documents by the values matched by `group_by`
+    pub fn with_group_by(mut self, group_by: JsonPathQuery) -> Self {
+        self.group_by = Some(group_by);
+        self
+    }
+
+    /// Whether documents are grouped
+    pub fn is_grouped(&self) -> bool {
+        self.group_by.is_some()
+    }
+
+    /// Add the front matter of a document
+    pub fn add_document(&mut self, document: &Document) -> Result<()> {
+        let json = YamlJsonConverter::yaml_to_json(&document.to_yaml_value())?;
+
+        let matches = match &self.query {
+            Some(query) => query
+                .query(&json)
+                .into_iter()
+                .map(YamlJsonConverter::json_to_yaml)
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        let mut keys = match &self.group_by {
+            Some(group_by) => group_by
+                .query(&json)
+                .into_iter()
+                .map(|value| YamlJsonConverter::json_to_yaml(value).map(Some))
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![None],
+        };
+        if keys.is_empty() {
+            keys.push(None);
+        }
+        keys.dedup();
+
+        for key in keys {
+            let group = match self.groups.iter().position(|g| g.key == key) {
+                Some(index) => &mut self.groups[index],
+                None => {
+                    self.groups.push(GroupSummary::new(key));
+                    self.groups.last_mut().expect("group was just added")
+                }
+            };
+            group.files += 1;
+            for value in &matches {
+                group.observe(value.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Summaries of all groups, sorted by group value (ungrouped documents last)
+    pub fn groups(&self) -> Vec<&GroupSummary> {
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_by(|a, b| match (&a.key, &b.key) {
+            (Some(a), Some(b)) => compare_values(a, b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        groups
+    }
+
+    /// Summary of all documents when not grouping
+    pub fn total(&self) -> GroupSummary {
+        let mut total = GroupSummary::new(None);
+        for group in &self.groups {
+            total.files += group.files;
+            for (value, count) in &group.values {
+                for _ in 0..*count {
+                    total.observe(value.clone());
+                }
+            }
+        }
+        total
+    }
+}
+
+/// Order values by number when both are numbers, otherwise by their natural order
+fn compare_values(a: &YamlValue, b: &YamlValue) -> Ordering {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::FrontMatterReader;
+
+    fn documents() -> Vec<Document> {
+        let reader = FrontMatterReader::new();
+        [
+            "---\nauthor: alice\ntags: [rust, cli]\nwords: 100\n---\n",
+            "---\nauthor: bob\ntags: [rust]\nwords: 250\n---\n",
+            "---\nauthor: alice\ntags: [yaml, rust]\nwords: 50\n---\n",
+            "---\ntitle: no author\nwords: n/a\n---\n",
+        ]
+        .iter()
+        .map(|content| reader.parse_content(content, None).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_distinct_and_counts() {
+        let mut aggregator = Aggregator::new(Some(JsonPathQuery::new("tags[*]").unwrap()));
+        for document in documents() {
+            aggregator.add_document(&document).unwrap();
+        }
+
+        let total = aggregator.total();
+        assert_eq!(total.files, 4);
+        assert_eq!(total.matches, 5);
+        assert_eq!(
+            total.distinct(),
+            vec![
+                &YamlValue::from("cli"),
+                &YamlValue::from("rust"),
+                &YamlValue::from("yaml")
+            ]
+        );
+        assert_eq!(total.counts()[0], (&YamlValue::from("rust"), 3));
+    }
+
+    #[test]
+    fn test_group_by_with_numeric_stats() {
+        let mut aggregator = Aggregator::new(Some(JsonPathQuery::new("words").unwrap()))
+            .with_group_by(JsonPathQuery::new("author").unwrap());
+        for document in documents() {
+            aggregator.add_document(&document).unwrap();
+        }
+
+        let groups = aggregator.groups();
+        assert_eq!(groups.len(), 3);
+
+        let alice = groups[0];
+        assert_eq!(alice.key, Some(YamlValue::from("alice")));
+        assert_eq!(alice.files, 2);
+        assert_eq!(
+            alice.numeric.to_value(alice.numeric.sum),
+            YamlValue::from(150)
+        );
+        assert_eq!(alice.numeric.min, Some(50.0));
+        assert_eq!(alice.numeric.max, Some(100.0));
+
+        // Documents without an author end up in the trailing ungrouped bucket
+        assert_eq!(groups[2].key, None);
+        assert_eq!(groups[2].non_numeric, 1);
+        assert_eq!(groups[2].numeric.count, 0);
+    }
+}
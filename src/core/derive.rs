@@ -0,0 +1,461 @@
+//! Deriving front matter from the markdown body
+//!
+//! An [`Extractor`] computes a value from a document's body, such as its
+//! title from the first `# Heading` or its reading time from the word count.
+//! Extractors are looked up by name in an [`ExtractorRegistry`], which comes
+//! with the built-in ones and accepts custom implementations. The extractors
+//! working from file metadata and git history live in [`crate::core::metadata`].
+
+use crate::core::metadata::{
+    CreatedTimeExtractor, FilenameDateExtractor, FilenameSlugExtractor, GitExtractor, GitField,
+    GitHistory, ModifiedTimeExtractor,
+};
+use crate::core::{Document, FrontMatterValue};
+use crate::error::Result;
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Computes a front matter value from a document
+pub trait Extractor: Send + Sync {
+    /// Name the extractor is registered under
+    fn name(&self) -> &str;
+
+    /// Short description for listings
+    fn description(&self) -> &str;
+
+    /// Extract the value, or `None` if the document has nothing to offer
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>>;
+
+    /// Extract the value for a document read from `path`
+    ///
+    /// Body extractors ignore the path; extractors that need the file override this.
+    fn extract_file(&self, document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        let _ = path;
+        self.extract(document)
+    }
+}
+
+/// Text of the first level-1 heading
+#[derive(Debug, Clone, Default)]
+pub struct TitleExtractor;
+
+impl Extractor for TitleExtractor {
+    fn name(&self) -> &str {
+        "title"
+    }
+
+    fn description(&self) -> &str {
+        "text of the first level-1 heading"
+    }
+
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>> {
+        let title = outline(document.body())
+            .into_iter()
+            .find(|(level, _)| *level == 1)
+            .map(|(_, text)| FrontMatterValue::string(text));
+        Ok(title)
+    }
+}
+
+/// Text of the first paragraph, shortened at a word boundary
+#[derive(Debug, Clone)]
+pub struct DescriptionExtractor {
+    /// Longest description in characters, including the ellipsis
+    pub max_length: usize,
+}
+
+impl Default for DescriptionExtractor {
+    fn default() -> Self {
+        Self { max_length: 160 }
+    }
+}
+
+impl Extractor for DescriptionExtractor {
+    fn name(&self) -> &str {
+        "description"
+    }
+
+    fn description(&self) -> &str {
+        "text of the first paragraph"
+    }
+
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>> {
+        let mut text = String::new();
+        let mut in_paragraph = false;
+        for event in Parser::new(document.body()) {
+            match event {
+                Event::Start(Tag::Paragraph) => in_paragraph = true,
+                Event::End(TagEnd::Paragraph) if !text.trim().is_empty() => break,
+                Event::End(TagEnd::Paragraph) => in_paragraph = false,
+                Event::Text(t) | Event::Code(t) if in_paragraph => text.push_str(&t),
+                Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+                _ => {}
+            }
+        }
+
+        let text = collapse_whitespace(&text);
+        if text.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(FrontMatterValue::string(truncate(
+            &text,
+            self.max_length,
+        ))))
+    }
+}
+
+/// Number of words in the body, outside code blocks
+#[derive(Debug, Clone, Default)]
+pub struct WordCountExtractor;
+
+impl Extractor for WordCountExtractor {
+    fn name(&self) -> &str {
+        "word_count"
+    }
+
+    fn description(&self) -> &str {
+        "number of words outside code blocks"
+    }
+
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(Some(FrontMatterValue::int(
+            word_count(document.body()) as i64
+        )))
+    }
+}
+
+/// Minutes needed to read the body, rounded up
+#[derive(Debug, Clone)]
+pub struct ReadingTimeExtractor {
+    pub words_per_minute: usize,
+}
+
+impl Default for ReadingTimeExtractor {
+    fn default() -> Self {
+        Self {
+            words_per_minute: 200,
+        }
+    }
+}
+
+impl Extractor for ReadingTimeExtractor {
+    fn name(&self) -> &str {
+        "reading_time"
+    }
+
+    fn description(&self) -> &str {
+        "minutes to read the body, rounded up"
+    }
+
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>> {
+        let words = word_count(document.body());
+        let minutes = words.div_ceil(self.words_per_minute.max(1));
+        Ok(Some(FrontMatterValue::int(minutes as i64)))
+    }
+}
+
+/// Outline of all headings as a list of `{level, text}` mappings
+#[derive(Debug, Clone, Default)]
+pub struct HeadingsExtractor;
+
+impl Extractor for HeadingsExtractor {
+    fn name(&self) -> &str {
+        "headings"
+    }
+
+    fn description(&self) -> &str {
+        "outline of all headings with their levels"
+    }
+
+    fn extract(&self, document: &Document) -> Result<Option<FrontMatterValue>> {
+        let headings: Vec<_> = outline(document.body())
+            .into_iter()
+            .map(|(level, text)| {
+                let mut heading = serde_yaml::Mapping::new();
+                heading.insert("level".into(), level.into());
+                heading.insert("text".into(), text.into());
+                FrontMatterValue::new(serde_yaml::Value::Mapping(heading))
+            })
+            .collect();
+
+        if headings.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(FrontMatterValue::array(headings)))
+    }
+}
+
+/// Extractors available by name
+pub struct ExtractorRegistry {
+    extractors: Vec<Box<dyn Extractor>>,
+}
+
+impl ExtractorRegistry {
+    /// Create a registry with the built-in extractors
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(TitleExtractor);
+        registry.register(DescriptionExtractor::default());
+        registry.register(WordCountExtractor);
+        registry.register(ReadingTimeExtractor::default());
+        registry.register(HeadingsExtractor);
+        registry.register_file_extractors(None);
+        registry
+    }
+
+    /// Register the file metadata and git extractors, formatting dates with a
+    /// `strftime` pattern or as RFC 3339
+    pub fn register_file_extractors(&mut self, date_format: Option<&str>) {
+        let date_format = date_format.map(str::to_string);
+        self.register(ModifiedTimeExtractor {
+            date_format: date_format.clone(),
+        });
+        self.register(CreatedTimeExtractor {
+            date_format: date_format.clone(),
+        });
+        self.register(FilenameDateExtractor);
+        self.register(FilenameSlugExtractor);
+        let history = Arc::new(GitHistory::new());
+        for field in GitField::ALL {
+            self.register(
+                GitExtractor::new(field, history.clone()).with_date_format(date_format.clone()),
+            );
+        }
+    }
+
+    /// Create a registry without any extractors
+    pub fn empty() -> Self {
+        Self {
+            extractors: Vec::new(),
+        }
+    }
+
+    /// Add an extractor, replacing any registered under the same name in place
+    pub fn register<E: Extractor + 'static>(&mut self, extractor: E) {
+        match self
+            .extractors
+            .iter()
+            .position(|e| e.name() == extractor.name())
+        {
+            Some(index) => self.extractors[index] = Box::new(extractor),
+            None => self.extractors.push(Box::new(extractor)),
+        }
+    }
+
+    /// Look up an extractor by name
+    pub fn get(&self, name: &str) -> Option<&dyn Extractor> {
+        self.extractors
+            .iter()
+            .find(|e| e.name() == name)
+            .map(|e| e.as_ref())
+    }
+
+    /// All registered extractors, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Extractor> {
+        self.extractors.iter().map(|e| e.as_ref())
+    }
+}
+
+impl Default for ExtractorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ExtractorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.extractors.iter().map(|e| e.name()))
+            .finish()
+    }
+}
+
+/// Levels and plain texts of all headings
+fn outline(body: &str) -> Vec<(u8, String)> {
+    let mut headings = Vec::new();
+    let mut current: Option<(u8, String)> = None;
+    for event in Parser::new(body) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((heading_level(level), String::new()))
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, text)) = current.take() {
+                    headings.push((level, collapse_whitespace(&text)));
+                }
+            }
+            Event::Text(t) | Event::Code(t) => {
+                if let Some((_, text)) = current.as_mut() {
+                    text.push_str(&t);
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Count the words outside code blocks
+///
+/// The inline text of each block is joined before splitting, so `foo**bar**`
+/// is one word and the `.` after a link is not a word of its own.
+fn word_count(body: &str) -> usize {
+    let mut in_code_block = false;
+    let mut block = String::new();
+    let mut count = 0;
+    for event in Parser::new(body) {
+        match event {
+            Event::Text(t) | Event::Code(t) => {
+                if !in_code_block {
+                    block.push_str(&t);
+                }
+            }
+            Event::Start(tag) if is_inline(&tag.to_end()) => {}
+            Event::End(tag) if is_inline(&tag) => {}
+            event => {
+                match event {
+                    Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+                    Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                    _ => {}
+                }
+                count += block.split_whitespace().count();
+                block.clear();
+            }
+        }
+    }
+    count + block.split_whitespace().count()
+}
+
+fn is_inline(tag: &TagEnd) -> bool {
+    matches!(
+        tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Shorten text to at most `max_length` characters, cutting at a word boundary
+fn truncate(text: &str, max_length: usize) -> String {
+    if text.chars().count() <= max_length {
+        return text.to_string();
+    }
+    let limit = max_length.saturating_sub(1);
+    let mut cut: String = text.chars().take(limit).collect();
+    // Drop the partial word unless the cut fell right before a space
+    if !text.chars().nth(limit).is_some_and(char::is_whitespace) {
+        if let Some(space) = cut.rfind(' ') {
+            cut.truncate(space);
+        }
+    }
+    let cut = cut.trim_end_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation());
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "\
+# Hello `matterof`
+
+Front matter is *the* metadata
+of a post.
+
+```sh
+matterof derive --field title
+```
+
+## Usage
+
+### Flags
+";
+
+    fn extract(extractor: &dyn Extractor, body: &str) -> Option<serde_yaml::Value> {
+        let document = Document::body_only(body.to_string());
+        extractor
+            .extract(&document)
+            .unwrap()
+            .map(|value| value.as_inner().clone())
+    }
+
+    #[test]
+    fn test_title_and_description() {
+        assert_eq!(
+            extract(&TitleExtractor, BODY),
+            Some("Hello matterof".into())
+        );
+        assert_eq!(
+            extract(&DescriptionExtractor::default(), BODY),
+            Some("Front matter is the metadata of a post.".into())
+        );
+        assert_eq!(
+            extract(&DescriptionExtractor { max_length: 20 }, BODY),
+            Some("Front matter is the…".into())
+        );
+        assert_eq!(extract(&TitleExtractor, "## Only h2\n"), None);
+    }
+
+    #[test]
+    fn test_word_count_and_reading_time() {
+        // Code blocks are not counted
+        assert_eq!(extract(&WordCountExtractor, BODY), Some(12.into()));
+        assert_eq!(
+            extract(&ReadingTimeExtractor::default(), BODY),
+            Some(1.into())
+        );
+        let extractor = ReadingTimeExtractor {
+            words_per_minute: 5,
+        };
+        assert_eq!(extract(&extractor, BODY), Some(3.into()));
+
+        // Inline markup does not split or add words
+        assert_eq!(
+            extract(&WordCountExtractor, "see [docs](x).\n"),
+            Some(2.into())
+        );
+        assert_eq!(extract(&WordCountExtractor, "foo**bar**\n"), Some(1.into()));
+        assert_eq!(
+            extract(&WordCountExtractor, "one\ntwo\n\n- three\n- four\n"),
+            Some(4.into())
+        );
+    }
+
+    #[test]
+    fn test_headings_and_registry() {
+        let headings = extract(&HeadingsExtractor, BODY).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "[{level: 1, text: Hello matterof}, {level: 2, text: Usage}, {level: 3, text: Flags}]",
+        )
+        .unwrap();
+        assert_eq!(headings, expected);
+
+        let mut registry = ExtractorRegistry::new();
+        assert!(registry.get("reading_time").is_some());
+        assert!(registry.get("nope").is_none());
+        registry.register(ReadingTimeExtractor {
+            words_per_minute: 5,
+        });
+        assert_eq!(registry.iter().count(), 13);
+    }
+}
@@ -0,0 +1,449 @@
+//! Semantic diff of front matter
+//!
+//! Compares the front matter of two documents value by value and reports the
+//! changes per NormalizedPath instead of per rendered line. Array elements are
+//! aligned with a sequence diff, so inserting one tag reports a single
+//! addition rather than a change of every following index, and a value that
+//! disappears in one place and shows up in another is reported as moved.
+//! Paths are spelled exactly like the NormalizedPaths of query results, so a
+//! change can be looked up with `matterof get --format internal`.
+
+use crate::core::{Document, NormalizedPathUtils, PathSegment};
+use serde::Serialize;
+use serde_yaml::Value as YamlValue;
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Kind of a front matter change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The path only exists in the new document
+    Added,
+    /// The path only exists in the old document
+    Removed,
+    /// The value at the path differs
+    Changed,
+    /// The value was removed at one path and added at another
+    Moved,
+}
+
+/// A single change at a NormalizedPath
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrontMatterChange {
+    pub kind: ChangeKind,
+    /// Path of the change (the destination for moves)
+    pub path: String,
+    /// Original path of a moved value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// Value before the change
+    #[serde(rename = "old", skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<YamlValue>,
+    /// Value after the change
+    #[serde(rename = "new", skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<YamlValue>,
+}
+
+impl fmt::Display for FrontMatterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<YamlValue>| value.as_ref().map(format_value).unwrap_or_default();
+        match self.kind {
+            ChangeKind::Added => write!(f, "{} added {}", self.path, show(&self.new_value)),
+            ChangeKind::Removed => write!(f, "{} removed {}", self.path, show(&self.old_value)),
+            ChangeKind::Changed => write!(
+                f,
+                "{} changed {} → {}",
+                self.path,
+                show(&self.old_value),
+                show(&self.new_value)
+            ),
+            ChangeKind::Moved => write!(
+                f,
+                "{} moved from {} {}",
+                self.path,
+                self.from.as_deref().unwrap_or_default(),
+                show(&self.new_value)
+            ),
+        }
+    }
+}
+
+/// Path-level changes between two versions of front matter
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct FrontMatterDiff {
+    changes: Vec<FrontMatterChange>,
+}
+
+impl FrontMatterDiff {
+    /// Compare the front matter of two documents
+    pub fn between(old: &Document, new: &Document) -> Self {
+        Self::between_values(&old.to_yaml_value(), &new.to_yaml_value())
+    }
+
+    /// Compare two front matter values
+    pub fn between_values(old: &YamlValue, new: &YamlValue) -> Self {
+        let empty = YamlValue::Mapping(Default::default());
+        let old = if old.is_null() { &empty } else { old };
+        let new = if new.is_null() { &empty } else { new };
+
+        let mut changes = Vec::new();
+        compare(old, new, &mut Vec::new(), &mut changes);
+        Self {
+            changes: pair_moves(changes),
+        }
+    }
+
+    /// The changes, in document order
+    pub fn changes(&self) -> &[FrontMatterChange] {
+        &self.changes
+    }
+
+    /// Keep only the changes for which `keep` returns true
+    pub fn retain(&mut self, keep: impl FnMut(&FrontMatterChange) -> bool) {
+        self.changes.retain(keep);
+    }
+
+    /// Drop the changes at or below any of the given NormalizedPaths
+    pub fn ignore_paths(&mut self, paths: &[String]) {
+        let ignored = |path: &str| paths.iter().any(|prefix| is_within(path, prefix));
+        self.changes.retain(|change| {
+            !ignored(&change.path) && !change.from.as_deref().is_some_and(ignored)
+        });
+    }
+
+    /// Number of changes
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Whether the front matter is the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Iterate over the changes
+    pub fn iter(&self) -> std::slice::Iter<'_, FrontMatterChange> {
+        self.changes.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FrontMatterDiff {
+    type Item = &'a FrontMatterChange;
+    type IntoIter = std::slice::Iter<'a, FrontMatterChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+fn change(
+    kind: ChangeKind,
+    path: &[PathSegment],
+    old_value: Option<&YamlValue>,
+    new_value: Option<&YamlValue>,
+) -> FrontMatterChange {
+    FrontMatterChange {
+        kind,
+        path: NormalizedPathUtils::format_segments(path),
+        from: None,
+        old_value: old_value.cloned(),
+        new_value: new_value.cloned(),
+    }
+}
+
+fn compare(
+    old: &YamlValue,
+    new: &YamlValue,
+    path: &mut Vec<PathSegment>,
+    changes: &mut Vec<FrontMatterChange>,
+) {
+    match (old, new) {
+        (YamlValue::Mapping(old_map), YamlValue::Mapping(new_map)) => {
+            for (key, old_value) in old_map {
+                path.push(PathSegment::Property(key_name(key)));
+                match new_map.get(key) {
+                    Some(new_value) => compare(old_value, new_value, path, changes),
+                    None => changes.push(change(ChangeKind::Removed, path, Some(old_value), None)),
+                }
+                path.pop();
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    path.push(PathSegment::Property(key_name(key)));
+                    changes.push(change(ChangeKind::Added, path, None, Some(new_value)));
+                    path.pop();
+                }
+            }
+        }
+        (YamlValue::Sequence(old_items), YamlValue::Sequence(new_items)) => {
+            compare_sequences(old_items, new_items, path, changes)
+        }
+        _ if old == new => {}
+        _ => changes.push(change(ChangeKind::Changed, path, Some(old), Some(new))),
+    }
+}
+
+/// Align array elements so insertions and deletions do not shift every index
+fn compare_sequences(
+    old_items: &[YamlValue],
+    new_items: &[YamlValue],
+    path: &mut Vec<PathSegment>,
+    changes: &mut Vec<FrontMatterChange>,
+) {
+    let removed = |index: usize, path: &mut Vec<PathSegment>, changes: &mut Vec<_>| {
+        path.push(PathSegment::Index(index));
+        changes.push(change(
+            ChangeKind::Removed,
+            path,
+            Some(&old_items[index]),
+            None,
+        ));
+        path.pop();
+    };
+    let added = |index: usize, path: &mut Vec<PathSegment>, changes: &mut Vec<_>| {
+        path.push(PathSegment::Index(index));
+        changes.push(change(
+            ChangeKind::Added,
+            path,
+            None,
+            Some(&new_items[index]),
+        ));
+        path.pop();
+    };
+
+    // Values are not `Ord`, so the sequences are diffed as ids of equal values
+    let mut ids = HashMap::new();
+    let mut intern = |items: &[YamlValue]| -> Vec<usize> {
+        items
+            .iter()
+            .map(|item| {
+                let next = ids.len();
+                *ids.entry(item.clone()).or_insert(next)
+            })
+            .collect()
+    };
+    let old_ids = intern(old_items);
+    let new_ids = intern(new_items);
+
+    for op in capture_diff_slices(Algorithm::Myers, &old_ids, &new_ids) {
+        match op {
+            DiffOp::Equal { .. } => {}
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => {
+                for i in old_index..old_index + old_len {
+                    removed(i, path, changes);
+                }
+            }
+            DiffOp::Insert {
+                new_index, new_len, ..
+            } => {
+                for i in new_index..new_index + new_len {
+                    added(i, path, changes);
+                }
+            }
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => {
+                // Elements replaced position by position are compared in depth
+                let paired = old_len.min(new_len);
+                for i in 0..paired {
+                    path.push(PathSegment::Index(new_index + i));
+                    compare(
+                        &old_items[old_index + i],
+                        &new_items[new_index + i],
+                        path,
+                        changes,
+                    );
+                    path.pop();
+                }
+                for i in old_index + paired..old_index + old_len {
+                    removed(i, path, changes);
+                }
+                for i in new_index + paired..new_index + new_len {
+                    added(i, path, changes);
+                }
+            }
+        }
+    }
+}
+
+/// Turn a removal and an addition of the same value into a move
+fn pair_moves(changes: Vec<FrontMatterChange>) -> Vec<FrontMatterChange> {
+    let mut changes: Vec<Option<FrontMatterChange>> = changes.into_iter().map(Some).collect();
+
+    for added in 0..changes.len() {
+        let Some(FrontMatterChange {
+            kind: ChangeKind::Added,
+            new_value: Some(value),
+            ..
+        }) = &changes[added]
+        else {
+            continue;
+        };
+
+        let source = changes.iter().position(|candidate| {
+            matches!(
+                candidate,
+                Some(FrontMatterChange {
+                    kind: ChangeKind::Removed,
+                    old_value: Some(old),
+                    ..
+                }) if old == value
+            )
+        });
+
+        if let Some(source) = source {
+            let from = changes[source].take().map(|removed| removed.path);
+            if let Some(moved) = changes[added].as_mut() {
+                moved.kind = ChangeKind::Moved;
+                moved.from = from;
+            }
+        }
+    }
+
+    changes.into_iter().flatten().collect()
+}
+
+/// Whether a NormalizedPath equals `prefix` or lies below it
+fn is_within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('['))
+}
+
+fn key_name(key: &YamlValue) -> String {
+    match key {
+        YamlValue::String(s) => s.clone(),
+        other => format_value(other),
+    }
+}
+
+/// Short single-line rendering of a value, strings in single quotes
+fn format_value(value: &YamlValue) -> String {
+    match value {
+        YamlValue::String(s) => format!("'{}'", s),
+        YamlValue::Null => "null".to_string(),
+        YamlValue::Bool(b) => b.to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        YamlValue::Tagged(tagged) => format!("{} {}", tagged.tag, format_value(&tagged.value)),
+        other => serde_json::to_string(other).unwrap_or_else(|_| format!("{:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::FrontMatterReader;
+
+    fn diff(old: &str, new: &str) -> Vec<String> {
+        let reader = FrontMatterReader::new();
+        let old = reader.parse_content(old, None).unwrap();
+        let new = reader.parse_content(new, None).unwrap();
+        FrontMatterDiff::between(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_scalar_changes() {
+        assert_eq!(
+            diff(
+                "---\ntitle: A\ndraft: true\nold: 1\n---\n",
+                "---\ntitle: A\ndraft: false\nnew: [x]\n---\n"
+            ),
+            vec![
+                "$['draft'] changed true → false",
+                "$['old'] removed 1",
+                "$['new'] added [\"x\"]",
+            ]
+        );
+        assert!(diff("---\na: 1\n---\nBody", "---\na: 1\n---\nOther body").is_empty());
+    }
+
+    #[test]
+    fn test_array_alignment_and_moves() {
+        assert_eq!(
+            diff(
+                "---\ntags: [cli, yaml]\n---\n",
+                "---\ntags: [cli, yaml, rust]\n---\n"
+            ),
+            vec!["$['tags'][2] added 'rust'"]
+        );
+        assert_eq!(
+            diff("---\ntags: [a, b, c]\n---\n", "---\ntags: [c, a, b]\n---\n"),
+            vec!["$['tags'][0] moved from $['tags'][2] 'c'"]
+        );
+        assert_eq!(
+            diff("---\ncats: [a]\n---\n", "---\ntags: [a]\n---\n"),
+            vec!["$['tags'] moved from $['cats'] [\"a\"]"]
+        );
+    }
+
+    #[test]
+    fn test_nested_changes_and_json() {
+        let reader = FrontMatterReader::new();
+        let old = reader
+            .parse_content("---\nauthors:\n  - name: Ann\n    it's: 1\n---\n", None)
+            .unwrap();
+        let new = reader
+            .parse_content("---\nauthors:\n  - name: Bob\n    it's: 1\n---\n", None)
+            .unwrap();
+
+        let diff = FrontMatterDiff::between(&old, &new);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap(),
+            serde_json::json!([{
+                "kind": "changed",
+                "path": "$['authors'][0]['name']",
+                "old": "Ann",
+                "new": "Bob",
+            }])
+        );
+
+        let mut ignored = FrontMatterDiff::between(&Document::empty(), &old);
+        ignored.ignore_paths(&["$['authors']".to_string()]);
+        assert!(ignored.is_empty());
+
+        // Missing front matter compares like an empty mapping
+        let diff = FrontMatterDiff::between(&Document::empty(), &old);
+        assert_eq!(diff.changes()[0].kind, ChangeKind::Added);
+    }
+
+    #[test]
+    fn test_paths_match_query_results() {
+        let reader = FrontMatterReader::new();
+        let old = reader
+            .parse_content("---\n\"it's\": {a\\b: [1, 2]}\n---\n", None)
+            .unwrap();
+        let new = reader
+            .parse_content("---\n\"it's\": {a\\b: [1, 3]}\n---\n", None)
+            .unwrap();
+        let diff = FrontMatterDiff::between(&old, &new);
+        let paths: Vec<&str> = diff.iter().map(|change| change.path.as_str()).collect();
+
+        let json = crate::core::YamlJsonConverter::yaml_to_json(&new.to_yaml_value()).unwrap();
+        let located: Vec<String> = crate::core::JsonPathQuery::new("$..*")
+            .unwrap()
+            .query_located(&json)
+            .into_iter()
+            .map(|(path, _)| NormalizedPathUtils::to_string(&path))
+            .collect();
+        assert!(!paths.is_empty());
+        for path in paths {
+            assert!(
+                located.iter().any(|p| p == path),
+                "{} not in {:?}",
+                path,
+                located
+            );
+        }
+    }
+}
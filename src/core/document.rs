@@ -5,30 +5,38 @@
 //! the front matter while preserving the document body.
 
 use crate::core::{
+    format::FrontMatterFormat,
     path::KeyPath,
     query::{Query, QueryResult},
     value::FrontMatterValue,
 };
 use crate::error::{MatterOfError, Result};
+use indexmap::IndexMap;
 use std::collections::BTreeMap;
 
 /// Represents a markdown document with front matter and body
 #[derive(Debug, Clone)]
 pub struct Document {
-    front_matter: Option<BTreeMap<String, FrontMatterValue>>,
+    front_matter: Option<IndexMap<String, FrontMatterValue>>,
     body: String,
     original_content: Option<String>,
+    /// Serialization format of the front matter block
+    format: FrontMatterFormat,
+    /// Raw front matter text as read, used to preserve formatting on write
+    source: Option<String>,
     /// Tracks whether this document has been mutated since creation
     mutated: bool,
 }
 
 impl Document {
     /// Create a new document with optional front matter and body
-    pub fn new(front_matter: Option<BTreeMap<String, FrontMatterValue>>, body: String) -> Self {
+    pub fn new(front_matter: Option<IndexMap<String, FrontMatterValue>>, body: String) -> Self {
         Self {
             front_matter,
             body,
             original_content: None,
+            format: FrontMatterFormat::default(),
+            source: None,
             mutated: false,
         }
     }
@@ -47,7 +55,7 @@ impl Document {
     pub fn from_yaml_value(yaml_value: Option<serde_yaml::Value>, body: String) -> Result<Self> {
         let front_matter = match yaml_value {
             Some(serde_yaml::Value::Mapping(map)) => {
-                let mut fm = BTreeMap::new();
+                let mut fm = IndexMap::new();
                 for (k, v) in map {
                     if let Some(key_str) = k.as_str() {
                         fm.insert(key_str.to_string(), FrontMatterValue::new(v));
@@ -73,8 +81,59 @@ impl Document {
         self
     }
 
+    /// Set the serialization format of the front matter
+    pub fn with_format(mut self, format: FrontMatterFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Get the serialization format of the front matter
+    pub fn format(&self) -> FrontMatterFormat {
+        self.format
+    }
+
+    /// Set the raw front matter text the document was parsed from
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Get the raw front matter text if available
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Forget the raw front matter text so the next write renders it from scratch
+    pub fn discard_source(&mut self) {
+        if self.source.take().is_some() {
+            self.mutated = true;
+        }
+    }
+
+    /// Re-emit the front matter in another serialization format
+    ///
+    /// The body is left untouched and keys keep their original order. Fails
+    /// without modifying the document if the front matter holds values the
+    /// target format cannot represent, such as null in TOML.
+    pub fn convert_to(&mut self, format: FrontMatterFormat) -> Result<()> {
+        if format == self.format {
+            return Ok(());
+        }
+
+        let source = if self.has_front_matter() {
+            Some(format.render(&self.to_yaml_value(), 2)?)
+        } else {
+            None
+        };
+
+        self.format = format;
+        self.source = source;
+        self.mutated = true;
+        Ok(())
+    }
+
     /// Get the front matter as a reference
-    pub fn front_matter(&self) -> Option<&BTreeMap<String, FrontMatterValue>> {
+    pub fn front_matter(&self) -> Option<&IndexMap<String, FrontMatterValue>> {
         self.front_matter.as_ref()
     }
 
@@ -104,7 +163,7 @@ impl Document {
     /// Initialize front matter if it doesn't exist
     pub fn ensure_front_matter(&mut self) {
         if self.front_matter.is_none() {
-            self.front_matter = Some(BTreeMap::new());
+            self.front_matter = Some(IndexMap::new());
         }
     }
 
@@ -218,6 +277,12 @@ impl Document {
         result
     }
 
+    /// Replace the whole front matter, keeping the body and raw source
+    pub fn replace_front_matter(&mut self, front_matter: IndexMap<String, FrontMatterValue>) {
+        self.front_matter = Some(front_matter);
+        self.mutated = true;
+    }
+
     /// Update the body content
     pub fn set_body(&mut self, body: String) {
         self.body = body;
@@ -242,6 +307,31 @@ impl Document {
         Ok(())
     }
 
+    /// Merge another document's front matter into this one without clobbering
+    ///
+    /// Unlike [`Self::merge_front_matter`], existing values always win: only
+    /// missing keys are added, nested mappings are filled in recursively, and
+    /// existing arrays are left as they are. Returns whether anything was added.
+    pub fn merge_missing_front_matter(&mut self, other: &Document) -> bool {
+        let Some(ref other_fm) = other.front_matter else {
+            return false;
+        };
+        self.ensure_front_matter();
+        let fm = self.front_matter.as_mut().unwrap();
+
+        let mut added = false;
+        for (key, value) in other_fm {
+            if let Some(existing) = fm.get_mut(key) {
+                added |= existing.merge_missing(value.clone());
+            } else {
+                fm.insert(key.clone(), value.clone());
+                added = true;
+            }
+        }
+        self.mutated |= added;
+        added
+    }
+
     /// Convert to YAML value representation
     pub fn to_yaml_value(&self) -> serde_yaml::Value {
         match &self.front_matter {
@@ -287,7 +377,7 @@ impl Document {
 
     fn get_nested_value(
         &self,
-        container: &BTreeMap<String, FrontMatterValue>,
+        container: &IndexMap<String, FrontMatterValue>,
         path: &[String],
     ) -> Option<FrontMatterValue> {
         if path.is_empty() {
@@ -323,7 +413,7 @@ impl Document {
     }
 
     fn set_nested_value_static(
-        container: &mut BTreeMap<String, FrontMatterValue>,
+        container: &mut IndexMap<String, FrontMatterValue>,
         path: &[String],
         value: FrontMatterValue,
     ) -> Result<()> {
@@ -363,7 +453,7 @@ impl Document {
                     let element_value = if array[index].is_object() {
                         array[index].as_object().unwrap()
                     } else {
-                        BTreeMap::new()
+                        IndexMap::new()
                     };
 
                     let mut nested_map = element_value;
@@ -378,14 +468,14 @@ impl Document {
 
         // Handle object path (original logic)
         if !container.contains_key(key) {
-            container.insert(key.clone(), FrontMatterValue::object(BTreeMap::new()));
+            container.insert(key.clone(), FrontMatterValue::object(IndexMap::new()));
         }
 
         // Get the nested container
         let nested_value = container.get_mut(key).unwrap();
         if !nested_value.is_object() {
             // Convert to object if it's not already
-            *nested_value = FrontMatterValue::object(BTreeMap::new());
+            *nested_value = FrontMatterValue::object(IndexMap::new());
         }
 
         let mut nested_map = nested_value.as_object().unwrap();
@@ -397,7 +487,7 @@ impl Document {
     }
 
     fn remove_nested_value_static(
-        container: &mut BTreeMap<String, FrontMatterValue>,
+        container: &mut IndexMap<String, FrontMatterValue>,
         path: &[String],
     ) -> Result<Option<FrontMatterValue>> {
         if path.is_empty() {
@@ -405,7 +495,7 @@ impl Document {
         }
 
         if path.len() == 1 {
-            return Ok(container.remove(&path[0]));
+            return Ok(container.shift_remove(&path[0]));
         }
 
         let key = &path[0];
@@ -419,7 +509,7 @@ impl Document {
 
         // Update the nested container or remove it if empty
         if nested_map.is_empty() {
-            container.remove(key);
+            container.shift_remove(key);
         } else {
             container.insert(key.clone(), FrontMatterValue::object(nested_map));
         }
@@ -429,7 +519,7 @@ impl Document {
 
     fn query_recursive(
         &self,
-        container: &BTreeMap<String, FrontMatterValue>,
+        container: &IndexMap<String, FrontMatterValue>,
         current_path: &KeyPath,
         query: &Query,
         result: &mut QueryResult,
@@ -464,7 +554,7 @@ impl Document {
 
     fn flatten_recursive(
         &self,
-        container: &BTreeMap<String, FrontMatterValue>,
+        container: &IndexMap<String, FrontMatterValue>,
         current_path: &KeyPath,
         result: &mut BTreeMap<KeyPath, FrontMatterValue>,
     ) {
@@ -541,6 +631,27 @@ mod tests {
         assert!(author.is_object());
     }
 
+    #[test]
+    fn test_key_order() {
+        let mut doc = Document::from_yaml_value(
+            Some(serde_yaml::from_str("{zeta: 1, author: {zname: a, alpha: b}}").unwrap()),
+            String::new(),
+        )
+        .unwrap();
+        for key in ["title", "date", "author.name", "alpha"] {
+            doc.set(&KeyPath::parse(key).unwrap(), FrontMatterValue::string("x"))
+                .unwrap();
+        }
+        doc.remove(&KeyPath::parse("title").unwrap()).unwrap();
+
+        // Keys stay where they were and new ones follow in insertion order
+        let keys: Vec<_> = doc.front_matter().unwrap().keys().collect();
+        assert_eq!(keys, ["zeta", "author", "date", "alpha"]);
+        let author = doc.get(&KeyPath::parse("author").unwrap()).unwrap();
+        let keys: Vec<_> = author.as_object().unwrap().into_keys().collect();
+        assert_eq!(keys, ["zname", "alpha", "name"]);
+    }
+
     #[test]
     fn test_array_operations() {
         let mut doc = Document::empty();
@@ -657,6 +768,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_document_merge_missing() {
+        let mut doc = Document::from_yaml_value(
+            Some(serde_yaml::from_str("{title: Mine, tags: [rust], author: {name: Ann}}").unwrap()),
+            String::new(),
+        )
+        .unwrap();
+        let template = Document::from_yaml_value(
+            Some(
+                serde_yaml::from_str(
+                    "{title: Template, tags: [draft], author: {name: X, email: x@y.z}, draft: true}",
+                )
+                .unwrap(),
+            ),
+            String::new(),
+        )
+        .unwrap();
+
+        assert!(doc.merge_missing_front_matter(&template));
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "{author: {name: Ann, email: x@y.z}, draft: true, tags: [rust], title: Mine}",
+        )
+        .unwrap();
+        assert_eq!(doc.to_yaml_value(), expected);
+
+        // Nothing left to add
+        assert!(!doc.merge_missing_front_matter(&template));
+    }
+
     #[test]
     fn test_document_validation() {
         let doc = Document::empty();
@@ -700,6 +840,26 @@ mod tests {
         assert!(flattened.contains_key(&KeyPath::parse("author.email").unwrap()));
     }
 
+    #[test]
+    fn test_convert_to() {
+        let mut doc = crate::io::FrontMatterReader::new()
+            .parse_content("---\nzeta: 1\nalpha: [a]\n---\nBody\n", None)
+            .unwrap();
+
+        doc.convert_to(FrontMatterFormat::Toml).unwrap();
+        assert_eq!(doc.format(), FrontMatterFormat::Toml);
+        assert_eq!(doc.source(), Some("zeta = 1\nalpha = [\"a\"]\n"));
+        assert_eq!(doc.body(), "Body\n");
+
+        doc.set(&KeyPath::parse("zeta").unwrap(), FrontMatterValue::null())
+            .unwrap();
+        let err = doc
+            .convert_to(FrontMatterFormat::Json { fenced: false })
+            .and_then(|_| doc.convert_to(FrontMatterFormat::Toml));
+        assert!(matches!(err, Err(MatterOfError::TypeConversion { .. })));
+        assert_eq!(doc.format(), FrontMatterFormat::Json { fenced: false });
+    }
+
     #[test]
     fn test_array_indexing() {
         let mut doc = Document::empty();
@@ -715,7 +875,7 @@ mod tests {
         // Set up nested array of objects
         let authors = FrontMatterValue::array(vec![
             FrontMatterValue::object({
-                let mut obj = std::collections::BTreeMap::new();
+                let mut obj = IndexMap::new();
                 obj.insert("name".to_string(), FrontMatterValue::string("John Doe"));
                 obj.insert(
                     "email".to_string(),
@@ -724,7 +884,7 @@ mod tests {
                 obj
             }),
             FrontMatterValue::object({
-                let mut obj = std::collections::BTreeMap::new();
+                let mut obj = IndexMap::new();
                 obj.insert("name".to_string(), FrontMatterValue::string("Jane Smith"));
                 obj.insert(
                     "email".to_string(),
@@ -926,6 +1086,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
     fn test_array_index_reconstruction() {
         let mut doc = Document::empty();
 
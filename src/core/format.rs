@@ -0,0 +1,160 @@
+//! Front matter serialization formats
+//!
+//! A front matter block is delimited by a fence line that identifies its
+//! format: `---` for YAML, `+++` for TOML and `;;;` for JSON. JSON front matter
+//! may also be written without fences as an object at the very start of the file.
+
+use crate::core::{JsonPatcher, TomlPatcher, YamlPatcher, YamlStyle, YamlTomlConverter};
+use crate::error::Result;
+use serde_yaml::Value as YamlValue;
+use std::fmt;
+
+/// Serialization format of a front matter block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FrontMatterFormat {
+    /// YAML between `---` fences
+    #[default]
+    Yaml,
+    /// TOML between `+++` fences
+    Toml,
+    /// A JSON object, either between `;;;` fences or bare at the start of the file
+    Json {
+        /// Whether the object is wrapped in `;;;` fence lines
+        fenced: bool,
+    },
+}
+
+impl FrontMatterFormat {
+    /// Fence line that opens and closes a block of this format
+    ///
+    /// Returns `None` for bare JSON objects, which have no fences.
+    pub fn delimiter(&self) -> Option<&'static str> {
+        match self {
+            Self::Yaml => Some("---"),
+            Self::Toml => Some("+++"),
+            Self::Json { fenced: true } => Some(";;;"),
+            Self::Json { fenced: false } => None,
+        }
+    }
+
+    /// Detect the format from the opening fence line of a document
+    pub fn from_delimiter(line: &str) -> Option<Self> {
+        match line.trim_end() {
+            "---" => Some(Self::Yaml),
+            "+++" => Some(Self::Toml),
+            ";;;" => Some(Self::Json { fenced: true }),
+            _ => None,
+        }
+    }
+
+    /// Check whether a line closes a block of this format
+    pub fn is_closing_delimiter(&self, line: &str) -> bool {
+        let line = line.trim_end();
+        match self {
+            Self::Yaml => line == "---" || line == "...",
+            Self::Toml => line == "+++",
+            Self::Json { fenced } => *fenced && line == ";;;",
+        }
+    }
+
+    /// Parse a format from its name (e.g. `"yaml"`, `"toml"`, `"json"`)
+    ///
+    /// JSON defaults to a bare object, the form Hugo reads.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json { fenced: false }),
+            _ => None,
+        }
+    }
+
+    /// Parse raw front matter text of this format into a YAML value
+    pub fn parse(&self, source: &str) -> Result<YamlValue> {
+        match self {
+            Self::Yaml => Ok(serde_yaml::from_str(source)?),
+            Self::Toml => YamlTomlConverter::parse(source),
+            Self::Json { .. } => JsonPatcher::parse(source),
+        }
+    }
+
+    /// Rewrite raw front matter text of this format so that it holds `value`
+    pub fn patch(&self, source: &str, value: &YamlValue) -> Result<String> {
+        match self {
+            Self::Yaml => YamlPatcher::patch(source, value),
+            Self::Toml => TomlPatcher::patch(source, value),
+            Self::Json { .. } => JsonPatcher::patch(source, value),
+        }
+    }
+
+    /// Render a value as fresh front matter text of this format
+    ///
+    /// Fails for values the format cannot represent, such as null in TOML.
+    pub fn render(&self, value: &YamlValue, indent: usize) -> Result<String> {
+        match self {
+            Self::Yaml => YamlPatcher::render(
+                value,
+                YamlStyle {
+                    indent,
+                    ..YamlStyle::default()
+                },
+            ),
+            Self::Toml => TomlPatcher::render(value),
+            Self::Json { .. } => JsonPatcher::render(value, indent),
+        }
+    }
+}
+
+impl fmt::Display for FrontMatterFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml => write!(f, "yaml"),
+            Self::Toml => write!(f, "toml"),
+            Self::Json { .. } => write!(f, "json"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delimiters() {
+        assert_eq!(
+            FrontMatterFormat::from_delimiter("---\n"),
+            Some(FrontMatterFormat::Yaml)
+        );
+        assert_eq!(
+            FrontMatterFormat::from_delimiter("+++\r\n"),
+            Some(FrontMatterFormat::Toml)
+        );
+        assert_eq!(
+            FrontMatterFormat::from_delimiter(";;;\n"),
+            Some(FrontMatterFormat::Json { fenced: true })
+        );
+        assert_eq!(FrontMatterFormat::from_delimiter("# Title"), None);
+        assert_eq!(FrontMatterFormat::Json { fenced: false }.delimiter(), None);
+
+        assert!(FrontMatterFormat::Yaml.is_closing_delimiter("...\n"));
+        assert!(!FrontMatterFormat::Toml.is_closing_delimiter("---\n"));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            FrontMatterFormat::from_name("YML"),
+            Some(FrontMatterFormat::Yaml)
+        );
+        assert_eq!(
+            FrontMatterFormat::from_name("toml"),
+            Some(FrontMatterFormat::Toml)
+        );
+        assert_eq!(
+            FrontMatterFormat::from_name("json"),
+            Some(FrontMatterFormat::Json { fenced: false })
+        );
+        assert_eq!(FrontMatterFormat::from_name("ini"), None);
+        assert_eq!(FrontMatterFormat::Toml.to_string(), "toml");
+    }
+}
@@ -0,0 +1,222 @@
+//! JSON front matter support
+//!
+//! JSON front matter is parsed straight into the `serde_yaml::Value` model used
+//! for YAML and TOML, keeping the key order of the source object. JSON has no
+//! comments to preserve, so a changed block is re-rendered in the indentation
+//! and spacing of the original while an unchanged block is written back
+//! verbatim.
+
+use crate::core::YamlPatcher;
+use crate::error::{MatterOfError, Result};
+use serde::Serialize;
+use serde_json::ser::{Formatter, PrettyFormatter, Serializer};
+use serde_yaml::Value as YamlValue;
+use std::io;
+
+/// Format-preserving patcher for JSON front matter
+pub struct JsonPatcher;
+
+impl JsonPatcher {
+    /// Parse JSON source into a YAML value, keeping object key order
+    pub fn parse(source: &str) -> Result<YamlValue> {
+        serde_json::from_str(source)
+            .map_err(|e| MatterOfError::validation(format!("Invalid JSON: {}", e)))
+    }
+
+    /// Rewrite JSON `source` so that it holds `new_value`
+    ///
+    /// Returns the source untouched when the value did not change. Otherwise
+    /// the object is rendered with the source's indentation, or on one line
+    /// with the source's spacing around `:` and `,`, keeping existing keys in
+    /// their original order.
+    pub fn patch(source: &str, new_value: &YamlValue) -> Result<String> {
+        let old_value = Self::parse(source)?;
+        if &old_value == new_value {
+            return Ok(source.to_string());
+        }
+
+        let value = YamlPatcher::order_like(new_value, &old_value);
+        let trimmed = source.trim_end();
+        let mut output = match Self::detect_indent(trimmed) {
+            Some(indent) => {
+                Self::to_string(&value, PrettyFormatter::with_indent(indent.as_bytes()))?
+            }
+            None => Self::to_string(&value, Self::detect_separators(trimmed))?,
+        };
+        output.push_str(&source[trimmed.len()..]);
+        Ok(output)
+    }
+
+    /// Render a value as a pretty-printed JSON object with a trailing newline
+    pub fn render(value: &YamlValue, indent: usize) -> Result<String> {
+        let indent = " ".repeat(indent);
+        let mut output = Self::to_string(value, PrettyFormatter::with_indent(indent.as_bytes()))?;
+        output.push('\n');
+        Ok(output)
+    }
+
+    /// Indentation of the first indented line, or `None` for single-line JSON
+    fn detect_indent(source: &str) -> Option<&str> {
+        if !source.contains('\n') {
+            return None;
+        }
+        source
+            .lines()
+            .skip(1)
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .find(|indent| !indent.is_empty())
+            .or(Some("  "))
+    }
+
+    /// Separators of single-line JSON, taken from the first `:` and `,`
+    /// outside strings; a missing `,` is spaced like the `:`
+    fn detect_separators(source: &str) -> SeparatorFormatter {
+        let (mut colon, mut comma) = (None, None);
+        let (mut in_string, mut escaped) = (false, false);
+        for (index, c) in source.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            let separator = match c {
+                '"' => {
+                    in_string = true;
+                    continue;
+                }
+                ':' if colon.is_none() => &mut colon,
+                ',' if comma.is_none() => &mut comma,
+                _ => continue,
+            };
+            let before = source[..index].trim_end().len();
+            let rest = &source[index + 1..];
+            let after = index + 1 + rest.len() - rest.trim_start().len();
+            *separator = Some(source[before..after].to_string());
+        }
+
+        let colon = colon.unwrap_or_else(|| ":".to_string());
+        let comma = comma.unwrap_or_else(|| colon.replacen(':', ",", 1));
+        SeparatorFormatter { colon, comma }
+    }
+
+    fn to_string(value: &YamlValue, formatter: impl Formatter) -> Result<String> {
+        if matches!(value, YamlValue::Null) {
+            return Ok("{}".to_string());
+        }
+
+        let mut buffer = Vec::new();
+        value
+            .serialize(&mut Serializer::with_formatter(&mut buffer, formatter))
+            .map_err(|e| MatterOfError::type_conversion(format!("{:?}", value), e.to_string()))?;
+
+        String::from_utf8(buffer)
+            .map_err(|e| MatterOfError::type_conversion("JSON output", e.to_string()))
+    }
+}
+
+/// Single-line formatter writing `:` and `,` with the given spacing
+struct SeparatorFormatter {
+    colon: String,
+    comma: String,
+}
+
+impl Formatter for SeparatorFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(self.comma.as_bytes())
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.begin_array_value(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.colon.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keeps_key_order() {
+        let value =
+            JsonPatcher::parse(r#"{"title": "Hello", "date": "2024-01-02", "a": 1}"#).unwrap();
+        let keys: Vec<_> = value
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap())
+            .collect();
+        assert_eq!(keys, ["title", "date", "a"]);
+
+        assert!(JsonPatcher::parse("{title: Hello}").is_err());
+    }
+
+    #[test]
+    fn test_patch_preserves_style() {
+        let source = "{\n    \"title\": \"Hello\",\n    \"draft\": true\n}\n";
+        let mut value = JsonPatcher::parse(source).unwrap();
+        assert_eq!(JsonPatcher::patch(source, &value).unwrap(), source);
+
+        let map = value.as_mapping_mut().unwrap();
+        map.insert("draft".into(), false.into());
+        map.insert("tags".into(), vec!["a"].into());
+        assert_eq!(
+            JsonPatcher::patch(source, &value).unwrap(),
+            "{\n    \"title\": \"Hello\",\n    \"draft\": false,\n    \"tags\": [\n        \"a\"\n    ]\n}\n"
+        );
+
+        let compact = r#"{"title":"Hello","draft":true}"#;
+        assert_eq!(
+            JsonPatcher::patch(compact, &value).unwrap(),
+            r#"{"title":"Hello","draft":false,"tags":["a"]}"#
+        );
+    }
+
+    #[test]
+    fn test_patch_keeps_single_line_spacing() {
+        let source = r#"{"title": "Hi", "n": 1}"#;
+        let mut value = JsonPatcher::parse(source).unwrap();
+        let map = value.as_mapping_mut().unwrap();
+        map.insert("title".into(), "Yo".into());
+        map.insert("tags".into(), vec!["a", "b"].into());
+        assert_eq!(
+            JsonPatcher::patch(source, &value).unwrap(),
+            r#"{"title": "Yo", "n": 1, "tags": ["a", "b"]}"#
+        );
+
+        // Separators inside strings are not mistaken for the object's own
+        let source = r#"{"a:b" : "x,y"}"#;
+        let value = serde_yaml::from_str("{'a:b': z}").unwrap();
+        assert_eq!(
+            JsonPatcher::patch(source, &value).unwrap(),
+            r#"{"a:b" : "z"}"#
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let value: YamlValue = serde_yaml::from_str("title: T\ncount: 2\n").unwrap();
+        assert_eq!(
+            JsonPatcher::render(&value, 2).unwrap(),
+            "{\n  \"title\": \"T\",\n  \"count\": 2\n}\n"
+        );
+    }
+}
@@ -7,10 +7,10 @@
 
 use crate::core::FrontMatterValue;
 use crate::error::{MatterOfError, Result};
+use indexmap::IndexMap;
 use serde_json::Value as JsonValue;
-use serde_json_path::{JsonPath, NormalizedPath};
+use serde_json_path::{JsonPath, NormalizedPath, PathElement};
 use serde_yaml::Value as YamlValue;
-use std::collections::BTreeMap;
 
 /// JSONPath query wrapper with auto-prepending logic
 #[derive(Debug, Clone)]
@@ -225,7 +225,7 @@ impl YamlJsonConverter {
 
     /// Convert Document front matter to YAML Value
     pub fn document_front_matter_to_yaml(
-        front_matter: &BTreeMap<String, FrontMatterValue>,
+        front_matter: &IndexMap<String, FrontMatterValue>,
     ) -> YamlValue {
         let mut map = serde_yaml::Mapping::new();
         for (key, value) in front_matter {
@@ -237,10 +237,10 @@ impl YamlJsonConverter {
     /// Convert YAML Value to Document front matter format
     pub fn yaml_to_document_front_matter(
         yaml: &YamlValue,
-    ) -> Result<BTreeMap<String, FrontMatterValue>> {
+    ) -> Result<IndexMap<String, FrontMatterValue>> {
         match yaml {
             YamlValue::Mapping(map) => {
-                let mut fm = BTreeMap::new();
+                let mut fm = IndexMap::new();
                 for (k, v) in map {
                     if let Some(key_str) = k.as_str() {
                         fm.insert(key_str.to_string(), FrontMatterValue::new(v.clone()));
@@ -248,7 +248,7 @@ impl YamlJsonConverter {
                 }
                 Ok(fm)
             }
-            YamlValue::Null => Ok(BTreeMap::new()),
+            YamlValue::Null => Ok(IndexMap::new()),
             _ => Err(MatterOfError::type_conversion(
                 format!("{:?}", yaml),
                 "Document front matter".to_string(),
@@ -283,9 +283,23 @@ pub struct ParsedPath {
 impl NormalizedPathUtils {
     /// Convert a NormalizedPath to a human-readable string
     ///
-    /// This follows the RFC 9535 §2.7 format for canonical path representation
+    /// This follows the RFC 9535 §2.7 format for canonical path representation,
+    /// escaping property names, which `NormalizedPath`'s `Display` does not.
     pub fn to_string(path: &NormalizedPath<'_>) -> String {
-        path.to_string()
+        let segments: Vec<PathSegment> = path
+            .iter()
+            .map(|element| match element {
+                PathElement::Name(name) => PathSegment::Property(name.to_string()),
+                PathElement::Index(index) => PathSegment::Index(*index),
+            })
+            .collect();
+        Self::format_segments(&segments)
+    }
+
+    /// Path of the property `key` of the value at the NormalizedPath `path`
+    pub fn child_path(path: &str, key: &str) -> String {
+        let child = Self::format_segments(&[PathSegment::Property(key.to_string())]);
+        format!("{}{}", path, &child[1..])
     }
 
     /// Parse a NormalizedPath from a string into navigable segments
@@ -328,6 +342,19 @@ impl NormalizedPathUtils {
                 });
             }
 
+            // Property names are quoted and may contain escaped quotes and brackets
+            if remaining.starts_with("['") {
+                let (key, rest) = Self::parse_name(&remaining[2..]).ok_or_else(|| {
+                    MatterOfError::InvalidPath {
+                        path: path_str.to_string(),
+                        reason: "Unclosed property name in path".to_string(),
+                    }
+                })?;
+                segments.push(PathSegment::Property(key));
+                remaining = rest;
+                continue;
+            }
+
             // Find the closing bracket
             let close_pos = remaining
                 .find(']')
@@ -339,11 +366,7 @@ impl NormalizedPathUtils {
             let segment_content = &remaining[1..close_pos];
 
             // Parse the segment content
-            if segment_content.starts_with('\'') && segment_content.ends_with('\'') {
-                // Property access: ['key']
-                let key = segment_content[1..segment_content.len() - 1].to_string();
-                segments.push(PathSegment::Property(key));
-            } else if segment_content.chars().all(|c| c.is_ascii_digit()) {
+            if segment_content.chars().all(|c| c.is_ascii_digit()) {
                 // Array index: [0], [1], etc.
                 let index: usize =
                     segment_content
@@ -373,6 +396,95 @@ impl NormalizedPathUtils {
         })
     }
 
+    /// Unescape a property name up to its closing `']`, returning it and the rest
+    fn parse_name(text: &str) -> Option<(String, &str)> {
+        let mut name = String::new();
+        let mut chars = text.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\'' => return text[i + 1..].strip_prefix(']').map(|rest| (name, rest)),
+                '\\' => {
+                    let unescaped = match chars.next()?.1 {
+                        'b' => '\u{0008}',
+                        'f' => '\u{000C}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let hex: String = (0..4)
+                                .filter_map(|_| chars.next())
+                                .map(|(_, c)| c)
+                                .collect();
+                            char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                        }
+                        other => other,
+                    };
+                    name.push(unescaped);
+                }
+                c => name.push(c),
+            }
+        }
+        None
+    }
+
+    /// Build a NormalizedPath string from segments
+    ///
+    /// Property names are quoted and escaped as in RFC 9535 §2.7.
+    pub fn format_segments(segments: &[PathSegment]) -> String {
+        let mut path = String::from("$");
+        for segment in segments {
+            match segment {
+                PathSegment::Property(key) => {
+                    path.push_str("['");
+                    for c in key.chars() {
+                        match c {
+                            '\\' => path.push_str("\\\\"),
+                            '\'' => path.push_str("\\'"),
+                            '\u{0008}' => path.push_str("\\b"),
+                            '\u{000C}' => path.push_str("\\f"),
+                            '\n' => path.push_str("\\n"),
+                            '\r' => path.push_str("\\r"),
+                            '\t' => path.push_str("\\t"),
+                            c if (c as u32) < 0x20 => {
+                                path.push_str(&format!("\\u{:04x}", c as u32))
+                            }
+                            c => path.push(c),
+                        }
+                    }
+                    path.push_str("']");
+                }
+                PathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+                PathSegment::Append => path.push_str("[-]"),
+            }
+        }
+        path
+    }
+
+    /// Convert a JSON Pointer (RFC 6901) into a NormalizedPath string
+    ///
+    /// The `value` the pointer refers into decides whether a numeric token is an
+    /// array index or an object key.
+    pub fn from_json_pointer(pointer: &str, value: &JsonValue) -> String {
+        let mut segments = Vec::new();
+        let mut current = Some(value);
+
+        for token in pointer.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            match (current, token.parse::<usize>()) {
+                (Some(JsonValue::Array(items)), Ok(index)) => {
+                    current = items.get(index);
+                    segments.push(PathSegment::Index(index));
+                }
+                _ => {
+                    current = current.and_then(|v| v.get(&token));
+                    segments.push(PathSegment::Property(token));
+                }
+            }
+        }
+
+        Self::format_segments(&segments)
+    }
+
     /// Check if a NormalizedPath represents an array index access
     pub fn is_array_access(path: &NormalizedPath<'_>) -> bool {
         // Check if the path contains numeric indices
@@ -524,7 +636,7 @@ impl JsonMutator {
             match &segments[0] {
                 PathSegment::Property(key) => {
                     if let Some(obj) = current.as_object_mut() {
-                        Ok(obj.remove(key).is_some())
+                        Ok(obj.shift_remove(key).is_some())
                     } else {
                         Ok(false)
                     }
@@ -872,6 +984,60 @@ mod tests {
         assert_eq!(value.as_str().unwrap(), "John");
     }
 
+    #[test]
+    fn test_normalized_path_escaping() {
+        let json = json!({"it's": {"a\\b]": [1]}});
+        let located = JsonPathQuery::new("$..*").unwrap().query_located(&json);
+        let paths: Vec<String> = located
+            .iter()
+            .map(|(path, _)| NormalizedPathUtils::to_string(path))
+            .collect();
+        assert_eq!(paths[2], "$['it\\'s']['a\\\\b]'][0]");
+
+        // Escaped names parse back to the original keys
+        let parsed = NormalizedPathUtils::parse_path(&paths[2]).unwrap();
+        assert_eq!(
+            parsed.segments,
+            vec![
+                PathSegment::Property("it's".to_string()),
+                PathSegment::Property("a\\b]".to_string()),
+                PathSegment::Index(0),
+            ]
+        );
+        assert_eq!(
+            NormalizedPathUtils::format_segments(&parsed.segments),
+            paths[2]
+        );
+    }
+
+    #[test]
+    fn test_normalized_path_from_segments_and_pointer() {
+        let segments = vec![
+            PathSegment::Property("it's".to_string()),
+            PathSegment::Index(0),
+        ];
+        assert_eq!(
+            NormalizedPathUtils::format_segments(&segments),
+            "$['it\\'s'][0]"
+        );
+        assert_eq!(NormalizedPathUtils::format_segments(&[]), "$");
+
+        let value = json!({"tags": ["a"], "map": {"0": 1}, "a/b": 2});
+        assert_eq!(
+            NormalizedPathUtils::from_json_pointer("/tags/0", &value),
+            "$['tags'][0]"
+        );
+        assert_eq!(
+            NormalizedPathUtils::from_json_pointer("/map/0", &value),
+            "$['map']['0']"
+        );
+        assert_eq!(
+            NormalizedPathUtils::from_json_pointer("/a~1b", &value),
+            "$['a/b']"
+        );
+        assert_eq!(NormalizedPathUtils::from_json_pointer("", &value), "$");
+    }
+
     #[test]
     fn test_query_result_formatting() {
         let json = json!({"title": "Test", "tags": ["a", "b"]});
@@ -0,0 +1,623 @@
+//! Deriving front matter from file metadata and git history
+//!
+//! These [`Extractor`]s look at where a document lives rather than at its
+//! body: file modification and creation times, dates and slugs encoded in
+//! file names like `2024-05-01-my-post.md`, and the first and last commits
+//! that touched the file in the local git repository. They only produce a
+//! value through [`Extractor::extract_file`].
+
+use crate::core::{Document, Extractor, FrontMatterValue};
+use crate::error::{MatterOfError, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Check that a `strftime` pattern is valid
+pub fn check_date_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(MatterOfError::validation(format!(
+            "Invalid date format '{}'",
+            format
+        )));
+    }
+    Ok(())
+}
+
+/// Format a timestamp with a `strftime` pattern, or as RFC 3339 without one
+fn format_date<Tz: chrono::TimeZone>(time: DateTime<Tz>, format: Option<&str>) -> Result<String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        Some(format) => {
+            // Formatting panics on an invalid pattern
+            check_date_format(format)?;
+            Ok(time.format(format).to_string())
+        }
+        None => Ok(time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+    }
+}
+
+fn format_system_time(time: SystemTime, format: Option<&str>) -> Result<FrontMatterValue> {
+    format_date(DateTime::<Local>::from(time), format).map(FrontMatterValue::string)
+}
+
+/// Last modification time of the file
+#[derive(Debug, Clone, Default)]
+pub struct ModifiedTimeExtractor {
+    /// `strftime` pattern for the value, RFC 3339 if unset
+    pub date_format: Option<String>,
+}
+
+impl Extractor for ModifiedTimeExtractor {
+    fn name(&self) -> &str {
+        "file:mtime"
+    }
+
+    fn description(&self) -> &str {
+        "last modification time of the file"
+    }
+
+    fn extract(&self, _document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(None)
+    }
+
+    fn extract_file(&self, _document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        let modified = fs::metadata(path)?.modified()?;
+        format_system_time(modified, self.date_format.as_deref()).map(Some)
+    }
+}
+
+/// Creation time of the file, where the filesystem records one
+#[derive(Debug, Clone, Default)]
+pub struct CreatedTimeExtractor {
+    /// `strftime` pattern for the value, RFC 3339 if unset
+    pub date_format: Option<String>,
+}
+
+impl Extractor for CreatedTimeExtractor {
+    fn name(&self) -> &str {
+        "file:ctime"
+    }
+
+    fn description(&self) -> &str {
+        "creation time of the file, if the filesystem records it"
+    }
+
+    fn extract(&self, _document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(None)
+    }
+
+    fn extract_file(&self, _document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        // Not every platform and filesystem keeps a birth time
+        let created = fs::metadata(path)?.created().ok();
+        created
+            .map(|time| format_system_time(time, self.date_format.as_deref()))
+            .transpose()
+    }
+}
+
+/// Date and slug parts of a file name like `2024-05-01-my-post.md`
+///
+/// For `index.md` and `_index.md` the name of the parent directory is used
+/// instead, so `2024-05-01-my-post/index.md` works the same way.
+fn filename_parts(path: &Path) -> Option<(Option<String>, String)> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN
+        .get_or_init(|| Regex::new(r"^(\d{4}-\d{2}-\d{2})(?:[-_](.*))?$").expect("valid regex"));
+
+    let mut stem = path.file_stem()?.to_str()?;
+    if stem == "index" || stem == "_index" {
+        stem = path.parent()?.file_name()?.to_str()?;
+    }
+
+    match pattern.captures(stem) {
+        Some(captures) => {
+            let date = captures[1].to_string();
+            let slug = captures.get(2).map_or("", |m| m.as_str()).to_string();
+            Some((Some(date), slug))
+        }
+        None => Some((None, stem.to_string())),
+    }
+}
+
+/// Date prefix of the file name, as in `2024-05-01-my-post.md`
+#[derive(Debug, Clone, Default)]
+pub struct FilenameDateExtractor;
+
+impl Extractor for FilenameDateExtractor {
+    fn name(&self) -> &str {
+        "filename:date"
+    }
+
+    fn description(&self) -> &str {
+        "YYYY-MM-DD prefix of the file name"
+    }
+
+    fn extract(&self, _document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(None)
+    }
+
+    fn extract_file(&self, _document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        Ok(filename_parts(path)
+            .and_then(|(date, _)| date)
+            .filter(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+            .map(FrontMatterValue::string))
+    }
+}
+
+/// File name without its extension and date prefix
+#[derive(Debug, Clone, Default)]
+pub struct FilenameSlugExtractor;
+
+impl Extractor for FilenameSlugExtractor {
+    fn name(&self) -> &str {
+        "filename:slug"
+    }
+
+    fn description(&self) -> &str {
+        "file name without extension and date prefix"
+    }
+
+    fn extract(&self, _document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(None)
+    }
+
+    fn extract_file(&self, _document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        Ok(filename_parts(path)
+            .map(|(_, slug)| slug)
+            .filter(|slug| !slug.is_empty())
+            .map(FrontMatterValue::string))
+    }
+}
+
+/// A commit that touched a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub author: String,
+    pub email: String,
+    /// Author date, in the author's time zone
+    pub time: DateTime<FixedOffset>,
+}
+
+/// First and last commits that touched a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHistory {
+    pub first: CommitInfo,
+    pub last: CommitInfo,
+}
+
+/// Reads file history from local git repositories
+///
+/// Repositories are discovered from each file's directory and opened once.
+/// The first time a file of a repository is asked for, its commits are
+/// walked once, recording the first and last commit of every path, so any
+/// number of files and git extractors sharing one `GitHistory` cost a
+/// single walk.
+#[derive(Debug, Default)]
+pub struct GitHistory {
+    repositories: Mutex<HashMap<PathBuf, Option<gix::ThreadSafeRepository>>>,
+    /// Histories of all committed files, by work tree and relative path
+    histories: Mutex<HashMap<PathBuf, Arc<HashMap<PathBuf, FileHistory>>>>,
+}
+
+impl GitHistory {
+    /// Create an empty history cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// History of a file, or `None` if it is not committed in a git repository
+    pub fn file_history(&self, path: &Path) -> Result<Option<FileHistory>> {
+        let path = fs::canonicalize(path)?;
+        let Some(repository) = path.parent().and_then(|dir| self.repository(dir)) else {
+            return Ok(None);
+        };
+        let Some(workdir) = repository
+            .workdir()
+            .and_then(|dir| fs::canonicalize(dir).ok())
+        else {
+            return Ok(None);
+        };
+        let Ok(relative) = path.strip_prefix(&workdir) else {
+            return Ok(None);
+        };
+
+        let histories = {
+            // Held during the walk, so that a repository is only walked once
+            let mut histories = self.histories.lock().expect("cache lock");
+            match histories.get(&workdir) {
+                Some(histories) => histories.clone(),
+                None => {
+                    let walked = Arc::new(walk(&repository)?);
+                    histories.insert(workdir.clone(), walked.clone());
+                    walked
+                }
+            }
+        };
+        Ok(histories.get(relative).cloned())
+    }
+
+    fn repository(&self, directory: &Path) -> Option<gix::Repository> {
+        let mut repositories = self.repositories.lock().expect("cache lock");
+        repositories
+            .entry(directory.to_path_buf())
+            .or_insert_with(|| gix::ThreadSafeRepository::discover(directory).ok())
+            .as_ref()
+            .map(|repository| repository.to_thread_local())
+    }
+}
+
+/// First and last commits of every file reachable from `HEAD`
+fn walk(repository: &gix::Repository) -> Result<HashMap<PathBuf, FileHistory>> {
+    let mut histories: HashMap<PathBuf, FileHistory> = HashMap::new();
+    // An unborn HEAD has no history yet
+    let Ok(head) = repository.head_commit() else {
+        return Ok(histories);
+    };
+
+    for info in head.ancestors().all().map_err(git_error)? {
+        let info = info.map_err(git_error)?;
+        let commit = info.object().map_err(git_error)?;
+        let tree = commit.tree_id().map_err(git_error)?.detach();
+
+        // Like `git log -- <path>`, a commit counts for the files that differ from every parent
+        let mut touched: Option<Vec<PathBuf>> = None;
+        for parent in info.parent_ids() {
+            let parent = parent
+                .object()
+                .map_err(git_error)?
+                .try_into_commit()
+                .map_err(git_error)?;
+            let parent_tree = parent.tree_id().map_err(git_error)?.detach();
+            let mut changed = Vec::new();
+            changed_files(
+                repository,
+                Some(parent_tree),
+                tree,
+                &PathBuf::new(),
+                &mut changed,
+            )?;
+            touched = Some(match touched {
+                Some(touched) => {
+                    let changed: HashSet<_> = changed.into_iter().collect();
+                    touched
+                        .into_iter()
+                        .filter(|path| changed.contains(path))
+                        .collect()
+                }
+                None => changed,
+            });
+        }
+        let touched = match touched {
+            Some(touched) => touched,
+            None => {
+                let mut added = Vec::new();
+                changed_files(repository, None, tree, &PathBuf::new(), &mut added)?;
+                added
+            }
+        };
+        if touched.is_empty() {
+            continue;
+        }
+
+        let commit = commit_info(&commit)?;
+        for path in touched {
+            match histories.get_mut(&path) {
+                Some(history) => {
+                    if commit.time <= history.first.time {
+                        history.first = commit.clone();
+                    }
+                    if commit.time > history.last.time {
+                        history.last = commit.clone();
+                    }
+                }
+                None => {
+                    let history = FileHistory {
+                        first: commit.clone(),
+                        last: commit.clone(),
+                    };
+                    histories.insert(path, history);
+                }
+            }
+        }
+    }
+
+    Ok(histories)
+}
+
+fn git_error(error: impl std::fmt::Display) -> MatterOfError {
+    MatterOfError::validation(format!("Git error: {}", error))
+}
+
+/// Collect the files of tree `new` that are not the same in tree `old`
+///
+/// Subtrees with the same id in both are skipped without being read.
+fn changed_files(
+    repository: &gix::Repository,
+    old: Option<gix::ObjectId>,
+    new: gix::ObjectId,
+    prefix: &Path,
+    changed: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let old_tree = old
+        .map(|old| repository.find_tree(old))
+        .transpose()
+        .map_err(git_error)?;
+    let old_entries: HashMap<_, _> = match &old_tree {
+        Some(tree) => tree
+            .decode()
+            .map_err(git_error)?
+            .entries
+            .into_iter()
+            .map(|entry| (entry.filename, (entry.mode, entry.oid.to_owned())))
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let new_tree = repository.find_tree(new).map_err(git_error)?;
+    for entry in new_tree.decode().map_err(git_error)?.entries {
+        let old = old_entries.get(entry.filename);
+        if old.is_some_and(|(_, id)| *id == entry.oid) {
+            continue;
+        }
+
+        let path = prefix.join(gix::path::from_bstr(entry.filename));
+        if entry.mode.is_tree() {
+            let old = old.filter(|(mode, _)| mode.is_tree()).map(|(_, id)| *id);
+            changed_files(repository, old, entry.oid.to_owned(), &path, changed)?;
+        } else {
+            changed.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn commit_info(commit: &gix::Commit<'_>) -> Result<CommitInfo> {
+    let author = commit.author().map_err(git_error)?;
+    let time = author.time().map_err(git_error)?;
+    let offset = FixedOffset::east_opt(time.offset)
+        .ok_or_else(|| git_error(format!("invalid time zone offset {}", time.offset)))?;
+    let time = DateTime::from_timestamp(time.seconds, 0)
+        .ok_or_else(|| git_error(format!("invalid commit time {}", time.seconds)))?
+        .with_timezone(&offset);
+    Ok(CommitInfo {
+        author: author.name.to_string(),
+        email: author.email.to_string(),
+        time,
+    })
+}
+
+/// Which part of a file's git history to extract
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitField {
+    /// Date of the first commit that touched the file
+    FirstCommitDate,
+    /// Date of the last commit that touched the file
+    LastCommitDate,
+    /// Author of the first commit that touched the file
+    Author,
+    /// Author of the last commit that touched the file
+    LastAuthor,
+}
+
+impl GitField {
+    /// All fields, in listing order
+    pub const ALL: [GitField; 4] = [
+        GitField::FirstCommitDate,
+        GitField::LastCommitDate,
+        GitField::Author,
+        GitField::LastAuthor,
+    ];
+}
+
+/// A value from the file's local git history
+#[derive(Debug, Clone)]
+pub struct GitExtractor {
+    field: GitField,
+    history: Arc<GitHistory>,
+    /// `strftime` pattern for dates, RFC 3339 if unset
+    pub date_format: Option<String>,
+}
+
+impl GitExtractor {
+    /// Create an extractor reading from a shared history cache
+    pub fn new(field: GitField, history: Arc<GitHistory>) -> Self {
+        Self {
+            field,
+            history,
+            date_format: None,
+        }
+    }
+
+    /// Format dates with a `strftime` pattern
+    pub fn with_date_format(mut self, date_format: Option<String>) -> Self {
+        self.date_format = date_format;
+        self
+    }
+}
+
+impl Extractor for GitExtractor {
+    fn name(&self) -> &str {
+        match self.field {
+            GitField::FirstCommitDate => "git:first-commit-date",
+            GitField::LastCommitDate => "git:last-commit-date",
+            GitField::Author => "git:author",
+            GitField::LastAuthor => "git:last-author",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self.field {
+            GitField::FirstCommitDate => "author date of the first commit of the file",
+            GitField::LastCommitDate => "author date of the last commit of the file",
+            GitField::Author => "author of the first commit of the file",
+            GitField::LastAuthor => "author of the last commit of the file",
+        }
+    }
+
+    fn extract(&self, _document: &Document) -> Result<Option<FrontMatterValue>> {
+        Ok(None)
+    }
+
+    fn extract_file(&self, _document: &Document, path: &Path) -> Result<Option<FrontMatterValue>> {
+        let Some(history) = self.history.file_history(path)? else {
+            return Ok(None);
+        };
+        let format = self.date_format.as_deref();
+        let value = match self.field {
+            GitField::FirstCommitDate => format_date(history.first.time, format)?,
+            GitField::LastCommitDate => format_date(history.last.time, format)?,
+            GitField::Author => history.first.author,
+            GitField::LastAuthor => history.last.author,
+        };
+        Ok(Some(FrontMatterValue::string(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn extract(extractor: &dyn Extractor, path: &Path) -> Option<serde_yaml::Value> {
+        extractor
+            .extract_file(&Document::empty(), path)
+            .unwrap()
+            .map(|value| value.as_inner().clone())
+    }
+
+    #[test]
+    fn test_filename_date_and_slug() {
+        let post = Path::new("content/2024-05-01-my-post.md");
+        assert_eq!(
+            extract(&FilenameDateExtractor, post),
+            Some("2024-05-01".into())
+        );
+        assert_eq!(
+            extract(&FilenameSlugExtractor, post),
+            Some("my-post".into())
+        );
+
+        let bundle = Path::new("content/2024-05-01_bundle/index.md");
+        assert_eq!(
+            extract(&FilenameDateExtractor, bundle),
+            Some("2024-05-01".into())
+        );
+        assert_eq!(
+            extract(&FilenameSlugExtractor, bundle),
+            Some("bundle".into())
+        );
+
+        let plain = Path::new("about.md");
+        assert_eq!(extract(&FilenameDateExtractor, plain), None);
+        assert_eq!(extract(&FilenameSlugExtractor, plain), Some("about".into()));
+        assert_eq!(
+            extract(&FilenameDateExtractor, Path::new("2024-13-40-nope.md")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_modified_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("post.md");
+        fs::write(&path, "# Post\n").unwrap();
+
+        let extractor = ModifiedTimeExtractor {
+            date_format: Some("%Y".to_string()),
+        };
+        let year = Local::now().format("%Y").to_string();
+        assert_eq!(extract(&extractor, &path), Some(year.into()));
+        assert!(ModifiedTimeExtractor::default()
+            .extract(&Document::empty())
+            .unwrap()
+            .is_none());
+
+        let invalid = ModifiedTimeExtractor {
+            date_format: Some("%Q".to_string()),
+        };
+        assert!(invalid.extract_file(&Document::empty(), &path).is_err());
+        assert!(check_date_format("%Y-%m-%d").is_ok());
+    }
+
+    #[test]
+    fn test_git_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let git = |args: &[&str], date: &str| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .env("HOME", dir)
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false)
+        };
+        // Skip where git is not installed
+        if !git(&["init", "-q"], "") {
+            return;
+        }
+        let commit = |name: &str, date: &str| {
+            let author = format!("user.name={}", name);
+            assert!(git(&["add", "-A"], date));
+            assert!(git(
+                &[
+                    "-c",
+                    &author,
+                    "-c",
+                    "user.email=a@b.c",
+                    "commit",
+                    "-qm",
+                    "x"
+                ],
+                date
+            ));
+        };
+
+        let post = dir.join("post.md");
+        fs::write(&post, "one\n").unwrap();
+        commit("Ada", "2024-05-01T10:00:00+02:00");
+        fs::write(dir.join("other.md"), "other\n").unwrap();
+        let nested = dir.join("posts/2024/nested.md");
+        fs::create_dir_all(nested.parent().unwrap()).unwrap();
+        fs::write(&nested, "nested\n").unwrap();
+        commit("Bob", "2024-06-01T10:00:00+02:00");
+        fs::write(&post, "two\n").unwrap();
+        commit("Cy", "2024-07-01T10:00:00+02:00");
+
+        let history = Arc::new(GitHistory::new());
+        let field = |field| GitExtractor::new(field, history.clone());
+        assert_eq!(
+            extract(&field(GitField::FirstCommitDate), &post),
+            Some("2024-05-01T10:00:00+02:00".into())
+        );
+        assert_eq!(
+            extract(&field(GitField::LastCommitDate), &post),
+            Some("2024-07-01T10:00:00+02:00".into())
+        );
+        assert_eq!(extract(&field(GitField::Author), &post), Some("Ada".into()));
+        assert_eq!(
+            extract(&field(GitField::LastAuthor), &post),
+            Some("Cy".into())
+        );
+
+        assert_eq!(
+            extract(&field(GitField::LastAuthor), &nested),
+            Some("Bob".into())
+        );
+
+        let untracked = dir.join("draft.md");
+        fs::write(&untracked, "draft\n").unwrap();
+        assert_eq!(extract(&field(GitField::Author), &untracked), None);
+    }
+}
@@ -5,18 +5,52 @@
 //! - FrontMatterValue: Type-safe wrapper for YAML values
 //! - KeyPath: Parsed key paths for nested access
 //! - Query: Composable query system for filtering and selecting data
+//! - YamlPatcher / TomlPatcher / JsonPatcher: Format-preserving edits of the original front matter text
+//! - SchemaValidator / SchemaInferrer: JSON Schema validation and inference for front matter
+//! - Aggregator: Cross-file aggregation (distinct, counts, group-by, sums) of JSONPath matches
+//! - Extractor / ExtractorRegistry: Derive front matter values (title, word count, ...) from the body
+//! - GitHistory and the file extractors: Derive dates, slugs and authors from file metadata and git
+//! - Template: Front matter templates with per-file `{{placeholder}}` values
+//! - FrontMatterDiff: Semantic, per-path comparison of two versions of front matter
+//! - FrontMatterFormat: The serialization format (YAML, TOML or JSON) of a front matter block
 
+pub mod aggregate;
+pub mod derive;
+pub mod diff;
 pub mod document;
+pub mod format;
+pub mod json_patch;
 pub mod jsonpath;
+pub mod metadata;
 pub mod path;
 pub mod query;
+pub mod schema;
+pub mod template;
+pub mod toml_patch;
 pub mod value;
+pub mod yaml_patch;
 
+pub use aggregate::{Aggregator, GroupSummary, NumericStats};
+pub use derive::{
+    DescriptionExtractor, Extractor, ExtractorRegistry, HeadingsExtractor, ReadingTimeExtractor,
+    TitleExtractor, WordCountExtractor,
+};
+pub use diff::{ChangeKind, FrontMatterChange, FrontMatterDiff};
 pub use document::Document;
+pub use format::FrontMatterFormat;
+pub use json_patch::JsonPatcher;
 pub use jsonpath::{
     JsonMutator, JsonPathQuery, JsonPathQueryResult, NormalizedPathUtils, ParsedPath, PathSegment,
     YamlJsonConverter,
 };
+pub use metadata::{
+    check_date_format, CommitInfo, CreatedTimeExtractor, FileHistory, FilenameDateExtractor,
+    FilenameSlugExtractor, GitExtractor, GitField, GitHistory, ModifiedTimeExtractor,
+};
 pub use path::KeyPath;
 pub use query::{CombineMode, Query, QueryResult, ValueTypeCondition};
+pub use schema::{SchemaInferrer, SchemaValidator, SchemaViolation, TypeDisagreement};
+pub use template::{Template, TemplateContext};
+pub use toml_patch::{TomlPatcher, YamlTomlConverter};
 pub use value::{FrontMatterValue, ValueType};
+pub use yaml_patch::{YamlPatcher, YamlStyle};
@@ -693,6 +693,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
     fn test_query_result_to_yaml() {
         let mut result = QueryResult::new();
         result.add_match(
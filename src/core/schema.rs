@@ -0,0 +1,579 @@
+//! JSON Schema validation and inference for front matter
+//!
+//! Front matter is converted to JSON with `YamlJsonConverter` and checked
+//! against a JSON Schema. Each violation carries the NormalizedPath of the
+//! offending value (RFC 9535 §2.7) and the schema keyword that rejected it.
+//!
+//! `SchemaInferrer` works the other way round: it merges the values observed
+//! across many documents into a draft schema.
+
+use crate::core::{Document, NormalizedPathUtils, PathSegment, YamlJsonConverter};
+use crate::error::{MatterOfError, Result};
+use serde_json::{json, Value as JsonValue};
+use serde_yaml::Value as YamlValue;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A single schema rule broken by a document's front matter
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    /// NormalizedPath of the offending value, e.g. `$['tags'][0]`
+    pub path: String,
+    /// Schema keyword that failed, e.g. `type`, `required` or `format`
+    pub rule: String,
+    /// Human-readable description of the failure
+    pub message: String,
+    /// JSON Pointer to the failing keyword within the schema
+    pub schema_path: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.path, self.message, self.rule)
+    }
+}
+
+/// Validates front matter against a compiled JSON Schema
+pub struct SchemaValidator {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    /// Compile a schema
+    ///
+    /// `format` keywords such as `date` and `date-time` are enforced, not just
+    /// treated as annotations.
+    pub fn new(schema: &JsonValue) -> Result<Self> {
+        let validator = jsonschema::options()
+            .should_validate_formats(true)
+            .build(schema)
+            .map_err(|e| MatterOfError::validation(format!("Invalid schema: {}", e)))?;
+        Ok(Self { validator })
+    }
+
+    /// Load and compile a schema from a JSON file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => MatterOfError::file_not_found(path),
+            _ => MatterOfError::Io(e),
+        })?;
+        let schema: JsonValue = serde_json::from_str(&content).map_err(|e| {
+            MatterOfError::validation(format!("Invalid schema {}: {}", path.display(), e))
+        })?;
+        Self::new(&schema)
+    }
+
+    /// Validate a JSON value, returning every violation found
+    pub fn validate_json(&self, value: &JsonValue) -> Vec<SchemaViolation> {
+        self.validator
+            .iter_errors(value)
+            .map(|error| {
+                let schema_path = error.schema_path.as_str().to_string();
+                let rule = schema_path
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .replace("~1", "/")
+                    .replace("~0", "~");
+                SchemaViolation {
+                    path: NormalizedPathUtils::from_json_pointer(
+                        error.instance_path.as_str(),
+                        value,
+                    ),
+                    rule,
+                    message: error.to_string(),
+                    schema_path,
+                }
+            })
+            .collect()
+    }
+
+    /// Validate the front matter of a document
+    ///
+    /// A document without front matter is validated as an empty object, so
+    /// `required` rules still apply to it.
+    pub fn validate_document(&self, document: &Document) -> Result<Vec<SchemaViolation>> {
+        let json = match document.front_matter() {
+            Some(front_matter) => YamlJsonConverter::yaml_to_json(
+                &YamlJsonConverter::document_front_matter_to_yaml(front_matter),
+            )?,
+            None => JsonValue::Object(serde_json::Map::new()),
+        };
+        Ok(self.validate_json(&json))
+    }
+}
+
+/// A key whose type differs between files
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDisagreement {
+    /// Path of the key, with `[*]` standing for any array element
+    pub path: String,
+    /// Type observed in most files; on a tie, the first of the tied types by name
+    pub majority: String,
+    /// Whether another type is observed in as many files as the majority
+    pub tied: bool,
+    /// Files that disagree with the majority, with the type they use
+    pub files: Vec<(String, String)>,
+}
+
+impl fmt::Display for TypeDisagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|(file, ty)| format!("{} ({})", file, ty))
+            .collect();
+        if self.tied {
+            write!(
+                f,
+                "{}: no majority type (tied, taking {}), found {}",
+                self.path,
+                self.majority,
+                files.join(", ")
+            )
+        } else {
+            write!(
+                f,
+                "{}: majority type is {}, but found {}",
+                self.path,
+                self.majority,
+                files.join(", ")
+            )
+        }
+    }
+}
+
+/// Builds a draft JSON Schema from the front matter of many documents
+///
+/// Values are collected with the same traversal as `Document::flatten`. Array
+/// elements are merged into a single `items` schema, keys present in every
+/// parent object are marked required, and scalar keys with a small set of
+/// repeated values get `enum` candidates.
+#[derive(Debug, Clone)]
+pub struct SchemaInferrer {
+    root: ShapeNode,
+    max_enum: usize,
+}
+
+/// One step from a node to a child in the inferred shape
+enum ShapeStep {
+    Key(String),
+    Item,
+}
+
+/// Everything observed at one position of the front matter tree
+#[derive(Debug, Clone, Default)]
+struct ShapeNode {
+    /// Number of values seen here
+    occurrences: usize,
+    /// Number of those values that were objects
+    objects: usize,
+    /// Files in which each JSON type was seen
+    types: BTreeMap<&'static str, BTreeSet<String>>,
+    /// Distinct scalar values and how often each was seen; `None` once too many
+    values: Option<BTreeMap<String, (JsonValue, usize)>>,
+    properties: BTreeMap<String, ShapeNode>,
+    items: Option<Box<ShapeNode>>,
+}
+
+impl SchemaInferrer {
+    /// Create an inferrer that suggests enums of up to 10 values
+    pub fn new() -> Self {
+        Self {
+            root: ShapeNode::default(),
+            max_enum: 10,
+        }
+    }
+
+    /// Set the largest number of distinct values suggested as an `enum`
+    pub fn with_max_enum(mut self, max_enum: usize) -> Self {
+        self.max_enum = max_enum;
+        self
+    }
+
+    /// Number of documents merged so far
+    pub fn document_count(&self) -> usize {
+        self.root.objects
+    }
+
+    /// Merge the front matter of a document; `source` names it in reports
+    pub fn add_document(&mut self, source: impl Into<String>, document: &Document) {
+        let source = source.into();
+        self.root.observe(
+            &source,
+            &YamlValue::Mapping(Default::default()),
+            self.max_enum,
+        );
+
+        let flattened = document.flatten();
+        for (key_path, value) in &flattened {
+            let steps = key_path
+                .segments()
+                .iter()
+                .enumerate()
+                .map(|(i, segment)| {
+                    let parent = flattened.get(&key_path.prefix(i));
+                    if i > 0 && parent.is_some_and(|p| p.is_array()) {
+                        ShapeStep::Item
+                    } else {
+                        ShapeStep::Key(segment.clone())
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            self.root
+                .descend(&steps)
+                .observe(&source, value.as_inner(), self.max_enum);
+        }
+    }
+
+    /// Render the merged observations as a JSON Schema (draft 2020-12)
+    pub fn to_schema(&self) -> JsonValue {
+        let mut schema = self.root.to_schema();
+        if let JsonValue::Object(map) = &mut schema {
+            map.insert(
+                "$schema".to_string(),
+                json!("https://json-schema.org/draft/2020-12/schema"),
+            );
+        }
+        schema
+    }
+
+    /// Keys whose type is not the same in every file
+    pub fn disagreements(&self) -> Vec<TypeDisagreement> {
+        let mut result = Vec::new();
+        self.root.collect_disagreements("$", &mut result);
+        result
+    }
+}
+
+impl Default for SchemaInferrer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShapeNode {
+    fn descend(&mut self, steps: &[ShapeStep]) -> &mut ShapeNode {
+        let mut node = self;
+        for step in steps {
+            node = match step {
+                ShapeStep::Key(key) => node.properties.entry(key.clone()).or_default(),
+                ShapeStep::Item => node.items.get_or_insert_with(Default::default),
+            };
+        }
+        node
+    }
+
+    fn observe(&mut self, source: &str, value: &YamlValue, max_enum: usize) {
+        let value = match value {
+            YamlValue::Tagged(tagged) => &tagged.value,
+            other => other,
+        };
+        let ty = json_type(value);
+
+        if self.occurrences == 0 {
+            self.values = Some(BTreeMap::new());
+        }
+        self.occurrences += 1;
+        self.types.entry(ty).or_default().insert(source.to_string());
+        if value.is_mapping() {
+            self.objects += 1;
+        }
+
+        let scalar = matches!(ty, "string" | "integer")
+            .then(|| YamlJsonConverter::yaml_to_json(value).ok())
+            .flatten();
+        if let (Some(values), Some(scalar)) = (&mut self.values, scalar) {
+            values.entry(scalar.to_string()).or_insert((scalar, 0)).1 += 1;
+            if values.len() > max_enum {
+                self.values = None;
+            }
+        }
+    }
+
+    /// Observed types ordered by the number of files using them, most common first
+    ///
+    /// Integers count as numbers when both were seen.
+    fn ranked_types(&self) -> Vec<(&'static str, BTreeSet<&str>)> {
+        let mut merged: BTreeMap<&'static str, BTreeSet<&str>> = BTreeMap::new();
+        for (ty, files) in &self.types {
+            let ty = match *ty {
+                "integer" if self.types.contains_key("number") => "number",
+                other => other,
+            };
+            merged
+                .entry(ty)
+                .or_default()
+                .extend(files.iter().map(String::as_str));
+        }
+
+        let mut ranked: Vec<_> = merged.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        ranked
+    }
+
+    fn to_schema(&self) -> JsonValue {
+        let mut schema = serde_json::Map::new();
+
+        let types: Vec<&str> = self.ranked_types().into_iter().map(|(ty, _)| ty).collect();
+        match types.as_slice() {
+            [] => {}
+            [ty] => {
+                schema.insert("type".to_string(), json!(ty));
+            }
+            _ => {
+                schema.insert("type".to_string(), json!(types));
+            }
+        }
+
+        if self.objects > 0 {
+            let properties: serde_json::Map<String, JsonValue> = self
+                .properties
+                .iter()
+                .map(|(key, node)| (key.clone(), node.to_schema()))
+                .collect();
+            let required: Vec<&String> = self
+                .properties
+                .iter()
+                .filter(|(_, node)| node.occurrences >= self.objects)
+                .map(|(key, _)| key)
+                .collect();
+            schema.insert("properties".to_string(), JsonValue::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+
+        if let Some(items) = &self.items {
+            schema.insert("items".to_string(), items.to_schema());
+        }
+
+        // Only repeated values suggest a closed set; unique titles do not
+        if let Some(values) = &self.values {
+            let scalars: usize = values.values().map(|(_, count)| count).sum();
+            if !values.is_empty() && scalars == self.occurrences && values.len() < scalars {
+                let candidates: Vec<&JsonValue> = values.values().map(|(v, _)| v).collect();
+                schema.insert("enum".to_string(), json!(candidates));
+            }
+        }
+
+        JsonValue::Object(schema)
+    }
+
+    fn collect_disagreements(&self, path: &str, result: &mut Vec<TypeDisagreement>) {
+        let ranked = self.ranked_types();
+        if let Some(((majority, majority_files), rest)) = ranked.split_first() {
+            let tied = rest
+                .first()
+                .is_some_and(|(_, files)| files.len() == majority_files.len());
+            let files: Vec<(String, String)> = rest
+                .iter()
+                .flat_map(|(ty, files)| files.iter().map(|f| (f.to_string(), ty.to_string())))
+                .collect();
+            if !files.is_empty() {
+                result.push(TypeDisagreement {
+                    path: path.to_string(),
+                    majority: majority.to_string(),
+                    tied,
+                    files,
+                });
+            }
+        }
+
+        for (key, node) in &self.properties {
+            let child = NormalizedPathUtils::format_segments(&[PathSegment::Property(key.clone())]);
+            node.collect_disagreements(&format!("{}{}", path, &child[1..]), result);
+        }
+        if let Some(items) = &self.items {
+            items.collect_disagreements(&format!("{}[*]", path), result);
+        }
+    }
+}
+
+/// JSON Schema type name of a YAML value
+fn json_type(value: &YamlValue) -> &'static str {
+    match value {
+        YamlValue::Null => "null",
+        YamlValue::Bool(_) => "boolean",
+        YamlValue::Number(n) if n.is_f64() => "number",
+        YamlValue::Number(_) => "integer",
+        YamlValue::String(_) => "string",
+        YamlValue::Sequence(_) => "array",
+        YamlValue::Mapping(_) => "object",
+        YamlValue::Tagged(tagged) => json_type(&tagged.value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::FrontMatterReader;
+    use serde_json::json;
+
+    fn post_schema() -> SchemaValidator {
+        SchemaValidator::new(&json!({
+            "type": "object",
+            "required": ["title", "date"],
+            "properties": {
+                "title": {"type": "string"},
+                "date": {"type": "string", "format": "date"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let document = FrontMatterReader::new()
+            .parse_content(
+                "---\ntitle: Hello\ndate: 2024-01-02\ntags: [rust]\n---\nBody",
+                None,
+            )
+            .unwrap();
+        assert!(post_schema()
+            .validate_document(&document)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_violations_report_path_and_rule() {
+        let document = FrontMatterReader::new()
+            .parse_content(
+                "---\ntitle: 42\ndate: next week\ntags: [rust, 7]\n---\nBody",
+                None,
+            )
+            .unwrap();
+        let mut violations: Vec<_> = post_schema()
+            .validate_document(&document)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.path, v.rule))
+            .collect();
+        violations.sort();
+
+        assert_eq!(
+            violations,
+            vec![
+                ("$['date']".to_string(), "format".to_string()),
+                ("$['tags'][1]".to_string(), "type".to_string()),
+                ("$['title']".to_string(), "type".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_front_matter_and_invalid_schema() {
+        let violations = post_schema()
+            .validate_document(&Document::body_only("Body".to_string()))
+            .unwrap();
+        assert_eq!(violations.len(), 2);
+        assert!(violations
+            .iter()
+            .all(|v| v.path == "$" && v.rule == "required"));
+
+        assert!(SchemaValidator::new(&json!({"type": 12})).is_err());
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let reader = FrontMatterReader::new();
+        let mut inferrer = SchemaInferrer::new();
+        for (name, content) in [
+            ("a.md", "---\ntitle: A\nstatus: draft\ntags: [x, y]\n---\n"),
+            (
+                "b.md",
+                "---\ntitle: B\nstatus: draft\ntags: [z]\nauthors:\n  - name: Ann\n---\n",
+            ),
+            ("c.md", "---\ntitle: C\nstatus: published\ntags: []\n---\n"),
+        ] {
+            let document = reader.parse_content(content, None).unwrap();
+            inferrer.add_document(name, &document);
+        }
+
+        assert_eq!(inferrer.document_count(), 3);
+        assert_eq!(
+            inferrer.to_schema(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "authors": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {"name": {"type": "string"}},
+                            "required": ["name"]
+                        }
+                    },
+                    "status": {"type": "string", "enum": ["draft", "published"]},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "title": {"type": "string"}
+                },
+                "required": ["status", "tags", "title"]
+            })
+        );
+        assert!(inferrer.disagreements().is_empty());
+    }
+
+    #[test]
+    fn test_infer_reports_disagreements() {
+        let reader = FrontMatterReader::new();
+        let mut inferrer = SchemaInferrer::new();
+        for (name, content) in [
+            ("a.md", "---\nweight: 1\ntags: [x]\n---\n"),
+            ("b.md", "---\nweight: 2\ntags: [3]\n---\n"),
+            ("c.md", "---\nweight: heavy\ntags: [y]\n---\n"),
+        ] {
+            let document = reader.parse_content(content, None).unwrap();
+            inferrer.add_document(name, &document);
+        }
+
+        let disagreements = inferrer.disagreements();
+        assert_eq!(disagreements.len(), 2);
+        assert_eq!(disagreements[0].path, "$['tags'][*]");
+        assert_eq!(disagreements[0].majority, "string");
+        assert_eq!(
+            disagreements[0].files,
+            vec![("b.md".to_string(), "integer".to_string())]
+        );
+        assert_eq!(disagreements[1].path, "$['weight']");
+        assert_eq!(disagreements[1].majority, "integer");
+        assert_eq!(
+            disagreements[1].files,
+            vec![("c.md".to_string(), "string".to_string())]
+        );
+        assert_eq!(
+            inferrer.to_schema()["properties"]["weight"]["type"],
+            json!(["integer", "string"])
+        );
+        assert!(disagreements.iter().all(|d| !d.tied));
+    }
+
+    #[test]
+    fn test_infer_reports_ties() {
+        let reader = FrontMatterReader::new();
+        let mut inferrer = SchemaInferrer::new();
+        for (name, content) in [
+            ("a.md", "---\nweight: heavy\n---\n"),
+            ("b.md", "---\nweight: 2\n---\n"),
+        ] {
+            let document = reader.parse_content(content, None).unwrap();
+            inferrer.add_document(name, &document);
+        }
+
+        let disagreements = inferrer.disagreements();
+        assert_eq!(disagreements.len(), 1);
+        assert!(disagreements[0].tied);
+        assert_eq!(disagreements[0].majority, "integer");
+        assert_eq!(
+            disagreements[0].to_string(),
+            "$['weight']: no majority type (tied, taking integer), found a.md (string)"
+        );
+    }
+}
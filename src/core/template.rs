@@ -0,0 +1,379 @@
+//! Front matter templates with per-file placeholders
+//!
+//! A [`Template`] is a mapping of front matter whose string values may contain
+//! `{{placeholder}}` expressions, optionally with a body skeleton that may
+//! contain them too. Rendering a template for a file evaluates them against a
+//! [`TemplateContext`]:
+//!
+//! - `{{filename}}` and `{{filename_stem}}`: the file name with and without extension
+//! - `{{today}}` and `{{now}}`: the current date, and the current time as RFC 3339
+//! - `{{uuid}}`: a random UUID, the same for every placeholder in one file
+//! - `{{env.NAME}}`: the environment variable `NAME`
+//! - `{{title}}`, `{{author.name}}`: a front matter value, taken from the
+//!   document first and from the template otherwise
+//! - `{{slug(EXPR)}}`: any of the above, lowercased with non-alphanumerics
+//!   turned into dashes
+
+use crate::core::{Document, KeyPath};
+use crate::error::{MatterOfError, Result};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat};
+use regex::{Captures, Regex};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// How deep placeholders may refer to template values that contain placeholders
+const MAX_DEPTH: usize = 8;
+
+/// Per-file values available to placeholders
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    /// File the template is rendered for
+    pub path: PathBuf,
+    /// Time used for `{{today}}` and `{{now}}`
+    pub now: DateTime<FixedOffset>,
+    /// Value of `{{uuid}}`
+    pub uuid: String,
+}
+
+impl TemplateContext {
+    /// Context for a file, with the current local time and a fresh UUID
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            now: Local::now().fixed_offset(),
+            uuid: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    fn variable(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "filename" => self.path.file_name()?.to_string_lossy().into_owned(),
+            "filename_stem" => self.path.file_stem()?.to_string_lossy().into_owned(),
+            "today" => self.now.format("%Y-%m-%d").to_string(),
+            "now" => self.now.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "uuid" => self.uuid.clone(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// A front matter mapping with `{{placeholder}}` values and an optional body
+#[derive(Debug, Clone)]
+pub struct Template {
+    front_matter: serde_yaml::Mapping,
+    body: Option<String>,
+}
+
+impl Template {
+    /// Create a template from a front matter mapping
+    pub fn new(front_matter: serde_yaml::Mapping) -> Self {
+        Self {
+            front_matter,
+            body: None,
+        }
+    }
+
+    /// Create a template from a markdown document, using its body as the skeleton
+    pub fn from_document(document: &Document) -> Self {
+        let front_matter = match document.to_yaml_value() {
+            serde_yaml::Value::Mapping(front_matter) => front_matter,
+            _ => serde_yaml::Mapping::new(),
+        };
+        let body = Some(document.body()).filter(|body| !body.trim().is_empty());
+        Self::new(front_matter).with_body(body.map(str::to_string))
+    }
+
+    /// Set the body skeleton
+    pub fn with_body(mut self, body: Option<String>) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Parse a template from YAML (or JSON) text
+    pub fn parse(content: &str) -> Result<Self> {
+        match serde_yaml::from_str(content)? {
+            serde_yaml::Value::Mapping(front_matter) => Ok(Self::new(front_matter)),
+            serde_yaml::Value::Null => Ok(Self::new(serde_yaml::Mapping::new())),
+            _ => Err(MatterOfError::validation(
+                "Template must be a mapping of front matter keys",
+            )),
+        }
+    }
+
+    /// Load a template from a YAML file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => MatterOfError::file_not_found(path),
+            _ => MatterOfError::Io(e),
+        })?;
+        Self::parse(&content).map_err(|e| {
+            MatterOfError::validation(format!("Invalid template {}: {}", path.display(), e))
+        })
+    }
+
+    /// The unrendered front matter
+    pub fn front_matter(&self) -> &serde_yaml::Mapping {
+        &self.front_matter
+    }
+
+    /// The unrendered body skeleton, if any
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    /// Render the template for one file
+    ///
+    /// Front matter placeholders like `{{title}}` look in `document` first, so
+    /// they agree with the values that survive a non-clobbering merge. The
+    /// rendered document carries the rendered body skeleton, or an empty body.
+    pub fn render(&self, context: &TemplateContext, document: &Document) -> Result<Document> {
+        let renderer = Renderer {
+            context,
+            document,
+            template: Document::from_yaml_value(
+                Some(serde_yaml::Value::Mapping(self.front_matter.clone())),
+                String::new(),
+            )?,
+        };
+        let rendered =
+            renderer.render_value(serde_yaml::Value::Mapping(self.front_matter.clone()))?;
+        let body = match &self.body {
+            Some(body) => renderer.render_str(body, 0)?,
+            None => String::new(),
+        };
+        Document::from_yaml_value(Some(rendered), body)
+    }
+}
+
+struct Renderer<'a> {
+    context: &'a TemplateContext,
+    document: &'a Document,
+    template: Document,
+}
+
+impl Renderer<'_> {
+    fn render_value(&self, value: serde_yaml::Value) -> Result<serde_yaml::Value> {
+        Ok(match value {
+            serde_yaml::Value::String(text) => {
+                serde_yaml::Value::String(self.render_str(&text, 0)?)
+            }
+            serde_yaml::Value::Sequence(items) => serde_yaml::Value::Sequence(
+                items
+                    .into_iter()
+                    .map(|item| self.render_value(item))
+                    .collect::<Result<_>>()?,
+            ),
+            serde_yaml::Value::Mapping(map) => serde_yaml::Value::Mapping(
+                map.into_iter()
+                    .map(|(key, value)| Ok((key, self.render_value(value)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => other,
+        })
+    }
+
+    fn render_str(&self, text: &str, depth: usize) -> Result<String> {
+        static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+        let placeholder = PLACEHOLDER.get_or_init(|| {
+            Regex::new(r"\{\{\s*([A-Za-z_][\w.-]*(?:\(\s*[\w.-]*\s*\))?)\s*\}\}")
+                .expect("valid regex")
+        });
+
+        // Only identifier-like expressions count, so `{{< shortcode >}}` stays as is
+        let mut error = None;
+        let rendered = placeholder.replace_all(text, |captures: &Captures| {
+            match self.evaluate(&captures[1], depth) {
+                Ok(value) => value,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    String::new()
+                }
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(rendered.into_owned()),
+        }
+    }
+
+    fn evaluate(&self, expression: &str, depth: usize) -> Result<String> {
+        if let Some(argument) = expression
+            .strip_prefix("slug(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Ok(slugify(&self.evaluate(argument.trim(), depth)?));
+        }
+        if let Some(name) = expression.strip_prefix("env.") {
+            return std::env::var(name).map_err(|_| {
+                MatterOfError::validation(format!(
+                    "Template placeholder {{{{{}}}}}: environment variable {} is not set",
+                    expression, name
+                ))
+            });
+        }
+        if let Some(value) = self.context.variable(expression) {
+            return Ok(value);
+        }
+        self.lookup(expression, depth)
+    }
+
+    /// Front matter value at a key path, from the document or else the template
+    fn lookup(&self, expression: &str, depth: usize) -> Result<String> {
+        let unknown = || {
+            MatterOfError::validation(format!(
+                "Unknown template placeholder {{{{{}}}}}",
+                expression
+            ))
+        };
+        let key_path = KeyPath::parse(expression).map_err(|_| unknown())?;
+
+        if let Some(value) = self.document.get(&key_path) {
+            return scalar_text(expression, value.as_inner());
+        }
+        let value = self.template.get(&key_path).ok_or_else(unknown)?;
+        match value.as_inner() {
+            serde_yaml::Value::String(text) if depth < MAX_DEPTH => {
+                self.render_str(text, depth + 1)
+            }
+            serde_yaml::Value::String(_) => Err(MatterOfError::validation(format!(
+                "Template placeholder {{{{{}}}}} refers to itself",
+                expression
+            ))),
+            other => scalar_text(expression, other),
+        }
+    }
+}
+
+fn scalar_text(expression: &str, value: &serde_yaml::Value) -> Result<String> {
+    match value {
+        serde_yaml::Value::String(text) => Ok(text.clone()),
+        serde_yaml::Value::Number(number) => Ok(number.to_string()),
+        serde_yaml::Value::Bool(flag) => Ok(flag.to_string()),
+        _ => Err(MatterOfError::validation(format!(
+            "Template placeholder {{{{{}}}}} is not a string, number or boolean",
+            expression
+        ))),
+    }
+}
+
+/// Lowercase text with runs of non-alphanumerics turned into single dashes
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            path: PathBuf::from("posts/hello-world.md"),
+            now: DateTime::parse_from_rfc3339("2024-05-01T10:00:00+02:00").unwrap(),
+            uuid: "7d444840-9dc0-11d1-b245-5ffdce74fad2".to_string(),
+        }
+    }
+
+    fn render(template: &str, existing: &str) -> Result<serde_yaml::Value> {
+        let document =
+            Document::from_yaml_value(Some(serde_yaml::from_str(existing).unwrap()), String::new())
+                .unwrap();
+        let rendered = Template::parse(template)?.render(&context(), &document)?;
+        Ok(rendered.to_yaml_value())
+    }
+
+    #[test]
+    fn test_render_variables_and_nesting() {
+        std::env::set_var("MATTEROF_TEMPLATE_TEST", "ann");
+        let rendered = render(
+            "{id: '{{uuid}}', date: '{{ today }}', file: '{{filename}}', \
+             author: {name: '{{env.MATTEROF_TEMPLATE_TEST}}'}, \
+             aliases: ['/{{filename_stem}}/', '/old/{{ slug(filename_stem) }}'], draft: true}",
+            "{}",
+        )
+        .unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            "{id: 7d444840-9dc0-11d1-b245-5ffdce74fad2, date: '2024-05-01', file: hello-world.md, \
+             author: {name: ann}, aliases: [/hello-world/, /old/hello-world], draft: true}",
+        )
+        .unwrap();
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_render_front_matter_lookups() {
+        // The document's title wins over the template's
+        let rendered = render(
+            "{title: '{{filename_stem}}', slug: '{{slug(title)}}'}",
+            "{title: 'Ünïcode & Rust: 2024!'}",
+        )
+        .unwrap();
+        assert_eq!(rendered["slug"], "ünïcode-rust-2024");
+
+        let rendered = render(
+            "{title: '{{filename_stem}}', slug: '{{slug(title)}}'}",
+            "{}",
+        )
+        .unwrap();
+        assert_eq!(rendered["slug"], "hello-world");
+
+        assert!(render("{a: '{{b}}', b: '{{a}}'}", "{}").is_err());
+        assert!(render("{a: '{{nope}}'}", "{}").is_err());
+        assert!(render("{a: '{{env.MATTEROF_SURELY_UNSET}}'}", "{}").is_err());
+        assert!(Template::parse("[not, a, mapping]").is_err());
+    }
+
+    #[test]
+    fn test_render_keeps_template_order() {
+        let mut document =
+            Document::from_yaml_value(Some(serde_yaml::from_str("zz: 1").unwrap()), String::new())
+                .unwrap();
+        let rendered = Template::parse("title: '{{filename_stem}}'\ndate: x\nauthor: {name: a}\n")
+            .unwrap()
+            .render(&context(), &document)
+            .unwrap();
+        let keys: Vec<_> = rendered.front_matter().unwrap().keys().collect();
+        assert_eq!(keys, ["title", "date", "author"]);
+
+        // Merged keys follow the existing ones in the template's order
+        document.merge_missing_front_matter(&rendered);
+        let keys: Vec<_> = document.front_matter().unwrap().keys().collect();
+        assert_eq!(keys, ["zz", "title", "date", "author"]);
+
+        let template = Template::from_document(
+            &crate::io::FrontMatterReader::new()
+                .parse_content("---\nzeta: 1\nalpha: 2\n---\n", None)
+                .unwrap(),
+        );
+        let keys: Vec<_> = template.front_matter().keys().collect();
+        assert_eq!(keys, ["zeta", "alpha"]);
+    }
+
+    #[test]
+    fn test_render_body_skeleton() {
+        let document = Document::from_yaml_value(
+            Some(serde_yaml::from_str("{title: '{{filename_stem}}'}").unwrap()),
+            "# {{title}}\n\nWritten {{today}}.\n{{< toc >}}\n".to_string(),
+        )
+        .unwrap();
+        let template = Template::from_document(&document);
+        assert!(template.body().is_some());
+
+        let rendered = template.render(&context(), &Document::empty()).unwrap();
+        assert_eq!(
+            rendered.body(),
+            "# hello-world\n\nWritten 2024-05-01.\n{{< toc >}}\n"
+        );
+        assert_eq!(rendered.to_yaml_value()["title"], "hello-world");
+    }
+}
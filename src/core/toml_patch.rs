@@ -0,0 +1,346 @@
+//! TOML front matter support
+//!
+//! TOML front matter is converted to the same `serde_yaml::Value` model used for
+//! YAML, so queries and mutations work unchanged. Writes go through `toml_edit`,
+//! which patches the original document in place: untouched keys keep their
+//! comments, ordering and formatting.
+
+use crate::error::{MatterOfError, Result};
+use serde_yaml::{Mapping, Value as YamlValue};
+use toml_edit::{
+    Array, ArrayOfTables, Datetime, DocumentMut, InlineTable, Item, Table, TableLike, Value,
+};
+
+/// Converts between TOML items and YAML values
+pub struct YamlTomlConverter;
+
+impl YamlTomlConverter {
+    /// Parse TOML source into a YAML value
+    pub fn parse(source: &str) -> Result<YamlValue> {
+        let document: DocumentMut = source
+            .parse()
+            .map_err(|e| MatterOfError::validation(format!("Invalid TOML: {}", e)))?;
+        Ok(Self::toml_to_yaml(document.as_item()))
+    }
+
+    /// Convert a TOML item to a YAML value
+    ///
+    /// Datetimes have no YAML counterpart here and become strings; `yaml_to_toml`
+    /// turns them back into datetimes on the way out.
+    pub fn toml_to_yaml(item: &Item) -> YamlValue {
+        match item {
+            Item::None => YamlValue::Null,
+            Item::Value(value) => Self::value_to_yaml(value),
+            Item::Table(table) => Self::table_to_yaml(table),
+            Item::ArrayOfTables(tables) => {
+                YamlValue::Sequence(tables.iter().map(|t| Self::table_to_yaml(t)).collect())
+            }
+        }
+    }
+
+    fn table_to_yaml(table: &dyn TableLike) -> YamlValue {
+        let mut map = Mapping::new();
+        for (key, item) in table.iter() {
+            map.insert(YamlValue::String(key.to_string()), Self::toml_to_yaml(item));
+        }
+        YamlValue::Mapping(map)
+    }
+
+    fn value_to_yaml(value: &Value) -> YamlValue {
+        match value {
+            Value::String(s) => YamlValue::String(s.value().clone()),
+            Value::Integer(i) => YamlValue::Number((*i.value()).into()),
+            Value::Float(f) => YamlValue::Number((*f.value()).into()),
+            Value::Boolean(b) => YamlValue::Bool(*b.value()),
+            Value::Datetime(dt) => YamlValue::String(dt.value().to_string()),
+            Value::Array(array) => {
+                YamlValue::Sequence(array.iter().map(Self::value_to_yaml).collect())
+            }
+            Value::InlineTable(table) => Self::table_to_yaml(table),
+        }
+    }
+
+    /// Convert a YAML value to an inline TOML value
+    ///
+    /// Strings holding a valid TOML datetime are written as bare datetimes.
+    /// Fails for values TOML cannot represent, such as null or non-string keys.
+    pub fn yaml_to_toml(value: &YamlValue) -> Result<Value> {
+        match value {
+            YamlValue::Null => Err(MatterOfError::type_conversion("null", "TOML")),
+            YamlValue::Bool(b) => Ok(Value::from(*b)),
+            YamlValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Ok(Value::from(i))
+                } else if n.is_u64() {
+                    Err(MatterOfError::type_conversion(
+                        format!("integer {}", n),
+                        "TOML (exceeds 64-bit signed range)",
+                    ))
+                } else {
+                    Ok(Value::from(n.as_f64().unwrap_or(f64::NAN)))
+                }
+            }
+            YamlValue::String(s) => Ok(match s.parse::<Datetime>() {
+                Ok(datetime) => Value::from(datetime),
+                Err(_) => Value::from(s.as_str()),
+            }),
+            YamlValue::Sequence(seq) => {
+                let mut array = Array::new();
+                for item in seq {
+                    array.push(Self::yaml_to_toml(item)?);
+                }
+                Ok(Value::Array(array))
+            }
+            YamlValue::Mapping(map) => {
+                let mut table = InlineTable::new();
+                for (key, item) in map {
+                    table.insert(Self::key(key)?, Self::yaml_to_toml(item)?);
+                }
+                Ok(Value::InlineTable(table))
+            }
+            YamlValue::Tagged(tagged) => Self::yaml_to_toml(&tagged.value),
+        }
+    }
+
+    /// Convert a YAML value to a TOML item for use directly inside a table
+    ///
+    /// Mappings become `[table]` sections and sequences of mappings become
+    /// `[[array.of.tables]]`.
+    pub fn yaml_to_toml_item(value: &YamlValue) -> Result<Item> {
+        match value {
+            YamlValue::Mapping(map) => Ok(Item::Table(Self::yaml_to_table(map)?)),
+            YamlValue::Sequence(seq)
+                if !seq.is_empty() && seq.iter().all(YamlValue::is_mapping) =>
+            {
+                let mut tables = ArrayOfTables::new();
+                for item in seq {
+                    if let YamlValue::Mapping(map) = item {
+                        tables.push(Self::yaml_to_table(map)?);
+                    }
+                }
+                Ok(Item::ArrayOfTables(tables))
+            }
+            YamlValue::Tagged(tagged) => Self::yaml_to_toml_item(&tagged.value),
+            _ => Ok(Item::Value(Self::yaml_to_toml(value)?)),
+        }
+    }
+
+    fn yaml_to_table(map: &Mapping) -> Result<Table> {
+        let mut table = Table::new();
+        for (key, value) in map {
+            table.insert(Self::key(key)?, Self::yaml_to_toml_item(value)?);
+        }
+        Ok(table)
+    }
+
+    fn key(key: &YamlValue) -> Result<&str> {
+        key.as_str()
+            .ok_or_else(|| MatterOfError::type_conversion(format!("key {:?}", key), "TOML"))
+    }
+}
+
+/// Format-preserving patcher for TOML front matter
+pub struct TomlPatcher;
+
+impl TomlPatcher {
+    /// Rewrite TOML `source` so that it holds `new_value`
+    ///
+    /// Unchanged keys keep their exact text; changed values keep their
+    /// surrounding whitespace and comments.
+    pub fn patch(source: &str, new_value: &YamlValue) -> Result<String> {
+        let mut document: DocumentMut = source
+            .parse()
+            .map_err(|e| MatterOfError::validation(format!("Invalid TOML: {}", e)))?;
+        let map = Self::root_mapping(new_value)?;
+
+        Self::patch_table(document.as_table_mut(), map, false)?;
+        Ok(document.to_string())
+    }
+
+    /// Render a value as a fresh TOML document
+    pub fn render(value: &YamlValue) -> Result<String> {
+        let mut document = DocumentMut::new();
+        for (key, value) in Self::root_mapping(value)? {
+            document.insert(
+                YamlTomlConverter::key(key)?,
+                YamlTomlConverter::yaml_to_toml_item(value)?,
+            );
+        }
+        Ok(document.to_string())
+    }
+
+    fn root_mapping(value: &YamlValue) -> Result<&Mapping> {
+        value
+            .as_mapping()
+            .ok_or_else(|| MatterOfError::type_conversion("non-mapping front matter", "TOML"))
+    }
+
+    /// Patch a table in place; `inline` tables can only hold plain values
+    fn patch_table(table: &mut dyn TableLike, new: &Mapping, inline: bool) -> Result<()> {
+        let removed: Vec<String> = table
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| !new.contains_key(key.as_str()))
+            .collect();
+        for key in removed {
+            table.remove(&key);
+        }
+
+        for (key, value) in new {
+            let key = YamlTomlConverter::key(key)?;
+            match table.get_key_value_mut(key) {
+                Some((mut key, item)) => {
+                    let was_value = item.is_value();
+                    Self::patch_item(item, value)?;
+                    // A `[table]` or `[[array]]` header key carries no `key = ` spacing
+                    if !was_value && item.is_value() {
+                        key.leaf_decor_mut().clear();
+                    }
+                }
+                None => {
+                    let item = if inline || table.is_dotted() {
+                        Item::Value(YamlTomlConverter::yaml_to_toml(value)?)
+                    } else {
+                        YamlTomlConverter::yaml_to_toml_item(value)?
+                    };
+                    table.insert(key, item);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn patch_item(item: &mut Item, new: &YamlValue) -> Result<()> {
+        if &YamlTomlConverter::toml_to_yaml(item) == new {
+            return Ok(());
+        }
+
+        match (item, new) {
+            (Item::Table(table), YamlValue::Mapping(map)) => Self::patch_table(table, map, false),
+            (Item::Value(Value::InlineTable(table)), YamlValue::Mapping(map)) => {
+                Self::patch_table(table, map, true)
+            }
+            (Item::ArrayOfTables(tables), YamlValue::Sequence(seq))
+                if !seq.is_empty() && seq.iter().all(YamlValue::is_mapping) =>
+            {
+                while tables.len() > seq.len() {
+                    tables.remove(tables.len() - 1);
+                }
+                for (index, value) in seq.iter().enumerate() {
+                    if let YamlValue::Mapping(map) = value {
+                        match tables.get_mut(index) {
+                            Some(table) => Self::patch_table(table, map, false)?,
+                            None => tables.push(YamlTomlConverter::yaml_to_table(map)?),
+                        }
+                    }
+                }
+                Ok(())
+            }
+            (Item::Value(value), _) => {
+                let decor = value.decor().clone();
+                *value = YamlTomlConverter::yaml_to_toml(new)?;
+                *value.decor_mut() = decor;
+                Ok(())
+            }
+            (item, _) => {
+                *item = YamlTomlConverter::yaml_to_toml_item(new)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(source: &str) -> YamlValue {
+        serde_yaml::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let value = YamlTomlConverter::parse(
+            "title = \"Hello\"\ndate = 2024-01-02T03:04:05Z\ntags = [\"a\", \"b\"]\n\n[author]\nname = \"Jane\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            yaml(
+                "title: Hello\ndate: '2024-01-02T03:04:05Z'\ntags: [a, b]\nauthor: {name: Jane}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_patch_preserves_formatting() {
+        let source = "# Hugo post\ntitle = \"Hello\" # shown in header\ndraft = true\nweight   = 10\n\n[params]\n  cover = \"a.png\"\n";
+        let mut value = YamlTomlConverter::parse(source).unwrap();
+        let map = value.as_mapping_mut().unwrap();
+        map.insert("title".into(), "World".into());
+        map.remove("draft");
+        map.insert("slug".into(), "hello".into());
+
+        let patched = TomlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            patched,
+            "# Hugo post\ntitle = \"World\" # shown in header\nweight   = 10\nslug = \"hello\"\n\n[params]\n  cover = \"a.png\"\n"
+        );
+    }
+
+    #[test]
+    fn test_render_and_unrepresentable_values() {
+        let value = yaml("title: T\nauthor:\n  name: A\n");
+        assert_eq!(
+            TomlPatcher::render(&value).unwrap(),
+            "title = \"T\"\n\n[author]\nname = \"A\"\n"
+        );
+
+        let err = TomlPatcher::render(&yaml("title: ~\n")).unwrap_err();
+        assert!(matches!(err, MatterOfError::TypeConversion { .. }));
+    }
+
+    #[test]
+    fn test_datetimes_stay_bare() {
+        let source = "title = \"Hello\"\ndate = 2024-05-01T10:00:00Z\nlastmod = 2024-05-02\n";
+        let mut value = YamlTomlConverter::parse(source).unwrap();
+        assert_eq!(TomlPatcher::render(&value).unwrap(), source);
+
+        value
+            .as_mapping_mut()
+            .unwrap()
+            .insert("lastmod".into(), "2024-06-02".into());
+        assert_eq!(
+            TomlPatcher::patch(source, &value).unwrap(),
+            "title = \"Hello\"\ndate = 2024-05-01T10:00:00Z\nlastmod = 2024-06-02\n"
+        );
+    }
+
+    #[test]
+    fn test_patch_array_of_tables() {
+        let source = "title = \"Menu\"\n\n# first entry\n[[menu]]\nname = \"Home\" # landing\n\n[[menu]]\nname = \"Blog\"\n";
+        let mut value = YamlTomlConverter::parse(source).unwrap();
+        let menu = value["menu"].as_sequence_mut().unwrap();
+
+        menu.push(yaml("name: About\n"));
+        let appended = TomlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            appended,
+            format!("{}\n[[menu]]\nname = \"About\"\n", source)
+        );
+
+        let menu = value["menu"].as_sequence_mut().unwrap();
+        menu.truncate(1);
+        assert_eq!(
+            TomlPatcher::patch(source, &value).unwrap(),
+            "title = \"Menu\"\n\n# first entry\n[[menu]]\nname = \"Home\" # landing\n"
+        );
+
+        value["menu"] = yaml("[a, b]\n");
+        assert_eq!(
+            TomlPatcher::patch(source, &value).unwrap(),
+            "title = \"Menu\"\nmenu = [\"a\", \"b\"]\n"
+        );
+    }
+}
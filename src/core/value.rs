@@ -4,8 +4,8 @@
 //! conversions and operations specific to front matter handling.
 
 use crate::error::{MatterOfError, Result};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 use std::fmt;
 
 /// A type-safe wrapper around YAML values for front matter
@@ -53,7 +53,7 @@ impl FrontMatterValue {
     }
 
     /// Create an object value
-    pub fn object(map: BTreeMap<String, FrontMatterValue>) -> Self {
+    pub fn object(map: IndexMap<String, FrontMatterValue>) -> Self {
         let mut yaml_map = serde_yaml::Mapping::new();
         for (k, v) in map {
             yaml_map.insert(serde_yaml::Value::String(k), v.inner);
@@ -131,7 +131,7 @@ impl FrontMatterValue {
     }
 
     /// Try to convert to object
-    pub fn as_object(&self) -> Option<BTreeMap<String, FrontMatterValue>> {
+    pub fn as_object(&self) -> Option<IndexMap<String, FrontMatterValue>> {
         self.inner.as_mapping().map(|map| {
             map.iter()
                 .filter_map(|(k, v)| {
@@ -215,6 +215,13 @@ impl FrontMatterValue {
         self.inner = merge_yaml_values(self.inner.clone(), other.inner)?;
         Ok(())
     }
+
+    /// Deep merge with another value, only adding keys this one lacks
+    ///
+    /// Returns whether anything was added.
+    pub fn merge_missing(&mut self, other: FrontMatterValue) -> bool {
+        fill_yaml_values(&mut self.inner, other.inner)
+    }
 }
 
 /// Supported value types for type conversion
@@ -299,6 +306,27 @@ fn merge_yaml_values(
     }
 }
 
+/// Add the keys of `source` missing from `target`, recursing into mappings
+fn fill_yaml_values(target: &mut serde_yaml::Value, source: serde_yaml::Value) -> bool {
+    let (serde_yaml::Value::Mapping(target_map), serde_yaml::Value::Mapping(source_map)) =
+        (target, source)
+    else {
+        return false;
+    };
+
+    let mut added = false;
+    for (key, value) in source_map {
+        match target_map.get_mut(&key) {
+            Some(existing) => added |= fill_yaml_values(existing, value),
+            None => {
+                target_map.insert(key, value);
+                added = true;
+            }
+        }
+    }
+    added
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,6 +347,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_string_parsing() {
         let val = FrontMatterValue::parse_from_string("42", Some(&ValueType::Int)).unwrap();
         assert_eq!(val.as_int(), Some(42));
@@ -345,14 +374,14 @@ mod tests {
     #[test]
     fn test_value_merge() {
         let mut obj1 = FrontMatterValue::object({
-            let mut map = BTreeMap::new();
+            let mut map = IndexMap::new();
             map.insert("a".to_string(), FrontMatterValue::int(1));
             map.insert("b".to_string(), FrontMatterValue::string("hello"));
             map
         });
 
         let obj2 = FrontMatterValue::object({
-            let mut map = BTreeMap::new();
+            let mut map = IndexMap::new();
             map.insert("b".to_string(), FrontMatterValue::string("world"));
             map.insert("c".to_string(), FrontMatterValue::int(3));
             map
@@ -0,0 +1,1134 @@
+//! Format-preserving YAML editing
+//!
+//! Re-serializing front matter with `serde_yaml` sorts keys, drops comments and
+//! normalizes scalar styles. This module patches the original YAML source
+//! instead: only the lines holding values that actually changed are rewritten,
+//! while key order, comments, blank lines and quoting are kept byte-for-byte.
+//!
+//! Every patch is verified by parsing the result back. If the source uses a
+//! construct the patcher does not understand, the value is rendered from
+//! scratch in the original key order instead.
+
+use crate::error::{MatterOfError, Result};
+use serde_yaml::{Mapping, Value as YamlValue};
+
+/// Indentation style used when rendering YAML blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YamlStyle {
+    /// Spaces per nesting level of block mappings
+    pub indent: usize,
+    /// Offset of the `-` of sequences nested in a mapping (0 renders indentless sequences)
+    pub sequence_indent: usize,
+}
+
+impl Default for YamlStyle {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            sequence_indent: 0,
+        }
+    }
+}
+
+impl YamlStyle {
+    /// Detect the indentation style used by existing YAML source
+    ///
+    /// Anything that cannot be determined keeps its default value.
+    pub fn detect(source: &str) -> Self {
+        let (lines, _, _) = split_lines(source);
+        let source = Source { lines };
+        match source.parse_root() {
+            Some(root) => Self::detect_in(&source, &root),
+            None => Self::default(),
+        }
+    }
+
+    fn detect_in(source: &Source<'_>, root: &Node) -> Self {
+        let mut indent = None;
+        let mut sequence_indent = None;
+        let mut stack = vec![root];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Mapping { entries, .. } => {
+                    for (_, slot) in entries {
+                        let own_line = !source.shares_line(slot);
+                        match &slot.value {
+                            Node::Mapping { indent: child, .. }
+                                if own_line && *child > slot.col =>
+                            {
+                                indent.get_or_insert(child - slot.col);
+                            }
+                            Node::Sequence { indent: child, .. }
+                                if own_line && *child >= slot.col =>
+                            {
+                                sequence_indent.get_or_insert(child - slot.col);
+                            }
+                            _ => {}
+                        }
+                        stack.push(&slot.value);
+                    }
+                }
+                Node::Sequence { items, .. } => stack.extend(items.iter().map(|slot| &slot.value)),
+                Node::Inline | Node::Empty => {}
+            }
+        }
+
+        let default = Self::default();
+        Self {
+            indent: indent.unwrap_or(default.indent),
+            sequence_indent: sequence_indent.unwrap_or(default.sequence_indent),
+        }
+    }
+}
+
+/// Format-preserving patcher for YAML front matter
+pub struct YamlPatcher;
+
+impl YamlPatcher {
+    /// Rewrite `source` so that it parses to `new_value`
+    ///
+    /// Entries whose values are unchanged keep their exact text. Changed values
+    /// are rewritten in place, removed keys are deleted, and new keys are
+    /// appended to the end of their mapping.
+    pub fn patch(source: &str, new_value: &YamlValue) -> Result<String> {
+        let old_value: YamlValue = if source.trim().is_empty() {
+            YamlValue::Null
+        } else {
+            serde_yaml::from_str(source)?
+        };
+
+        if &old_value == new_value {
+            return Ok(source.to_string());
+        }
+
+        if let Some(patched) = Self::try_patch(source, &old_value, new_value) {
+            if parses_to(&patched, new_value) {
+                return Ok(patched);
+            }
+        }
+
+        // Fall back to a fresh rendering that still keeps the original key order
+        let (_, newline, _) = split_lines(source);
+        let ordered = Self::order_like(new_value, &old_value);
+        let rendered = Self::render(&ordered, YamlStyle::detect(source))?;
+        Ok(rendered.replace('\n', newline))
+    }
+
+    /// Render a value as block YAML using the given style
+    ///
+    /// Output ends with a line break unless the value is null.
+    pub fn render(value: &YamlValue, style: YamlStyle) -> Result<String> {
+        match value {
+            YamlValue::Null => Ok(String::new()),
+            YamlValue::Mapping(map) if !map.is_empty() => {
+                let renderer = Renderer { style };
+                if let Ok(lines) = renderer.render_mapping(map, 0) {
+                    let rendered = format!("{}\n", lines.join("\n"));
+                    if parses_to(&rendered, value) {
+                        return Ok(rendered);
+                    }
+                }
+                Ok(serde_yaml::to_string(value)?)
+            }
+            _ => Ok(serde_yaml::to_string(value)?),
+        }
+    }
+
+    /// Reorder the mapping keys of `value` to follow `template`
+    ///
+    /// Keys missing from the template keep their relative order after the known ones.
+    pub fn order_like(value: &YamlValue, template: &YamlValue) -> YamlValue {
+        match (value, template) {
+            (YamlValue::Mapping(map), YamlValue::Mapping(template_map)) => {
+                let mut ordered = Mapping::new();
+                for (key, template_value) in template_map {
+                    if let Some(value) = map.get(key) {
+                        ordered.insert(key.clone(), Self::order_like(value, template_value));
+                    }
+                }
+                for (key, value) in map {
+                    if !ordered.contains_key(key) {
+                        ordered.insert(key.clone(), value.clone());
+                    }
+                }
+                YamlValue::Mapping(ordered)
+            }
+            (YamlValue::Sequence(seq), YamlValue::Sequence(template_seq)) => YamlValue::Sequence(
+                seq.iter()
+                    .enumerate()
+                    .map(|(i, value)| match template_seq.get(i) {
+                        Some(template_value) => Self::order_like(value, template_value),
+                        None => value.clone(),
+                    })
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    fn try_patch(source: &str, old_value: &YamlValue, new_value: &YamlValue) -> Option<String> {
+        let empty = Mapping::new();
+        let old_map = match old_value {
+            YamlValue::Mapping(map) => map,
+            YamlValue::Null => &empty,
+            _ => return None,
+        };
+        let new_map = new_value.as_mapping()?;
+
+        let (lines, newline, trailing) = split_lines(source);
+        let source = Source { lines };
+        let root = source.parse_root()?;
+        let Node::Mapping { indent, entries } = &root else {
+            return None;
+        };
+
+        let mut patcher = Patcher {
+            source: &source,
+            renderer: Renderer {
+                style: YamlStyle::detect_in(&source, &root),
+            },
+            edits: Vec::new(),
+            failed: false,
+        };
+        let end = source.lines.len();
+        if !patcher.patch_mapping(entries, *indent, old_map, new_map, end) || patcher.failed {
+            return None;
+        }
+
+        Some(patcher.finish(newline, trailing))
+    }
+}
+
+/// Check whether YAML text parses to the expected value
+fn parses_to(text: &str, expected: &YamlValue) -> bool {
+    serde_yaml::from_str::<YamlValue>(text)
+        .map(|value| &value == expected)
+        .unwrap_or(false)
+}
+
+/// Split source into lines without terminators
+///
+/// Returns the lines, the line break style, and whether the source ends with a line break.
+fn split_lines(source: &str) -> (Vec<&str>, &'static str, bool) {
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    if source.is_empty() {
+        return (Vec::new(), newline, true);
+    }
+
+    let trailing = source.ends_with('\n');
+    let content = source.strip_suffix('\n').unwrap_or(source);
+    let lines = content
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    (lines, newline, trailing)
+}
+
+/// Indentation of a line, or `None` for blank lines
+fn indent_of(line: &str) -> Option<usize> {
+    if line.trim().is_empty() {
+        None
+    } else {
+        Some(line.len() - line.trim_start_matches(' ').len())
+    }
+}
+
+/// Whether a line carries YAML content (not blank, not a comment)
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+/// Whether text starts with a block sequence indicator
+fn is_dash(text: &str) -> bool {
+    text.strip_prefix('-')
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+}
+
+/// Whether the text after an indicator holds a value
+fn has_inline_value(text: &str) -> bool {
+    is_content(text)
+}
+
+/// Parse a mapping key at the start of `text`
+///
+/// Returns the key and the byte offset of its `:` indicator.
+fn parse_key(text: &str) -> Option<(YamlValue, usize)> {
+    let bytes = text.as_bytes();
+    let key_end = match *bytes.first()? {
+        b'"' => {
+            let mut i = 1;
+            loop {
+                match bytes.get(i)? {
+                    b'\\' => i += 2,
+                    b'"' => break i + 1,
+                    _ => i += 1,
+                }
+            }
+        }
+        b'\'' => {
+            let mut i = 1;
+            loop {
+                match bytes.get(i)? {
+                    b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+                    b'\'' => break i + 1,
+                    _ => i += 1,
+                }
+            }
+        }
+        b'-' if is_dash(text) => return None,
+        b'#' | b'[' | b']' | b'{' | b'}' | b',' | b'?' | b'&' | b'*' | b'!' | b'|' | b'>'
+        | b'%' | b'@' | b'`' => return None,
+        _ => {
+            let mut colon = None;
+            for (i, c) in text.char_indices() {
+                if c == '#' && text[..i].ends_with([' ', '\t']) {
+                    return None;
+                }
+                if c == ':'
+                    && text[i + 1..]
+                        .chars()
+                        .next()
+                        .is_none_or(|n| n == ' ' || n == '\t')
+                {
+                    colon = Some(i);
+                    break;
+                }
+            }
+            let colon = colon?;
+            let key_text = text[..colon].trim_end();
+            if key_text.is_empty() {
+                return None;
+            }
+            return serde_yaml::from_str(key_text).ok().map(|key| (key, colon));
+        }
+    };
+
+    let after = &text[key_end..];
+    let trimmed = after.trim_start_matches([' ', '\t']);
+    let rest = trimmed.strip_prefix(':')?;
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let colon = key_end + (after.len() - trimmed.len());
+    serde_yaml::from_str(&text[..key_end])
+        .ok()
+        .map(|key| (key, colon))
+}
+
+/// Split the text after an indicator into its value and trailing comment
+///
+/// The comment keeps its leading whitespace so it can be re-attached verbatim.
+fn split_comment(text: &str) -> (&str, &str) {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut escaped = false;
+    let mut token_start = true;
+
+    for (i, c) in text.char_indices() {
+        if in_double {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_double = false;
+            }
+            continue;
+        }
+        if in_single {
+            if c == '\'' {
+                in_single = false;
+            }
+            continue;
+        }
+        match c {
+            '#' if i == 0 || text[..i].ends_with([' ', '\t']) => {
+                let value_end = text[..i].trim_end().len();
+                return (&text[..value_end], &text[value_end..]);
+            }
+            '"' if token_start => in_double = true,
+            '\'' if token_start => in_single = true,
+            _ => {}
+        }
+        token_start = c.is_whitespace() || matches!(c, '[' | '{' | ',');
+    }
+
+    (text, "")
+}
+
+/// A parsed block node of the YAML source
+#[derive(Debug)]
+enum Node {
+    /// Block mapping whose keys start at `indent`
+    Mapping {
+        indent: usize,
+        entries: Vec<(YamlValue, Slot)>,
+    },
+    /// Block sequence whose dashes start at `indent`
+    Sequence { indent: usize, items: Vec<Slot> },
+    /// Any other value (scalars, flow collections, block scalars) treated as opaque text
+    Inline,
+    /// Nothing after the indicator (implicit null)
+    Empty,
+}
+
+/// Location of a mapping entry or sequence item in the source lines
+#[derive(Debug)]
+struct Slot {
+    /// Line holding the key or dash
+    line: usize,
+    /// Column where the key or dash starts
+    col: usize,
+    /// Column right after the `:` or `-` indicator
+    value_col: usize,
+    /// Exclusive end line of the entry or item
+    end: usize,
+    /// The value of the entry or item
+    value: Node,
+}
+
+/// Line-oriented view of YAML source
+struct Source<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl Source<'_> {
+    fn parse_root(&self) -> Option<Node> {
+        match (0..self.lines.len()).find(|&i| is_content(self.lines[i])) {
+            None => Some(Node::Mapping {
+                indent: 0,
+                entries: Vec::new(),
+            }),
+            Some(first) => {
+                let indent = indent_of(self.lines[first])?;
+                match self.parse_block(first, indent, self.lines.len())? {
+                    node @ Node::Mapping { .. } => Some(node),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Parse the block node starting at `(line, col)` and ending before line `end`
+    fn parse_block(&self, line: usize, col: usize, end: usize) -> Option<Node> {
+        let text = &self.lines[line][col..];
+        if is_dash(text) {
+            self.parse_sequence(line, col, end)
+        } else if parse_key(text).is_some() {
+            self.parse_mapping(line, col, end)
+        } else {
+            None
+        }
+    }
+
+    /// Parse a value written on the lines following its key or dash
+    fn parse_nested(&self, start: usize, end: usize) -> Node {
+        match (start..end).find(|&i| is_content(self.lines[i])) {
+            None => Node::Empty,
+            Some(first) => indent_of(self.lines[first])
+                .and_then(|indent| self.parse_block(first, indent, end))
+                .unwrap_or(Node::Inline),
+        }
+    }
+
+    fn parse_mapping(&self, start: usize, indent: usize, end: usize) -> Option<Node> {
+        let mut entries = Vec::new();
+        let mut line = start;
+
+        loop {
+            let (key, colon) = parse_key(&self.lines[line][indent..])?;
+            let value_col = indent + colon + 1;
+            let inline = has_inline_value(&self.lines[line][value_col..]);
+            let entry_end = self.block_end(line, indent, end, !inline);
+            let value = if inline {
+                Node::Inline
+            } else {
+                self.parse_nested(line + 1, entry_end)
+            };
+            entries.push((
+                key,
+                Slot {
+                    line,
+                    col: indent,
+                    value_col,
+                    end: entry_end,
+                    value,
+                },
+            ));
+
+            match (entry_end..end).find(|&i| is_content(self.lines[i])) {
+                None => break,
+                Some(next) if indent_of(self.lines[next]) == Some(indent) => line = next,
+                Some(_) => return None,
+            }
+        }
+
+        Some(Node::Mapping { indent, entries })
+    }
+
+    fn parse_sequence(&self, start: usize, indent: usize, end: usize) -> Option<Node> {
+        let mut items = Vec::new();
+        let mut line = start;
+
+        loop {
+            if !is_dash(&self.lines[line][indent..]) {
+                return None;
+            }
+            let value_col = indent + 1;
+            let rest = &self.lines[line][value_col..];
+            let item_end = self.block_end(line, indent, end, false);
+            let value = if has_inline_value(rest) {
+                let inner_col = value_col + (rest.len() - rest.trim_start_matches(' ').len());
+                self.parse_block(line, inner_col, item_end)
+                    .unwrap_or(Node::Inline)
+            } else {
+                self.parse_nested(line + 1, item_end)
+            };
+            items.push(Slot {
+                line,
+                col: indent,
+                value_col,
+                end: item_end,
+                value,
+            });
+
+            match (item_end..end).find(|&i| is_content(self.lines[i])) {
+                None => break,
+                Some(next) if indent_of(self.lines[next]) == Some(indent) => line = next,
+                Some(_) => return None,
+            }
+        }
+
+        Some(Node::Sequence { indent, items })
+    }
+
+    /// Find the exclusive end line of the entry or item starting at `line`
+    ///
+    /// Trailing blank lines are not part of the block. Lines at the same
+    /// indentation only continue the block when they form an indentless sequence.
+    fn block_end(&self, line: usize, indent: usize, end: usize, allow_dash: bool) -> usize {
+        let mut last = line;
+        for i in line + 1..end {
+            let text = self.lines[i];
+            match indent_of(text) {
+                None => continue,
+                Some(n) if n > indent => last = i,
+                Some(n) if n == indent && allow_dash && is_dash(&text[n..]) => last = i,
+                Some(_) => break,
+            }
+        }
+        last + 1
+    }
+
+    /// Whether a slot starts in the middle of a line (e.g. the first key after `- `)
+    fn shares_line(&self, slot: &Slot) -> bool {
+        indent_of(self.lines[slot.line]) != Some(slot.col)
+    }
+}
+
+/// Renders values as block YAML lines
+struct Renderer {
+    style: YamlStyle,
+}
+
+impl Renderer {
+    fn render_mapping(&self, map: &Mapping, indent: usize) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        for (key, value) in map {
+            lines.extend(self.render_entry(&render_key(key)?, value, indent)?);
+        }
+        Ok(lines)
+    }
+
+    fn render_sequence(&self, seq: &[YamlValue], indent: usize) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+        for value in seq {
+            lines.extend(self.render_item(value, indent)?);
+        }
+        Ok(lines)
+    }
+
+    fn render_entry(&self, key: &str, value: &YamlValue, indent: usize) -> Result<Vec<String>> {
+        let pad = " ".repeat(indent);
+        match value {
+            YamlValue::Mapping(map) if !map.is_empty() => {
+                let mut lines = vec![format!("{}{}:", pad, key)];
+                lines.extend(self.render_mapping(map, indent + self.style.indent)?);
+                Ok(lines)
+            }
+            YamlValue::Sequence(seq) if !seq.is_empty() => {
+                let mut lines = vec![format!("{}{}:", pad, key)];
+                lines.extend(self.render_sequence(seq, indent + self.style.sequence_indent)?);
+                Ok(lines)
+            }
+            _ => {
+                let scalar = scalar_lines(value)?;
+                let mut lines = vec![format!("{}{}: {}", pad, key, scalar[0])];
+                lines.extend(scalar[1..].iter().map(|line| shift(line, indent)));
+                Ok(lines)
+            }
+        }
+    }
+
+    fn render_item(&self, value: &YamlValue, indent: usize) -> Result<Vec<String>> {
+        let pad = " ".repeat(indent);
+        let nested = match value {
+            YamlValue::Mapping(map) if !map.is_empty() => {
+                Some(self.render_mapping(map, indent + 2)?)
+            }
+            YamlValue::Sequence(seq) if !seq.is_empty() => {
+                Some(self.render_sequence(seq, indent + 2)?)
+            }
+            _ => None,
+        };
+
+        match nested {
+            Some(mut lines) => {
+                lines[0] = format!("{}- {}", pad, &lines[0][indent + 2..]);
+                Ok(lines)
+            }
+            None => {
+                let scalar = scalar_lines(value)?;
+                let mut lines = vec![format!("{}- {}", pad, scalar[0])];
+                lines.extend(scalar[1..].iter().map(|line| shift(line, indent)));
+                Ok(lines)
+            }
+        }
+    }
+}
+
+/// Render a scalar (or empty collection) as YAML lines
+///
+/// Multi-line strings become block scalars whose continuation lines are
+/// indented relative to column 0 and must be shifted by the caller.
+fn scalar_lines(value: &YamlValue) -> Result<Vec<String>> {
+    let is_block = |value: &YamlValue| match value {
+        YamlValue::Mapping(map) => !map.is_empty(),
+        YamlValue::Sequence(seq) => !seq.is_empty(),
+        _ => false,
+    };
+    let nested_tag = matches!(value, YamlValue::Tagged(tagged) if is_block(&tagged.value));
+    if is_block(value) || nested_tag {
+        return Err(MatterOfError::not_supported(
+            "rendering a collection as a scalar".to_string(),
+        ));
+    }
+
+    let rendered = serde_yaml::to_string(value)?;
+    let rendered = rendered.strip_suffix('\n').unwrap_or(&rendered);
+    Ok(rendered.split('\n').map(String::from).collect())
+}
+
+/// Render a mapping key on a single line
+fn render_key(key: &YamlValue) -> Result<String> {
+    let lines = scalar_lines(key)?;
+    if lines.len() != 1 {
+        return Err(MatterOfError::not_supported(format!(
+            "multi-line mapping key {:?}",
+            key
+        )));
+    }
+    Ok(lines.into_iter().next().unwrap_or_default())
+}
+
+/// Render a value in flow style (`[a, b]`, `{k: v}`)
+fn render_flow(value: &YamlValue) -> Result<String> {
+    match value {
+        YamlValue::Sequence(seq) => {
+            let items: Result<Vec<String>> = seq.iter().map(render_flow).collect();
+            Ok(format!("[{}]", items?.join(", ")))
+        }
+        YamlValue::Mapping(map) => {
+            let entries: Result<Vec<String>> = map
+                .iter()
+                .map(|(k, v)| Ok(format!("{}: {}", render_flow(k)?, render_flow(v)?)))
+                .collect();
+            Ok(format!("{{{}}}", entries?.join(", ")))
+        }
+        YamlValue::String(s) => {
+            let lines = scalar_lines(value)?;
+            let plain = !lines[0].starts_with(['\'', '"']);
+            if lines.len() != 1 || (plain && s.contains([',', '[', ']', '{', '}'])) {
+                serde_json::to_string(s).map_err(|e| MatterOfError::validation(e.to_string()))
+            } else {
+                Ok(lines[0].clone())
+            }
+        }
+        _ => {
+            let lines = scalar_lines(value)?;
+            if lines.len() != 1 {
+                return Err(MatterOfError::not_supported(format!(
+                    "flow rendering of {:?}",
+                    value
+                )));
+            }
+            Ok(lines[0].clone())
+        }
+    }
+}
+
+/// Indent a rendered line, leaving blank lines empty
+fn shift(line: &str, indent: usize) -> String {
+    if line.is_empty() {
+        String::new()
+    } else {
+        format!("{}{}", " ".repeat(indent), line)
+    }
+}
+
+/// A line-range replacement in the source
+struct Edit {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+/// Computes the line edits turning an old value into a new one
+struct Patcher<'a> {
+    source: &'a Source<'a>,
+    renderer: Renderer,
+    edits: Vec<Edit>,
+    /// Set when a changed value could not be rendered
+    failed: bool,
+}
+
+impl Patcher<'_> {
+    fn edit(&mut self, start: usize, end: usize, lines: Vec<String>) {
+        self.edits.push(Edit { start, end, lines });
+    }
+
+    /// Patch a block mapping; returns false (without recording edits) if the
+    /// source does not match the mapping closely enough
+    fn patch_mapping(
+        &mut self,
+        entries: &[(YamlValue, Slot)],
+        indent: usize,
+        old: &Mapping,
+        new: &Mapping,
+        end: usize,
+    ) -> bool {
+        if entries.len() != old.len() || entries.iter().any(|(key, _)| !old.contains_key(key)) {
+            return false;
+        }
+        if let Some((key, slot)) = entries.first() {
+            if !new.contains_key(key) && self.source.shares_line(slot) {
+                return false;
+            }
+        }
+
+        let mut added = Vec::new();
+        for (key, value) in new {
+            if !old.contains_key(key) {
+                match render_key(key).and_then(|k| self.renderer.render_entry(&k, value, indent)) {
+                    Ok(lines) => added.extend(lines),
+                    Err(_) => return false,
+                }
+            }
+        }
+
+        for (key, slot) in entries {
+            match (old.get(key), new.get(key)) {
+                (_, None) => self.edit(slot.line, slot.end, Vec::new()),
+                (Some(old_value), Some(new_value)) if old_value != new_value => {
+                    self.patch_slot(slot, old_value, new_value, true)
+                }
+                _ => {}
+            }
+        }
+
+        if !added.is_empty() {
+            let at = entries.last().map_or(end, |(_, slot)| slot.end);
+            self.edit(at, at, added);
+        }
+        true
+    }
+
+    /// Patch a block sequence; supports per-item changes, pure insertions and
+    /// pure removals
+    fn patch_sequence(
+        &mut self,
+        items: &[Slot],
+        indent: usize,
+        old: &[YamlValue],
+        new: &[YamlValue],
+    ) -> bool {
+        if items.len() != old.len() || items.is_empty() {
+            return false;
+        }
+
+        if old.len() == new.len() {
+            for ((slot, old_value), new_value) in items.iter().zip(old).zip(new) {
+                if old_value != new_value {
+                    self.patch_slot(slot, old_value, new_value, false);
+                }
+            }
+            return true;
+        }
+
+        if new.len() > old.len() {
+            // Insertions only: old must be a subsequence of new
+            let mut inserts: Vec<(usize, Vec<String>)> = Vec::new();
+            let mut next_old = 0;
+            for value in new {
+                if next_old < old.len() && &old[next_old] == value {
+                    next_old += 1;
+                    continue;
+                }
+                let at = match items.get(next_old) {
+                    Some(slot) if self.source.shares_line(slot) => return false,
+                    Some(slot) => slot.line,
+                    None => items[items.len() - 1].end,
+                };
+                match self.renderer.render_item(value, indent) {
+                    Ok(lines) => inserts.push((at, lines)),
+                    Err(_) => return false,
+                }
+            }
+            if next_old != old.len() {
+                return false;
+            }
+            for (at, lines) in inserts {
+                self.edit(at, at, lines);
+            }
+            true
+        } else {
+            // Removals only: new must be a subsequence of old
+            let mut removed = Vec::new();
+            let mut next_new = 0;
+            for (slot, value) in items.iter().zip(old) {
+                if next_new < new.len() && &new[next_new] == value {
+                    next_new += 1;
+                } else if self.source.shares_line(slot) {
+                    return false;
+                } else {
+                    removed.push(slot);
+                }
+            }
+            if next_new != new.len() {
+                return false;
+            }
+            for slot in removed {
+                self.edit(slot.line, slot.end, Vec::new());
+            }
+            true
+        }
+    }
+
+    fn patch_slot(&mut self, slot: &Slot, old: &YamlValue, new: &YamlValue, is_entry: bool) {
+        let handled = match (&slot.value, old, new) {
+            (
+                Node::Mapping { indent, entries },
+                YamlValue::Mapping(old_map),
+                YamlValue::Mapping(new_map),
+            ) if !new_map.is_empty() => {
+                self.patch_mapping(entries, *indent, old_map, new_map, slot.end)
+            }
+            (
+                Node::Sequence { indent, items },
+                YamlValue::Sequence(old_seq),
+                YamlValue::Sequence(new_seq),
+            ) if !new_seq.is_empty() => self.patch_sequence(items, *indent, old_seq, new_seq),
+            _ => false,
+        };
+
+        if !handled {
+            self.replace_slot(slot, new, is_entry);
+        }
+    }
+
+    /// Rewrite the whole value of an entry or item, keeping the key text,
+    /// scalar quoting style and trailing comment where possible
+    fn replace_slot(&mut self, slot: &Slot, new: &YamlValue, is_entry: bool) {
+        let line = self.source.lines[slot.line];
+        let (old_text, comment) = split_comment(&line[slot.value_col..]);
+        let single_line = slot.end == slot.line + 1;
+        let old_inline = match slot.value {
+            Node::Inline if single_line => old_text.trim(),
+            _ => "",
+        };
+        let comment = if single_line || old_text.trim().is_empty() {
+            comment
+        } else {
+            ""
+        };
+
+        if let Some(text) = inline_text(old_inline, new) {
+            let rendered = format!("{} {}{}", &line[..slot.value_col], text, comment);
+            self.edit(slot.line, slot.end, vec![rendered]);
+            return;
+        }
+
+        let rendered = if is_entry {
+            let key = line[slot.col..slot.value_col - 1].trim_end();
+            self.renderer.render_entry(key, new, slot.col)
+        } else {
+            self.renderer.render_item(new, slot.col)
+        };
+
+        match rendered {
+            Ok(mut lines) => {
+                lines[0] = format!("{}{}{}", &line[..slot.col], &lines[0][slot.col..], comment);
+                self.edit(slot.line, slot.end, lines);
+            }
+            Err(_) => self.failed = true,
+        }
+    }
+
+    fn finish(self, newline: &str, trailing: bool) -> String {
+        let mut lines: Vec<String> = self.source.lines.iter().map(|l| l.to_string()).collect();
+
+        // Apply from the bottom up; edits at the same position are applied in
+        // reverse order so that earlier (nested) insertions end up first
+        let mut edits: Vec<(usize, Edit)> = self.edits.into_iter().enumerate().collect();
+        edits.sort_by(|(seq_a, a), (seq_b, b)| {
+            b.start
+                .cmp(&a.start)
+                .then(b.end.cmp(&a.end))
+                .then(seq_b.cmp(seq_a))
+        });
+        for (_, edit) in edits {
+            lines.splice(edit.start..edit.end, edit.lines);
+        }
+
+        let mut patched = lines.join(newline);
+        if trailing && !lines.is_empty() {
+            patched.push_str(newline);
+        }
+        patched
+    }
+}
+
+/// Render a replacement value on the key line, following the style of the old value
+///
+/// Returns `None` when the value needs block rendering.
+fn inline_text(old_text: &str, new: &YamlValue) -> Option<String> {
+    let flow = old_text.starts_with(['[', '{']);
+    match new {
+        YamlValue::Mapping(map) if !map.is_empty() => flow.then(|| render_flow(new).ok())?,
+        YamlValue::Sequence(seq) if !seq.is_empty() => flow.then(|| render_flow(new).ok())?,
+        YamlValue::String(s) if old_text.starts_with('"') => serde_json::to_string(s).ok(),
+        YamlValue::String(s) if old_text.starts_with('\'') && !s.contains(char::is_control) => {
+            Some(format!("'{}'", s.replace('\'', "''")))
+        }
+        _ => {
+            let lines = scalar_lines(new).ok()?;
+            match lines.len() {
+                1 => lines.into_iter().next(),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(source: &str) -> YamlValue {
+        serde_yaml::from_str(source).unwrap()
+    }
+
+    fn set(value: &mut YamlValue, key: &str, new: YamlValue) {
+        value
+            .as_mapping_mut()
+            .unwrap()
+            .insert(YamlValue::String(key.to_string()), new);
+    }
+
+    #[test]
+    fn test_unchanged_source_is_kept() {
+        let source = "# comment\nz: 1\na: 'quoted'\n";
+        let patched = YamlPatcher::patch(source, &yaml(source)).unwrap();
+        assert_eq!(patched, source);
+    }
+
+    #[test]
+    fn test_change_preserves_order_comments_and_quotes() {
+        let source = "# Post metadata\ntitle: \"Old\"  # shown in header\ndate: 2024-01-01\n\nauthor: 'Jane'\n";
+        let mut value = yaml(source);
+        set(
+            &mut value,
+            "title",
+            YamlValue::String("New \"one\"".to_string()),
+        );
+        set(
+            &mut value,
+            "author",
+            YamlValue::String("Jane's".to_string()),
+        );
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            patched,
+            "# Post metadata\ntitle: \"New \\\"one\\\"\"  # shown in header\ndate: 2024-01-01\n\nauthor: 'Jane''s'\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_and_add_keys() {
+        let source = "zeta: 1\ndraft: true\nalpha: 2\n";
+        let mut value = yaml(source);
+        value.as_mapping_mut().unwrap().remove("draft");
+        set(&mut value, "beta", YamlValue::Bool(false));
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(patched, "zeta: 1\nalpha: 2\nbeta: false\n");
+    }
+
+    #[test]
+    fn test_nested_mapping_edit() {
+        let source = "author:\n    name: John  # full name\n    email: j@example.com\ntitle: T\n";
+        let mut value = yaml(source);
+        let author = value.get_mut("author").unwrap().as_mapping_mut().unwrap();
+        author.insert("name".into(), "Jane".into());
+        author.insert("url".into(), "https://example.com".into());
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            patched,
+            "author:\n    name: Jane  # full name\n    email: j@example.com\n    url: https://example.com\ntitle: T\n"
+        );
+    }
+
+    #[test]
+    fn test_flow_sequence_keeps_flow_style() {
+        let source = "tags: [rust, cli] # topics\ntitle: T\n";
+        let mut value = yaml(source);
+        set(&mut value, "tags", yaml("[rust, cli, \"a, b\"]"));
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(patched, "tags: [rust, cli, \"a, b\"] # topics\ntitle: T\n");
+    }
+
+    #[test]
+    fn test_block_sequence_insert_and_remove() {
+        let source = "tags:\n  - rust # language\n  - cli\ntitle: T\n";
+        let mut value = yaml(source);
+        set(&mut value, "tags", yaml("[first, rust, cli, last]"));
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            patched,
+            "tags:\n  - first\n  - rust # language\n  - cli\n  - last\ntitle: T\n"
+        );
+
+        let mut value = yaml(source);
+        set(&mut value, "tags", yaml("[rust]"));
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(patched, "tags:\n  - rust # language\ntitle: T\n");
+    }
+
+    #[test]
+    fn test_sequence_of_mappings() {
+        let source = "authors:\n- name: A\n  role: writer\n- name: B\n";
+        let mut value = yaml(source);
+        let authors = value.get_mut("authors").unwrap().as_sequence_mut().unwrap();
+        authors[0]
+            .as_mapping_mut()
+            .unwrap()
+            .insert("role".into(), "editor".into());
+        authors[1]
+            .as_mapping_mut()
+            .unwrap()
+            .insert("email".into(), "b@example.com".into());
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(
+            patched,
+            "authors:\n- name: A\n  role: editor\n- name: B\n  email: b@example.com\n"
+        );
+    }
+
+    #[test]
+    fn test_block_value_replaced_by_scalar_and_back() {
+        let source = "meta:\n  a: 1\ntitle: T\n";
+        let mut value = yaml(source);
+        set(&mut value, "meta", YamlValue::Null);
+        set(&mut value, "title", yaml("{x: 1, y: [1, 2]}"));
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(patched, "meta: null\ntitle:\n  x: 1\n  y:\n  - 1\n  - 2\n");
+    }
+
+    #[test]
+    fn test_multiline_string() {
+        let source = "title: T\n";
+        let mut value = yaml(source);
+        set(
+            &mut value,
+            "body",
+            YamlValue::String("line 1\nline 2".to_string()),
+        );
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(yaml(&patched), value);
+        assert!(patched.starts_with("title: T\nbody: |-\n"));
+    }
+
+    #[test]
+    fn test_crlf_and_empty_source() {
+        let source = "a: 1\r\nb: 2\r\n";
+        let mut value = yaml(source);
+        set(&mut value, "b", YamlValue::Number(3.into()));
+        assert_eq!(
+            YamlPatcher::patch(source, &value).unwrap(),
+            "a: 1\r\nb: 3\r\n"
+        );
+
+        let patched = YamlPatcher::patch("", &yaml("title: New")).unwrap();
+        assert_eq!(patched, "title: New\n");
+    }
+
+    #[test]
+    fn test_unsupported_source_falls_back_to_ordered_render() {
+        // Anchors and aliases are not patched line-wise
+        let source = "base: &base\n  x: 1\nderived: *base\nz: 0\n";
+        let mut value = yaml(source);
+        set(&mut value, "z", YamlValue::Number(1.into()));
+
+        let patched = YamlPatcher::patch(source, &value).unwrap();
+        assert_eq!(yaml(&patched), value);
+        let keys: Vec<_> = yaml(&patched)
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(keys, vec!["base", "derived", "z"]);
+    }
+
+    #[test]
+    fn test_render_and_style() {
+        let value = yaml("b:\n  c: [1, 2]\na: x\n");
+        assert_eq!(
+            YamlPatcher::render(&value, YamlStyle::default()).unwrap(),
+            serde_yaml::to_string(&value).unwrap()
+        );
+
+        let style = YamlStyle {
+            indent: 4,
+            sequence_indent: 2,
+        };
+        assert_eq!(
+            YamlPatcher::render(&value, style).unwrap(),
+            "b:\n    c:\n      - 1\n      - 2\na: x\n"
+        );
+        assert_eq!(YamlStyle::detect("b:\n    c:\n      - 1\n"), style);
+    }
+
+    #[test]
+    fn test_order_like() {
+        let value = yaml("a: 1\nb: {y: 1, x: 2}\nc: 3\n");
+        let template = yaml("b: {x: 0, y: 0}\na: 0\n");
+        let ordered = YamlPatcher::order_like(&value, &template);
+        assert_eq!(
+            serde_yaml::to_string(&ordered).unwrap(),
+            "b:\n  x: 2\n  y: 1\na: 1\nc: 3\n"
+        );
+    }
+}
@@ -0,0 +1,512 @@
+//! Project configuration from `.matterof.toml`
+//!
+//! The configuration file is looked up from the working directory upward.
+//! Its top-level sections set defaults for every invocation, and
+//! `[profiles.NAME]` tables holding the same sections override them when the
+//! profile is selected:
+//!
+//! ```toml
+//! [files]
+//! ext = ["md", "markdown"]
+//! exclude = ["drafts"]
+//!
+//! [write]
+//! backup_dir = ".backups"
+//!
+//! [query]
+//! no_auto_root = true
+//!
+//! [profiles.hugo.files]
+//! exclude = ["themes", "public"]
+//! ```
+//!
+//! `MATTEROF_*` environment variables ([`Settings::from_env`]) are a layer
+//! above the file, so settings take effect in this order of precedence:
+//! command-line arguments, environment variables, configuration file,
+//! built-in defaults.
+
+use crate::error::{MatterOfError, Result};
+use crate::io::{LineEndings, ReaderConfig, ResolverConfig, WriterConfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the project configuration file
+pub const CONFIG_FILE: &str = ".matterof.toml";
+
+/// Which files batch commands pick up, and how many they process at once
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileSettings {
+    /// Only process files with these extensions
+    pub ext: Option<Vec<String>>,
+    /// Exclude files matching these patterns
+    pub exclude: Option<Vec<String>>,
+    /// Follow symbolic links when processing directories
+    pub follow_links: Option<bool>,
+    /// Include hidden files
+    pub include_hidden: Option<bool>,
+    /// Maximum depth for directory recursion
+    pub max_depth: Option<usize>,
+    /// Number of files processed in parallel
+    pub jobs: Option<usize>,
+}
+
+/// How files are read
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadSettings {
+    /// Largest file to read, in bytes
+    pub max_file_size: Option<usize>,
+}
+
+/// How files are written
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WriteSettings {
+    /// Create backup files with this suffix
+    pub backup_suffix: Option<String>,
+    /// Create backup files in this directory
+    pub backup_dir: Option<PathBuf>,
+    /// Write files in place instead of through a temporary file
+    pub no_atomic: Option<bool>,
+    /// Line ending style
+    pub line_endings: Option<LineEndings>,
+    /// Lines of context around each change in diffs
+    pub diff_context: Option<usize>,
+    /// Write batches all-or-nothing
+    pub transactional: Option<bool>,
+    /// Record runs in the undo journal
+    pub journal: Option<bool>,
+}
+
+/// How queries are interpreted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuerySettings {
+    /// Do not prepend `$` or `$.` to queries
+    pub no_auto_root: Option<bool>,
+}
+
+/// One layer of settings; unset values fall through to the layer below
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub files: FileSettings,
+    pub read: ReadSettings,
+    pub write: WriteSettings,
+    pub query: QuerySettings,
+}
+
+impl Settings {
+    /// Settings from `MATTEROF_*` environment variables
+    ///
+    /// Lists are comma-separated and booleans accept `1`/`0`, `true`/`false`,
+    /// `yes`/`no` and `on`/`off`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Settings from variables looked up by their `MATTEROF_*` name
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let list = |name: &str| {
+            var(name).map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        };
+        let flag = |name: &str| var(name).map(|value| parse_bool(name, &value)).transpose();
+        let number = |name: &str| {
+            var(name)
+                .map(|value| {
+                    value.trim().parse::<usize>().map_err(|_| {
+                        MatterOfError::validation(format!(
+                            "{} must be a number, got '{}'",
+                            name, value
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        let line_endings = var("MATTEROF_LINE_ENDINGS")
+            .map(|value| parse_line_endings(&value))
+            .transpose()?;
+
+        Ok(Self {
+            files: FileSettings {
+                ext: list("MATTEROF_EXT"),
+                exclude: list("MATTEROF_EXCLUDE"),
+                follow_links: flag("MATTEROF_FOLLOW_LINKS")?,
+                include_hidden: flag("MATTEROF_INCLUDE_HIDDEN")?,
+                max_depth: number("MATTEROF_MAX_DEPTH")?,
+                jobs: number("MATTEROF_JOBS")?,
+            },
+            read: ReadSettings {
+                max_file_size: number("MATTEROF_MAX_FILE_SIZE")?,
+            },
+            write: WriteSettings {
+                backup_suffix: var("MATTEROF_BACKUP_SUFFIX"),
+                backup_dir: var("MATTEROF_BACKUP_DIR").map(PathBuf::from),
+                no_atomic: flag("MATTEROF_NO_ATOMIC")?,
+                line_endings,
+                diff_context: number("MATTEROF_DIFF_CONTEXT")?,
+                transactional: flag("MATTEROF_TRANSACTIONAL")?,
+                journal: flag("MATTEROF_JOURNAL")?,
+            },
+            query: QuerySettings {
+                no_auto_root: flag("MATTEROF_NO_AUTO_ROOT")?,
+            },
+        })
+    }
+
+    /// Put `other` on top of these settings: its set values win
+    pub fn overlay(self, other: Settings) -> Settings {
+        Settings {
+            files: FileSettings {
+                ext: other.files.ext.or(self.files.ext),
+                exclude: other.files.exclude.or(self.files.exclude),
+                follow_links: other.files.follow_links.or(self.files.follow_links),
+                include_hidden: other.files.include_hidden.or(self.files.include_hidden),
+                max_depth: other.files.max_depth.or(self.files.max_depth),
+                jobs: other.files.jobs.or(self.files.jobs),
+            },
+            read: ReadSettings {
+                max_file_size: other.read.max_file_size.or(self.read.max_file_size),
+            },
+            write: WriteSettings {
+                backup_suffix: other.write.backup_suffix.or(self.write.backup_suffix),
+                backup_dir: other.write.backup_dir.or(self.write.backup_dir),
+                no_atomic: other.write.no_atomic.or(self.write.no_atomic),
+                line_endings: other.write.line_endings.or(self.write.line_endings),
+                diff_context: other.write.diff_context.or(self.write.diff_context),
+                transactional: other.write.transactional.or(self.write.transactional),
+                journal: other.write.journal.or(self.write.journal),
+            },
+            query: QuerySettings {
+                no_auto_root: other.query.no_auto_root.or(self.query.no_auto_root),
+            },
+        }
+    }
+}
+
+impl FileSettings {
+    /// Fill a resolver configuration with the values set here
+    ///
+    /// `jobs` concerns how the files are processed and is left to the caller.
+    pub fn apply(&self, config: &mut ResolverConfig) {
+        if let Some(ext) = &self.ext {
+            config.include_extensions = ext.clone();
+        }
+        if let Some(exclude) = &self.exclude {
+            config.exclude_patterns = exclude.clone();
+        }
+        if let Some(follow_links) = self.follow_links {
+            config.follow_links = follow_links;
+        }
+        if let Some(include_hidden) = self.include_hidden {
+            config.include_hidden = include_hidden;
+        }
+        if self.max_depth.is_some() {
+            config.max_depth = self.max_depth;
+        }
+    }
+}
+
+impl ReadSettings {
+    /// Fill a reader configuration with the values set here
+    pub fn apply(&self, config: &mut ReaderConfig) {
+        if self.max_file_size.is_some() {
+            config.max_file_size = self.max_file_size;
+        }
+    }
+}
+
+impl WriteSettings {
+    /// Fill a writer configuration with the values set here
+    ///
+    /// `transactional` and `journal` concern how a batch is run rather
+    /// than the writer and are left to the caller.
+    pub fn apply(&self, config: &mut WriterConfig) {
+        if self.backup_suffix.is_some() || self.backup_dir.is_some() {
+            config.backup_enabled = true;
+        }
+        if self.backup_suffix.is_some() {
+            config.backup_suffix = self.backup_suffix.clone();
+        }
+        if self.backup_dir.is_some() {
+            config.backup_dir = self.backup_dir.clone();
+        }
+        if let Some(no_atomic) = self.no_atomic {
+            config.atomic_writes = !no_atomic;
+        }
+        if let Some(line_endings) = self.line_endings {
+            config.line_endings = line_endings;
+        }
+        if let Some(diff_context) = self.diff_context {
+            config.diff_context = diff_context;
+        }
+    }
+}
+
+/// Contents of a `.matterof.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    files: FileSettings,
+    read: ReadSettings,
+    write: WriteSettings,
+    query: QuerySettings,
+    profiles: BTreeMap<String, Settings>,
+}
+
+/// A loaded project configuration file
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    path: PathBuf,
+    settings: Settings,
+    profiles: BTreeMap<String, Settings>,
+}
+
+impl ProjectConfig {
+    /// Find and load the nearest `.matterof.toml` in `start` or its ancestors
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        match start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::load(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Load a configuration file
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => MatterOfError::file_not_found(&path),
+            _ => MatterOfError::Io(e),
+        })?;
+        let file: ConfigFile = toml_edit::de::from_str(&content).map_err(|e| {
+            MatterOfError::validation(format!("Invalid config {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self {
+            path,
+            settings: Settings {
+                files: file.files,
+                read: file.read,
+                write: file.write,
+                query: file.query,
+            },
+            profiles: file.profiles,
+        })
+    }
+
+    /// Path the configuration was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of the defined profiles
+    pub fn profiles(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Settings of the file, with a profile's settings on top if one is given
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings> {
+        let Some(name) = profile else {
+            return Ok(self.settings.clone());
+        };
+        let overrides = self.profiles.get(name).ok_or_else(|| {
+            let names: Vec<_> = self.profiles().collect();
+            MatterOfError::validation(format!(
+                "Unknown profile '{}' in {} (available: {})",
+                name,
+                self.path.display(),
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ))
+        })?;
+        Ok(self.settings.clone().overlay(overrides.clone()))
+    }
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" | "" => Ok(false),
+        _ => Err(MatterOfError::validation(format!(
+            "{} must be a boolean, got '{}'",
+            name, value
+        ))),
+    }
+}
+
+fn parse_line_endings(value: &str) -> Result<LineEndings> {
+    match value.trim().to_lowercase().as_str() {
+        "unix" => Ok(LineEndings::Unix),
+        "windows" => Ok(LineEndings::Windows),
+        "preserve" => Ok(LineEndings::Preserve),
+        _ => Err(MatterOfError::validation(format!(
+            "MATTEROF_LINE_ENDINGS must be unix, windows or preserve, got '{}'",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::FileResolver;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"
+[files]
+ext = ["md", "mdx"]
+exclude = ["drafts"]
+
+[write]
+backup_dir = ".backups"
+line_endings = "unix"
+
+[profiles.hugo.files]
+exclude = ["themes", "public"]
+
+[profiles.hugo.query]
+no_auto_root = true
+"#;
+
+    #[test]
+    fn test_discover_and_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(CONFIG_FILE), CONFIG).unwrap();
+        let nested = temp_dir.path().join("content/posts");
+        fs::create_dir_all(&nested).unwrap();
+
+        let config = ProjectConfig::discover(&nested).unwrap().unwrap();
+        assert_eq!(config.path(), temp_dir.path().join(CONFIG_FILE));
+
+        let base = config.settings(None).unwrap();
+        assert_eq!(base.files.exclude, Some(vec!["drafts".to_string()]));
+        assert_eq!(base.write.line_endings, Some(LineEndings::Unix));
+        assert_eq!(base.query.no_auto_root, None);
+
+        // Profiles override only what they set
+        let hugo = config.settings(Some("hugo")).unwrap();
+        assert_eq!(
+            hugo.files.exclude,
+            Some(vec!["themes".to_string(), "public".to_string()])
+        );
+        assert_eq!(hugo.files.ext, base.files.ext);
+        assert_eq!(hugo.query.no_auto_root, Some(true));
+
+        let error = config.settings(Some("jekyll")).unwrap_err();
+        assert!(error.to_string().contains("available: hugo"));
+    }
+
+    #[test]
+    fn test_config_excludes_apply_to_resolver() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(CONFIG_FILE), CONFIG).unwrap();
+        for file in [
+            "post.md",
+            "drafts/wip.md",
+            "themes/a/readme.md",
+            "public/x.md",
+        ] {
+            let path = temp_dir.path().join("content").join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "# Post\n").unwrap();
+        }
+
+        let config = ProjectConfig::discover(temp_dir.path()).unwrap().unwrap();
+        let resolve = |profile| {
+            let mut resolver = ResolverConfig::default();
+            config.settings(profile).unwrap().files.apply(&mut resolver);
+            let content = temp_dir.path().join("content");
+            FileResolver::with_config(resolver)
+                .resolve_paths(&[&content])
+                .unwrap()
+                .into_iter()
+                .map(|file| file.path.strip_prefix(&content).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            resolve(None),
+            vec![
+                PathBuf::from("post.md"),
+                PathBuf::from("public/x.md"),
+                PathBuf::from("themes/a/readme.md")
+            ]
+        );
+        assert_eq!(
+            resolve(Some("hugo")),
+            vec![PathBuf::from("drafts/wip.md"), PathBuf::from("post.md")]
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(CONFIG_FILE);
+        fs::write(&path, "[files]\nextensions = [\"md\"]\n").unwrap();
+        assert!(ProjectConfig::load(&path).is_err());
+        assert!(ProjectConfig::discover(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_env_layer_and_apply() {
+        let vars: HashMap<&str, &str> = [
+            ("MATTEROF_EXT", "md, markdown"),
+            ("MATTEROF_NO_ATOMIC", "yes"),
+            ("MATTEROF_MAX_FILE_SIZE", "1024"),
+            ("MATTEROF_NO_AUTO_ROOT", "0"),
+        ]
+        .into_iter()
+        .collect();
+        let env = Settings::from_vars(|name| vars.get(name).map(|v| v.to_string())).unwrap();
+
+        let file = Settings {
+            files: FileSettings {
+                ext: Some(vec!["mdx".to_string()]),
+                include_hidden: Some(true),
+                ..Default::default()
+            },
+            query: QuerySettings {
+                no_auto_root: Some(true),
+            },
+            ..Default::default()
+        };
+        let settings = file.overlay(env);
+        assert_eq!(
+            settings.files.ext,
+            Some(vec!["md".to_string(), "markdown".to_string()])
+        );
+        assert_eq!(settings.files.include_hidden, Some(true));
+        assert_eq!(settings.query.no_auto_root, Some(false));
+
+        let mut resolver = ResolverConfig::default();
+        settings.files.apply(&mut resolver);
+        assert!(resolver.include_hidden);
+        let mut reader = ReaderConfig::default();
+        settings.read.apply(&mut reader);
+        assert_eq!(reader.max_file_size, Some(1024));
+        let mut writer = WriterConfig::default();
+        settings.write.apply(&mut writer);
+        assert!(!writer.atomic_writes);
+        assert!(!writer.backup_enabled);
+
+        assert!(Settings::from_vars(|_| Some("maybe".to_string())).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! Persistent index of parsed front matter
+//!
+//! The index lives in `.matterof/index` next to the journal and maps each
+//! file, by its path relative to the project root, to its modification time,
+//! size, a SHA-256 hash of its content and its parsed front matter as JSON.
+//! A file whose time and size match its entry is served from the index; one
+//! that changed is hashed, and re-parsed only when its content differs.
+
+use crate::core::{Document, FrontMatterValue};
+use crate::error::{MatterOfError, Result};
+use crate::io::journal::{content_hash, STATE_DIR};
+use crate::io::FrontMatterReader;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tempfile::NamedTempFile;
+
+/// Name of the index file inside the `.matterof` directory
+pub const INDEX_FILE: &str = "index";
+
+/// Format version of the index file; other versions are rebuilt from scratch
+const INDEX_VERSION: u32 = 1;
+
+/// What the index knows about one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Modification time, in nanoseconds since the Unix epoch
+    pub mtime_ns: u64,
+    /// Size in bytes
+    pub size: u64,
+    /// SHA-256 hash of the content
+    pub hash: String,
+    /// Parsed front matter
+    pub front_matter: Option<IndexMap<String, FrontMatterValue>>,
+}
+
+impl IndexEntry {
+    /// Read and parse a file
+    pub fn from_file(reader: &FrontMatterReader, path: &Path) -> Result<Self> {
+        let (mtime_ns, size) = file_stamp(path)?;
+        let content = read_content(path)?;
+        let document = reader.parse_content(&content, Some(path))?;
+        Ok(Self {
+            mtime_ns,
+            size,
+            hash: content_hash(&content),
+            front_matter: document.front_matter().cloned(),
+        })
+    }
+
+    /// The indexed front matter as a document with an empty body
+    pub fn document(&self) -> Document {
+        Document::new(self.front_matter.clone(), String::new())
+    }
+}
+
+/// How an indexed file relates to its entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// Time and size match the entry
+    Fresh,
+    /// The file changed since it was indexed
+    Stale,
+    /// The file is not in the index
+    Missing,
+}
+
+/// On-disk layout of the index file
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// The front matter index of a project
+#[derive(Debug, Clone)]
+pub struct FrontMatterIndex {
+    path: PathBuf,
+    root: PathBuf,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+impl FrontMatterIndex {
+    /// Create an empty index stored at `path`, for files below `root`
+    pub fn new(path: impl Into<PathBuf>, root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            path: path.into(),
+            root: root.canonicalize().unwrap_or(root),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Find the index for `start`: in the nearest ancestor holding a
+    /// `.matterof` directory, or in `start` itself if there is none
+    ///
+    /// Nothing is read; see [`FrontMatterIndex::load`].
+    pub fn discover(start: &Path) -> Self {
+        let root = start
+            .ancestors()
+            .find(|dir| dir.join(STATE_DIR).is_dir())
+            .unwrap_or(start);
+        Self::new(root.join(STATE_DIR).join(INDEX_FILE), root)
+    }
+
+    /// Read the entries of the index file, if it exists
+    ///
+    /// An index written by another version of matterof is discarded.
+    pub fn load(mut self) -> Result<Self> {
+        if !self.exists() {
+            return Ok(self);
+        }
+
+        let content = fs::read_to_string(&self.path).map_err(MatterOfError::Io)?;
+        let file: IndexFile = serde_json::from_str(&content).map_err(|e| {
+            MatterOfError::validation(format!("Invalid index {}: {}", self.path.display(), e))
+        })?;
+        if file.version == INDEX_VERSION {
+            self.entries = file.entries;
+        }
+        Ok(self)
+    }
+
+    /// Atomically write the index file
+    pub fn save(&self) -> Result<()> {
+        let dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(dir).map_err(MatterOfError::Io)?;
+
+        let file = IndexFile {
+            version: INDEX_VERSION,
+            entries: self.entries.clone(),
+        };
+        let json = serde_json::to_string(&file)
+            .map_err(|e| MatterOfError::validation(format!("Cannot serialize index: {}", e)))?;
+
+        let mut temp = NamedTempFile::new_in(dir).map_err(MatterOfError::Io)?;
+        temp.write_all(json.as_bytes()).map_err(MatterOfError::Io)?;
+        temp.persist(&self.path)
+            .map_err(|e| MatterOfError::Io(e.error))?;
+        Ok(())
+    }
+
+    /// Path of the index file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the index file exists
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    /// Number of indexed files
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no file is indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Paths of all indexed files
+    pub fn files(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.entries.keys().map(|key| self.root.join(key))
+    }
+
+    /// The entry of a file
+    pub fn get(&self, file: &Path) -> Option<&IndexEntry> {
+        self.entries.get(&self.key(file))
+    }
+
+    /// Add or replace the entry of a file
+    pub fn insert(&mut self, file: &Path, entry: IndexEntry) {
+        let key = self.key(file);
+        self.entries.insert(key, entry);
+    }
+
+    /// Remove the entry of a file
+    pub fn remove(&mut self, file: &Path) -> Option<IndexEntry> {
+        self.entries.remove(&self.key(file))
+    }
+
+    /// Whether a file changed since it was indexed, judged by time and size
+    pub fn status(&self, file: &Path) -> Result<EntryStatus> {
+        let Some(entry) = self.get(file) else {
+            return Ok(EntryStatus::Missing);
+        };
+        let (mtime_ns, size) = file_stamp(file)?;
+        if entry.mtime_ns == mtime_ns && entry.size == size {
+            Ok(EntryStatus::Fresh)
+        } else {
+            Ok(EntryStatus::Stale)
+        }
+    }
+
+    /// Front matter of a file, from the index while it is fresh
+    ///
+    /// Returns the document with an empty body, and the entry to store when
+    /// the index needs updating. A changed file whose content hash still
+    /// matches only gets a new time and size; it is not parsed again.
+    pub fn read(
+        &self,
+        reader: &FrontMatterReader,
+        file: &Path,
+    ) -> Result<(Document, Option<IndexEntry>)> {
+        let entry = self.get(file);
+        let (mtime_ns, size) = file_stamp(file)?;
+        if let Some(entry) = entry {
+            if entry.mtime_ns == mtime_ns && entry.size == size {
+                return Ok((entry.document(), None));
+            }
+        }
+
+        let content = read_content(file)?;
+        let hash = content_hash(&content);
+        let front_matter = match entry {
+            Some(entry) if entry.hash == hash => entry.front_matter.clone(),
+            _ => reader
+                .parse_content(&content, Some(file))?
+                .front_matter()
+                .cloned(),
+        };
+        let entry = IndexEntry {
+            mtime_ns,
+            size,
+            hash,
+            front_matter,
+        };
+        Ok((entry.document(), Some(entry)))
+    }
+
+    /// Drop the entries of files that no longer exist, returning their paths
+    pub fn remove_deleted(&mut self) -> Vec<PathBuf> {
+        let deleted: Vec<_> = self
+            .entries
+            .keys()
+            .filter(|key| !self.root.join(key).is_file())
+            .cloned()
+            .collect();
+        for key in &deleted {
+            self.entries.remove(key);
+        }
+        deleted.iter().map(|key| self.root.join(key)).collect()
+    }
+
+    /// Key of a file: its path relative to the project root, if below it
+    pub fn key(&self, file: &Path) -> String {
+        let absolute = file.canonicalize().unwrap_or_else(|_| self.root.join(file));
+        let relative = absolute.strip_prefix(&self.root).unwrap_or(&absolute);
+        relative.to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// Modification time (nanoseconds since the epoch) and size of a file
+fn file_stamp(path: &Path) -> Result<(u64, u64)> {
+    let metadata = fs::metadata(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => MatterOfError::file_not_found(path),
+        _ => MatterOfError::Io(e),
+    })?;
+    let mtime_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as u64);
+    Ok((mtime_ns, metadata.len()))
+}
+
+/// Content of a file; bytes that are not UTF-8 (in a binary body, say) are replaced
+fn read_content(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => MatterOfError::permission_denied(path),
+        _ => MatterOfError::Io(e),
+    })?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::KeyPath;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn set_mtime(path: &Path, time: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_index_read_and_refresh() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join(STATE_DIR)).unwrap();
+        let post = temp_dir.path().join("post.md");
+        fs::write(&post, "---\ntitle: One\n---\nBody\n").unwrap();
+
+        let reader = FrontMatterReader::new();
+        let title = KeyPath::parse("title").unwrap();
+        let mut index = FrontMatterIndex::discover(&temp_dir.path().join("content"));
+        assert_eq!(
+            index.path(),
+            temp_dir.path().join(STATE_DIR).join(INDEX_FILE)
+        );
+        assert_eq!(index.status(&post).unwrap(), EntryStatus::Missing);
+
+        index.insert(&post, IndexEntry::from_file(&reader, &post).unwrap());
+        index.save().unwrap();
+        let index = FrontMatterIndex::discover(temp_dir.path()).load().unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.status(&post).unwrap(), EntryStatus::Fresh);
+        let (document, update) = index.read(&reader, &post).unwrap();
+        assert_eq!(document.get(&title).unwrap().as_string(), Some("One"));
+        assert!(update.is_none());
+
+        // Touched but unchanged: new stamp, same front matter
+        set_mtime(&post, SystemTime::now() + Duration::from_secs(60));
+        assert_eq!(index.status(&post).unwrap(), EntryStatus::Stale);
+        let (_, update) = index.read(&reader, &post).unwrap();
+        let update = update.unwrap();
+        assert_eq!(update.hash, index.get(&post).unwrap().hash);
+
+        // Changed content is parsed again
+        fs::write(&post, "---\ntitle: Two\n---\nBody\n").unwrap();
+        let (document, update) = index.read(&reader, &post).unwrap();
+        assert_eq!(document.get(&title).unwrap().as_string(), Some("Two"));
+        assert_ne!(update.unwrap().hash, index.get(&post).unwrap().hash);
+
+        let mut index = index;
+        fs::remove_file(&post).unwrap();
+        assert_eq!(index.remove_deleted().len(), 1);
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,527 @@
+//! Undo journal for modification runs
+//!
+//! Every run that modifies files can be recorded in a journal under
+//! `.matterof/journal/`, one JSON Lines file per run. The first line describes
+//! the run (id, time, command line, working directory), followed by one line
+//! per written file holding its original content and a SHA-256 hash of the
+//! new content. A run is restored as a whole with [`Journal::undo`], which
+//! refuses to overwrite files that changed since the run unless forced.
+
+use crate::error::{MatterOfError, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+
+/// Directory holding matterof state, relative to the project root
+pub const STATE_DIR: &str = ".matterof";
+
+/// One line of a journal file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JournalLine {
+    /// Header describing the run
+    Run {
+        id: String,
+        timestamp: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+    },
+    /// A file written by the run
+    File {
+        path: PathBuf,
+        original: Option<String>,
+        new_hash: String,
+    },
+    /// Marker appended when the run has been undone
+    Undone { timestamp: String },
+}
+
+/// A file written by a recorded run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalFile {
+    /// Absolute path of the written file
+    pub path: PathBuf,
+    /// Content before the run (`None` if the run created the file)
+    pub original: Option<String>,
+    /// SHA-256 hash of the content the run wrote
+    pub new_hash: String,
+}
+
+impl JournalFile {
+    /// Whether the file still holds the content written by the run
+    pub fn is_unchanged(&self) -> bool {
+        fs::read_to_string(&self.path)
+            .map(|current| content_hash(&current) == self.new_hash)
+            .unwrap_or(false)
+    }
+}
+
+/// A recorded modification run
+#[derive(Debug, Clone)]
+pub struct JournalRun {
+    /// Run identifier (sortable by time)
+    pub id: String,
+    /// RFC 3339 time the run started writing
+    pub timestamp: String,
+    /// Command line of the run
+    pub command: Vec<String>,
+    /// Working directory of the run
+    pub cwd: PathBuf,
+    /// Files written, in write order
+    pub files: Vec<JournalFile>,
+    /// RFC 3339 time the run was undone, if it was
+    pub undone_at: Option<String>,
+}
+
+impl JournalRun {
+    /// Whether the run has been undone
+    pub fn is_undone(&self) -> bool {
+        self.undone_at.is_some()
+    }
+
+    /// One entry per distinct file, with the content from before the run and
+    /// the hash of the last content the run wrote
+    fn net_files(&self) -> Vec<JournalFile> {
+        let mut files: Vec<JournalFile> = Vec::new();
+        let mut positions = HashMap::new();
+        for file in &self.files {
+            match positions.get(&file.path) {
+                Some(&i) => {
+                    let entry: &mut JournalFile = &mut files[i];
+                    entry.new_hash = file.new_hash.clone();
+                }
+                None => {
+                    positions.insert(file.path.clone(), files.len());
+                    files.push(file.clone());
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Options for [`Journal::undo`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UndoOptions {
+    /// Restore files even if they changed since the run
+    pub force: bool,
+    /// Only report what would be restored
+    pub dry_run: bool,
+}
+
+/// Result of undoing a run
+#[derive(Debug, Default)]
+pub struct UndoReport {
+    /// Files restored to their original content
+    pub restored: Vec<PathBuf>,
+    /// Files created by the run and removed again
+    pub removed: Vec<PathBuf>,
+    /// Files that changed since the run (overwritten only when forced)
+    pub conflicts: Vec<PathBuf>,
+}
+
+/// The journal directory of a project
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+impl Journal {
+    /// Use the given journal directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Find the journal for `start`: the nearest ancestor holding a
+    /// `.matterof` directory, or `start` itself if there is none
+    pub fn discover(start: &Path) -> Self {
+        let root = start
+            .ancestors()
+            .find(|dir| dir.join(STATE_DIR).is_dir())
+            .unwrap_or(start);
+        Self::new(root.join(STATE_DIR).join("journal"))
+    }
+
+    /// Journal directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Start recording a new run
+    ///
+    /// Nothing is written to disk until the first file is recorded.
+    pub fn start_run(&self, command: Vec<String>) -> Result<JournalRecorder> {
+        let now = Utc::now();
+        let mut id = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+        let mut suffix = 1;
+        while self.run_path(&id).exists() {
+            id = format!("{}-{}", now.format("%Y%m%d-%H%M%S-%3f"), suffix);
+            suffix += 1;
+        }
+
+        Ok(JournalRecorder {
+            path: self.run_path(&id),
+            header: JournalLine::Run {
+                id: id.clone(),
+                timestamp: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+                command,
+                cwd: std::env::current_dir().map_err(MatterOfError::Io)?,
+            },
+            id,
+            state: Mutex::new(RecorderState::default()),
+        })
+    }
+
+    /// All recorded runs, oldest first
+    pub fn runs(&self) -> Result<Vec<JournalRun>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .map_err(MatterOfError::Io)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        paths.sort_by(|a, b| a.file_stem().cmp(&b.file_stem()));
+
+        paths.iter().map(|path| Self::read_run(path)).collect()
+    }
+
+    /// Look up a run by id
+    pub fn run(&self, id: &str) -> Result<JournalRun> {
+        let path = self.run_path(id);
+        if !path.exists() {
+            return Err(MatterOfError::validation(format!(
+                "No run {} in journal {}",
+                id,
+                self.dir.display()
+            )));
+        }
+        Self::read_run(&path)
+    }
+
+    /// Most recent run that has not been undone
+    pub fn latest(&self) -> Result<Option<JournalRun>> {
+        Ok(self.runs()?.into_iter().rev().find(|run| !run.is_undone()))
+    }
+
+    /// Restore every file of a run to its content from before the run
+    ///
+    /// Files that no longer hold what the run wrote are conflicts; unless
+    /// forced, any conflict aborts the undo before a single file is touched.
+    pub fn undo(&self, run: &JournalRun, options: UndoOptions) -> Result<UndoReport> {
+        if run.is_undone() && !options.force {
+            return Err(MatterOfError::validation(format!(
+                "Run {} was already undone",
+                run.id
+            )));
+        }
+
+        let files = run.net_files();
+        let mut report = UndoReport {
+            conflicts: files
+                .iter()
+                .filter(|file| !file.is_unchanged())
+                .map(|file| file.path.clone())
+                .collect(),
+            ..Default::default()
+        };
+
+        if !report.conflicts.is_empty() && !options.force {
+            let paths: Vec<_> = report
+                .conflicts
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(MatterOfError::validation(format!(
+                "{} file(s) changed since run {}: {} (use --force to restore anyway)",
+                paths.len(),
+                run.id,
+                paths.join(", ")
+            )));
+        }
+
+        for file in files {
+            match &file.original {
+                Some(original) => {
+                    if !options.dry_run {
+                        restore_file(&file.path, original)?;
+                    }
+                    report.restored.push(file.path);
+                }
+                None => {
+                    if !options.dry_run && file.path.exists() {
+                        fs::remove_file(&file.path).map_err(MatterOfError::Io)?;
+                    }
+                    report.removed.push(file.path);
+                }
+            }
+        }
+
+        if !options.dry_run {
+            let mut journal = OpenOptions::new()
+                .append(true)
+                .open(self.run_path(&run.id))
+                .map_err(MatterOfError::Io)?;
+            write_line(
+                &mut journal,
+                &JournalLine::Undone {
+                    timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                },
+            )?;
+        }
+
+        Ok(report)
+    }
+
+    fn run_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", id))
+    }
+
+    fn read_run(path: &Path) -> Result<JournalRun> {
+        let invalid = |reason: String| {
+            MatterOfError::validation(format!("Invalid journal {}: {}", path.display(), reason))
+        };
+        let reader = BufReader::new(File::open(path).map_err(MatterOfError::Io)?);
+
+        let mut run: Option<JournalRun> = None;
+        for line in reader.lines() {
+            let line = line.map_err(MatterOfError::Io)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalLine =
+                serde_json::from_str(&line).map_err(|e| invalid(e.to_string()))?;
+
+            match (entry, run.as_mut()) {
+                (
+                    JournalLine::Run {
+                        id,
+                        timestamp,
+                        command,
+                        cwd,
+                    },
+                    None,
+                ) => {
+                    run = Some(JournalRun {
+                        id,
+                        timestamp,
+                        command,
+                        cwd,
+                        files: Vec::new(),
+                        undone_at: None,
+                    })
+                }
+                (
+                    JournalLine::File {
+                        path,
+                        original,
+                        new_hash,
+                    },
+                    Some(run),
+                ) => run.files.push(JournalFile {
+                    path,
+                    original,
+                    new_hash,
+                }),
+                (JournalLine::Undone { timestamp }, Some(run)) => run.undone_at = Some(timestamp),
+                _ => return Err(invalid("unexpected entry order".to_string())),
+            }
+        }
+
+        run.ok_or_else(|| invalid("missing run header".to_string()))
+    }
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    file: Option<File>,
+    recorded: usize,
+}
+
+/// Records the files written by one run
+///
+/// Entries are appended as they happen, so a run that fails halfway can
+/// still be undone up to the point of failure.
+#[derive(Debug)]
+pub struct JournalRecorder {
+    id: String,
+    path: PathBuf,
+    header: JournalLine,
+    state: Mutex<RecorderState>,
+}
+
+impl JournalRecorder {
+    /// Identifier of the run
+    pub fn run_id(&self) -> &str {
+        &self.id
+    }
+
+    /// Number of files recorded so far
+    pub fn recorded_count(&self) -> usize {
+        self.state.lock().map(|state| state.recorded).unwrap_or(0)
+    }
+
+    /// Record that `path` was written with `new_content`, replacing `original`
+    pub fn record(&self, path: &Path, original: Option<&str>, new_content: &str) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| MatterOfError::validation("Journal lock poisoned"))?;
+
+        if state.file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir).map_err(MatterOfError::Io)?;
+            }
+            let mut file = OpenOptions::new()
+                .create_new(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(MatterOfError::Io)?;
+            write_line(&mut file, &self.header)?;
+            state.file = Some(file);
+        }
+
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let line = JournalLine::File {
+            path,
+            original: original.map(str::to_string),
+            new_hash: content_hash(new_content),
+        };
+        write_line(state.file.as_mut().expect("journal file is open"), &line)?;
+        state.recorded += 1;
+        Ok(())
+    }
+}
+
+/// SHA-256 hash of file content, as lowercase hex
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn write_line(file: &mut File, line: &JournalLine) -> Result<()> {
+    let json = serde_json::to_string(line)
+        .map_err(|e| MatterOfError::validation(format!("Cannot serialize journal: {}", e)))?;
+    writeln!(file, "{}", json).map_err(MatterOfError::Io)?;
+    file.flush().map_err(MatterOfError::Io)
+}
+
+/// Atomically replace the content of a file
+fn restore_file(path: &Path, content: &str) -> Result<()> {
+    let parent_dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent_dir).map_err(MatterOfError::Io)?;
+
+    let mut temp_file = NamedTempFile::new_in(parent_dir).map_err(MatterOfError::Io)?;
+    temp_file
+        .write_all(content.as_bytes())
+        .map_err(MatterOfError::Io)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp_file.path(), metadata.permissions()).map_err(MatterOfError::Io)?;
+    }
+    temp_file.persist(path).map_err(|e| {
+        MatterOfError::Io(std::io::Error::other(format!(
+            "Failed to restore {}: {}",
+            path.display(),
+            e
+        )))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record_run(journal: &Journal, writes: &[(&Path, Option<&str>, &str)]) -> String {
+        let recorder = journal.start_run(vec!["matterof".to_string()]).unwrap();
+        for (path, original, new_content) in writes {
+            fs::write(path, new_content).unwrap();
+            recorder.record(path, *original, new_content).unwrap();
+        }
+        recorder.run_id().to_string()
+    }
+
+    #[test]
+    fn test_record_and_undo() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::discover(temp_dir.path());
+        let edited = temp_dir.path().join("edited.md");
+        let created = temp_dir.path().join("created.md");
+        fs::write(&edited, "before").unwrap();
+
+        // A recorder that writes nothing leaves no journal behind
+        journal.start_run(Vec::new()).unwrap();
+        assert!(journal.runs().unwrap().is_empty());
+
+        let id = record_run(
+            &journal,
+            &[
+                (&edited, Some("before"), "middle"),
+                (&created, None, "new"),
+                (&edited, Some("middle"), "after"),
+            ],
+        );
+
+        let run = journal.latest().unwrap().unwrap();
+        assert_eq!(run.id, id);
+        assert_eq!(run.files.len(), 3);
+
+        let report = journal.undo(&run, UndoOptions::default()).unwrap();
+        assert_eq!(report.restored.len(), 1);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "before");
+        assert!(!created.exists());
+
+        assert!(journal.run(&id).unwrap().is_undone());
+        assert!(journal.latest().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_undo_refuses_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = Journal::discover(temp_dir.path());
+        let file = temp_dir.path().join("post.md");
+        fs::write(&file, "before").unwrap();
+
+        record_run(&journal, &[(&file, Some("before"), "after")]);
+        fs::write(&file, "edited by hand").unwrap();
+
+        let run = journal.latest().unwrap().unwrap();
+        let err = journal.undo(&run, UndoOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("changed since run"));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "edited by hand");
+
+        let options = UndoOptions {
+            force: true,
+            dry_run: false,
+        };
+        let report = journal.undo(&run, options).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "before");
+    }
+
+    #[test]
+    fn test_discover_uses_nearest_state_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("content/posts");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(temp_dir.path().join(STATE_DIR)).unwrap();
+
+        let journal = Journal::discover(&nested);
+        assert_eq!(
+            journal.dir(),
+            temp_dir.path().join(STATE_DIR).join("journal")
+        );
+    }
+}
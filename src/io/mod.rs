@@ -2,18 +2,36 @@
 //!
 //! This module provides the complete IO stack for working with front matter files:
 //! - Reader: Efficient file reading and front matter parsing
-//! - Writer: Safe file writing with atomic operations and backup support
+//! - Writer: Safe file writing with atomic operations, backup support and
+//!   all-or-nothing transactions
+//! - Journal: Undo journal of modification runs under `.matterof/journal/`
+//! - Index: Persistent front matter index under `.matterof/index`
 //! - Resolver: File path resolution and filtering for batch operations
+//! - Parallel: Ordered multi-threaded execution of per-file work
+//! - Watch: Debounced notifications of changes to resolved files
+//! - Config: Project defaults and profiles from `.matterof.toml` and `MATTEROF_*` variables
 
+pub mod config;
+pub mod index;
+pub mod journal;
+pub mod parallel;
 pub mod reader;
 pub mod resolver;
+pub mod watch;
 pub mod writer;
 
+pub use config::{
+    FileSettings, ProjectConfig, QuerySettings, ReadSettings, Settings, WriteSettings, CONFIG_FILE,
+};
+pub use index::{EntryStatus, FrontMatterIndex, IndexEntry, INDEX_FILE};
+pub use journal::{Journal, JournalFile, JournalRecorder, JournalRun, UndoOptions, UndoReport};
+pub use parallel::{collect_results, ParallelExecutor};
 pub use reader::{FrontMatterReader, ReaderConfig};
 pub use resolver::{FileResolver, ResolvedFile, ResolverConfig};
+pub use watch::{FileWatcher, WatchBatch, DEFAULT_DEBOUNCE};
 pub use writer::{
-    BackupOptions, FrontMatterWriter, LineEndings, OutputOptions, WriteOptions, WriteResult,
-    WriterConfig,
+    BackupOptions, BatchReport, FrontMatterWriter, LineEndings, OutputOptions, Transaction,
+    WriteOptions, WriteResult, WriterConfig,
 };
 
 /// Re-export convenience functions for easy access
@@ -0,0 +1,173 @@
+//! Parallel execution of per-file work for batch operations
+//!
+//! Files are handed out to a fixed number of worker threads one at a time, so
+//! a few large files do not hold up the rest. Results always come back in the
+//! order of the input, which keeps the output of batch commands identical to a
+//! sequential run.
+
+use crate::error::{MatterOfError, Result};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Runs a function over many items on a pool of worker threads
+#[derive(Debug, Clone, Copy)]
+pub struct ParallelExecutor {
+    jobs: usize,
+}
+
+impl Default for ParallelExecutor {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl ParallelExecutor {
+    /// Create an executor running `jobs` threads, or one per CPU when `None`
+    pub fn new(jobs: Option<usize>) -> Self {
+        let jobs = jobs
+            .filter(|&jobs| jobs > 0)
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get));
+        Self { jobs }
+    }
+
+    /// Create an executor that runs everything on the calling thread
+    pub fn sequential() -> Self {
+        Self { jobs: 1 }
+    }
+
+    /// Number of worker threads
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Apply `f` to every item, returning the results in input order
+    pub fn map<T, R, F>(&self, items: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        let workers = self.jobs.min(items.len());
+        if workers <= 1 {
+            return items.iter().map(f).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let mut indexed: Vec<(usize, R)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(item) = items.get(index) else {
+                                break;
+                            };
+                            done.push((index, f(item)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| match handle.join() {
+                    Ok(done) => done,
+                    Err(panic) => std::panic::resume_unwind(panic),
+                })
+                .collect()
+        });
+
+        indexed.sort_unstable_by_key(|(index, _)| *index);
+        indexed.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Apply a fallible `f` to every item, returning the results in input order
+    ///
+    /// A failing item does not stop the others. When any fail, their errors
+    /// are returned instead, in input order: a single error as is, several
+    /// as [`MatterOfError::Multiple`].
+    pub fn try_map<T, R, F>(&self, items: &[T], f: F) -> Result<Vec<R>>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> Result<R> + Sync,
+    {
+        collect_results(self.map(items, f))
+    }
+}
+
+/// Split per-item results into the values, or all the errors
+pub fn collect_results<R>(results: Vec<Result<R>>) -> Result<Vec<R>> {
+    let mut values = Vec::with_capacity(results.len());
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(values),
+        1 => Err(errors.remove(0)),
+        _ => Err(MatterOfError::multiple(errors)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let items: Vec<u64> = (0..200).collect();
+        let executor = ParallelExecutor::new(Some(8));
+        assert_eq!(executor.jobs(), 8);
+
+        let results = executor.map(&items, |&n| {
+            // Make early items finish last
+            thread::sleep(std::time::Duration::from_micros(200 - n));
+            n * 2
+        });
+        assert_eq!(results, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(ParallelExecutor::sequential().map(&items, |&n| n), items);
+    }
+
+    #[test]
+    fn test_try_map_collects_errors() {
+        let items = vec![1, 2, 3, 4];
+        let executor = ParallelExecutor::new(Some(4));
+
+        let fail_even = |n: &i32| {
+            if n % 2 == 0 {
+                Err(MatterOfError::validation(format!("{} is even", n)))
+            } else {
+                Ok(*n)
+            }
+        };
+        match executor.try_map(&items, fail_even) {
+            Err(MatterOfError::Multiple { errors }) => {
+                let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                assert_eq!(
+                    messages,
+                    vec!["Validation error: 2 is even", "Validation error: 4 is even"]
+                );
+            }
+            other => panic!("Expected multiple errors, got {:?}", other),
+        }
+
+        // A single failure is returned as is, every other item still ran
+        let result = executor.try_map(&items, |&n| {
+            if n == 3 {
+                Err(MatterOfError::validation("three"))
+            } else {
+                Ok(n)
+            }
+        });
+        assert!(matches!(result, Err(MatterOfError::Validation { .. })));
+        assert_eq!(executor.try_map(&items, |&n| Ok(n)).unwrap(), items);
+    }
+}
@@ -3,11 +3,11 @@
 //! This module provides efficient file reading with front matter parsing,
 //! supporting lazy loading and proper error handling.
 
-use crate::core::{Document, FrontMatterValue};
+use crate::core::{Document, FrontMatterFormat, FrontMatterValue, JsonPatcher};
 use crate::error::{MatterOfError, Result};
-use gray_matter::{engine::YAML, Matter};
-use std::collections::BTreeMap;
-use std::fs;
+use indexmap::IndexMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 /// Configuration for the front matter reader
@@ -34,7 +34,6 @@ impl Default for ReaderConfig {
 /// Front matter reader
 pub struct FrontMatterReader {
     config: ReaderConfig,
-    matter: Matter<YAML>,
 }
 
 impl FrontMatterReader {
@@ -42,31 +41,18 @@ impl FrontMatterReader {
     pub fn new() -> Self {
         Self {
             config: ReaderConfig::default(),
-            matter: Matter::<YAML>::new(),
         }
     }
 
     /// Create a new reader with custom configuration
     pub fn with_config(config: ReaderConfig) -> Self {
-        Self {
-            config,
-            matter: Matter::<YAML>::new(),
-        }
+        Self { config }
     }
 
     /// Read a document from a file path
     pub fn read_file<P: AsRef<Path>>(&self, path: P) -> Result<Document> {
         let path = path.as_ref();
-
-        // Check if file exists
-        if !path.exists() {
-            return Err(MatterOfError::file_not_found(path));
-        }
-
-        // Check if it's a file
-        if !path.is_file() {
-            return Err(MatterOfError::invalid_file_format(path));
-        }
+        Self::check_file(path)?;
 
         // Check file size if limit is set
         if let Some(max_size) = self.config.max_file_size {
@@ -82,23 +68,138 @@ impl FrontMatterReader {
         }
 
         // Read file content
-        let content = fs::read_to_string(path).map_err(|e| match e.kind() {
-            std::io::ErrorKind::PermissionDenied => MatterOfError::permission_denied(path),
-            _ => MatterOfError::Io(e),
-        })?;
+        let content = fs::read_to_string(path).map_err(|e| Self::io_error(path, e))?;
 
         self.parse_content(&content, Some(path))
     }
 
+    /// Read a document's front matter, leaving its body empty
+    ///
+    /// The file is read only up to the closing delimiter, so the size of the
+    /// body does not matter; `max_file_size` only bounds the front matter
+    /// block. Meant for commands that never look at or write back the body.
+    pub fn read_front_matter_document<P: AsRef<Path>>(&self, path: P) -> Result<Document> {
+        let path = path.as_ref();
+        Self::check_file(path)?;
+
+        let Some((format, raw)) = self.read_front_matter_block(path)? else {
+            return Ok(Document::new(None, String::new()));
+        };
+        let path_str = path.to_string_lossy();
+        let front_matter = self.parse_front_matter(format, &raw, &path_str)?;
+        let document = Document::new(front_matter, String::new())
+            .with_format(format)
+            .with_source(raw);
+
+        if self.config.validate_on_read {
+            document.validate().map_err(|e| {
+                MatterOfError::invalid_front_matter(path_str.as_ref(), e.to_string())
+            })?;
+        }
+
+        Ok(document)
+    }
+
+    /// Fail unless the path is an existing regular file
+    fn check_file(path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Err(MatterOfError::file_not_found(path));
+        }
+        if !path.is_file() {
+            return Err(MatterOfError::invalid_file_format(path));
+        }
+        Ok(())
+    }
+
+    /// Map an I/O error on `path`, telling permission problems apart
+    fn io_error(path: &Path, error: std::io::Error) -> MatterOfError {
+        match error.kind() {
+            std::io::ErrorKind::PermissionDenied => MatterOfError::permission_denied(path),
+            _ => MatterOfError::Io(error),
+        }
+    }
+
+    /// Read the raw front matter block at the start of a file, line by line
+    ///
+    /// Reading stops at the closing delimiter (or the end of a bare JSON
+    /// object); the body is never read. Mirrors [`Self::split_front_matter`].
+    /// A block still open after `max_file_size` bytes is an error, so a file
+    /// that never closes its front matter is not read to the end.
+    fn read_front_matter_block(&self, path: &Path) -> Result<Option<(FrontMatterFormat, String)>> {
+        let file = File::open(path).map_err(|e| Self::io_error(path, e))?;
+        let mut reader = BufReader::new(file);
+        let limit = self.config.max_file_size;
+        let mut read_line = |line: &mut String| {
+            let read = reader
+                .read_line(line)
+                .map_err(|e| Self::io_error(path, e))?;
+            if let Some(limit) = limit.filter(|&limit| line.len() > limit) {
+                return Err(MatterOfError::invalid_front_matter(
+                    path,
+                    format!(
+                        "unterminated front matter (not closed within {} bytes)",
+                        limit
+                    ),
+                ));
+            }
+            Ok(read)
+        };
+
+        let mut first = String::new();
+        if read_line(&mut first)? == 0 {
+            return Ok(None);
+        }
+
+        if first.starts_with('{') {
+            // The object may span several lines; only the new text is scanned
+            let mut scanner = JsonObjectScanner::default();
+            let mut content = first;
+            let mut end = scanner.scan(&content);
+            while end.is_none() {
+                let scanned = content.len();
+                if read_line(&mut content)? == 0 {
+                    break;
+                }
+                end = scanner.scan(&content[scanned..]);
+            }
+            return Ok(
+                Self::split_json_object(&content).map(|(format, raw, _)| (format, raw.to_string()))
+            );
+        }
+
+        let Some(format) = FrontMatterFormat::from_delimiter(&first) else {
+            return Ok(None);
+        };
+        let mut raw = first;
+        let start = raw.len();
+        loop {
+            let line_start = raw.len();
+            if read_line(&mut raw)? == 0 {
+                // Never closed
+                return Ok(None);
+            }
+            if format.is_closing_delimiter(&raw[line_start..]) {
+                raw.truncate(line_start);
+                return Ok(Some((format, raw.split_off(start))));
+            }
+        }
+    }
+
     /// Parse document from string content
     pub fn parse_content(&self, content: &str, path: Option<&Path>) -> Result<Document> {
         let path_str = path.map(|p| p.to_string_lossy()).unwrap_or_default();
 
         // Parse front matter and body
-        let (front_matter, body) = self.extract_front_matter(content, &path_str)?;
-
-        // Create document
-        let mut document = Document::new(front_matter, body);
+        let mut document = match Self::split_front_matter(content) {
+            Some((format, raw, body)) => {
+                let front_matter = self.parse_front_matter(format, raw, &path_str)?;
+                // Keep the raw front matter for format-preserving writes
+                Document::new(front_matter, body.to_string())
+                    .with_format(format)
+                    .with_source(raw.to_string())
+            }
+            None => Document::new(None, content.to_string()),
+        };
 
         // Preserve original content if requested
         if self.config.preserve_original {
@@ -115,61 +216,100 @@ impl FrontMatterReader {
         Ok(document)
     }
 
-    /// Extract front matter and body from content
-    fn extract_front_matter(
-        &self,
-        content: &str,
-        path: &str,
-    ) -> Result<(Option<BTreeMap<String, FrontMatterValue>>, String)> {
-        // Handle empty content
-        if content.trim().is_empty() {
-            return Ok((None, content.to_string()));
+    /// Split content into the raw front matter block and the body
+    ///
+    /// The opening line selects the format (`---` for YAML, `+++` for TOML,
+    /// `;;;` for JSON) and the block ends at the matching closing line. A file
+    /// starting with `{` holds a bare JSON object instead. Both parts are
+    /// returned byte-for-byte. Returns `None` when there is no opening delimiter
+    /// or the block is never closed.
+    fn split_front_matter(content: &str) -> Option<(FrontMatterFormat, &str, &str)> {
+        if content.starts_with('{') {
+            return Self::split_json_object(content);
         }
 
-        // Check if content has front matter delimiters
-        if !content.trim_start().starts_with("---") {
-            return Ok((None, content.to_string()));
+        let mut lines = content.split_inclusive('\n');
+        let first = lines.next()?;
+        let format = FrontMatterFormat::from_delimiter(first)?;
+
+        let start = first.len();
+        let mut offset = start;
+        for line in lines {
+            if format.is_closing_delimiter(line) {
+                return Some((
+                    format,
+                    &content[start..offset],
+                    &content[offset + line.len()..],
+                ));
+            }
+            offset += line.len();
         }
 
-        // Parse using gray_matter
-        let parsed = self.matter.parse(content);
-
-        // Extract front matter
-        let front_matter = if let Some(data) = parsed.data {
-            match data.deserialize() {
-                Ok(serde_yaml::Value::Mapping(map)) => {
-                    let mut fm = BTreeMap::new();
-                    for (k, v) in map {
-                        if let Some(key_str) = k.as_str() {
-                            fm.insert(key_str.to_string(), FrontMatterValue::new(v));
-                        } else {
-                            return Err(MatterOfError::invalid_front_matter(
-                                path,
-                                format!("Non-string key found: {:?}", k),
-                            ));
-                        }
-                    }
-                    Some(fm)
-                }
-                Ok(serde_yaml::Value::Null) => None,
-                Ok(other) => {
-                    return Err(MatterOfError::invalid_front_matter(
-                        path,
-                        format!("Expected mapping or null, found {:?}", other),
-                    ));
-                }
-                Err(e) => {
-                    return Err(MatterOfError::invalid_front_matter(
-                        path,
-                        format!("Failed to deserialize front matter: {}", e),
-                    ));
-                }
-            }
+        None
+    }
+
+    /// Split off a bare JSON object at the start of the content
+    ///
+    /// The object must be valid JSON; anything else (such as a `{{< shortcode >}}`
+    /// on the first line) is treated as body text. A single line break after
+    /// the closing brace belongs to the front matter.
+    fn split_json_object(content: &str) -> Option<(FrontMatterFormat, &str, &str)> {
+        let raw = &content[..Self::json_object_end(content)?];
+        JsonPatcher::parse(raw).ok()?;
+
+        let rest = &content[raw.len()..];
+        let body = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+            .unwrap_or(rest);
+        Some((FrontMatterFormat::Json { fenced: false }, raw, body))
+    }
+
+    /// Byte offset just past the brace closing the object the content starts with
+    fn json_object_end(content: &str) -> Option<usize> {
+        JsonObjectScanner::default().scan(content)
+    }
+
+    /// Parse the raw text between the delimiters into a front matter map
+    fn parse_front_matter(
+        &self,
+        format: FrontMatterFormat,
+        raw: &str,
+        path: &str,
+    ) -> Result<Option<IndexMap<String, FrontMatterValue>>> {
+        let parsed = if raw.trim().is_empty() {
+            Ok(serde_yaml::Value::Null)
         } else {
-            None
+            format.parse(raw)
         };
+        let data = parsed.map_err(|e| {
+            MatterOfError::invalid_front_matter(
+                path,
+                format!("Failed to deserialize front matter: {}", e),
+            )
+        })?;
 
-        Ok((front_matter, parsed.content))
+        match data {
+            serde_yaml::Value::Mapping(map) => {
+                let mut fm = IndexMap::new();
+                for (k, v) in map {
+                    if let Some(key_str) = k.as_str() {
+                        fm.insert(key_str.to_string(), FrontMatterValue::new(v));
+                    } else {
+                        return Err(MatterOfError::invalid_front_matter(
+                            path,
+                            format!("Non-string key found: {:?}", k),
+                        ));
+                    }
+                }
+                Ok(Some(fm))
+            }
+            serde_yaml::Value::Null => Ok(None),
+            other => Err(MatterOfError::invalid_front_matter(
+                path,
+                format!("Expected mapping or null, found {:?}", other),
+            )),
+        }
     }
 
     /// Check if a file is a markdown file
@@ -188,43 +328,17 @@ impl FrontMatterReader {
     }
 
     /// Read only the front matter from a file (for efficiency)
+    ///
+    /// The file is streamed up to the closing delimiter; the body is never read.
     pub fn read_front_matter_only<P: AsRef<Path>>(
         &self,
         path: P,
-    ) -> Result<Option<BTreeMap<String, FrontMatterValue>>> {
-        let content = fs::read_to_string(path.as_ref()).map_err(MatterOfError::Io)?;
-
-        // Quick check for front matter
-        if !content.trim_start().starts_with("---") {
-            return Ok(None);
-        }
-
-        // Find the end of front matter to avoid reading entire file
-        let lines: Vec<&str> = content.lines().collect();
-        if lines.len() < 2 {
+    ) -> Result<Option<IndexMap<String, FrontMatterValue>>> {
+        let path = path.as_ref();
+        let Some((format, raw)) = self.read_front_matter_block(path)? else {
             return Ok(None);
-        }
-
-        // Find closing delimiter
-        let mut end_line = None;
-        for (i, line) in lines.iter().enumerate().skip(1) {
-            if line.trim() == "---" || line.trim() == "..." {
-                end_line = Some(i);
-                break;
-            }
-        }
-
-        let front_matter_content = if let Some(end) = end_line {
-            lines[0..=end].join("\n")
-        } else {
-            // No closing delimiter found, but try to parse anyway
-            content.clone()
         };
-
-        let (front_matter, _) =
-            self.extract_front_matter(&front_matter_content, &path.as_ref().to_string_lossy())?;
-
-        Ok(front_matter)
+        self.parse_front_matter(format, &raw, &path.to_string_lossy())
     }
 
     /// Get reader configuration
@@ -233,6 +347,52 @@ impl FrontMatterReader {
     }
 }
 
+/// Finds the end of a JSON object fed to it in pieces
+///
+/// Nesting and string state carry over between pieces, so each byte is
+/// looked at once however the object is split into lines.
+#[derive(Debug, Default)]
+struct JsonObjectScanner {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    offset: usize,
+}
+
+impl JsonObjectScanner {
+    /// Scan the next piece, returning the offset just past the closing brace
+    /// (counted from the start of the first piece) once it is found
+    fn scan(&mut self, text: &str) -> Option<usize> {
+        let offset = self.offset;
+        self.offset += text.len();
+
+        for (i, c) in text.char_indices() {
+            if self.in_string {
+                match c {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => self.in_string = true,
+                '{' | '[' => self.depth += 1,
+                '}' | ']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        return Some(offset + i + 1);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
 impl Default for FrontMatterReader {
     fn default() -> Self {
         Self::new()
@@ -256,7 +416,7 @@ pub mod convenience {
     /// Read only front matter from a file with default settings
     pub fn read_front_matter<P: AsRef<Path>>(
         path: P,
-    ) -> Result<Option<BTreeMap<String, FrontMatterValue>>> {
+    ) -> Result<Option<IndexMap<String, FrontMatterValue>>> {
         FrontMatterReader::new().read_front_matter_only(path)
     }
 
@@ -354,8 +514,7 @@ Body content"#;
         let file = create_test_file(content);
         let _result = reader.read_file(file.path());
 
-        // This should succeed initially but we can test that gray_matter is working
-        // Let's instead test a case where the front matter parsing should fail
+        // Test a case where the front matter parsing should fail
         // by having malformed YAML that serde_yaml cannot handle
 
         // Test with completely malformed YAML structure
@@ -369,20 +528,11 @@ Body content"#;
         let file2 = create_test_file(malformed_content);
         let result2 = reader.read_file(file2.path());
 
-        // If gray_matter is too permissive, let's at least verify our error handling works
-        // by testing the document validation separately
-        if result2.is_ok() {
-            // Gray_matter parsed it somehow, so let's test validation logic
-            let doc = result2.unwrap();
-            // This test just verifies the code path exists - we can refine validation later
-            assert!(doc.front_matter().is_some() || doc.front_matter().is_none());
-        } else {
-            // Good, it failed as expected
-            assert!(matches!(
-                result2.unwrap_err(),
-                MatterOfError::InvalidFrontMatter { .. } | MatterOfError::Yaml(_)
-            ));
-        }
+        // Malformed YAML inside the delimiters is reported, not silently dropped
+        assert!(matches!(
+            result2.unwrap_err(),
+            MatterOfError::InvalidFrontMatter { .. }
+        ));
     }
 
     #[test]
@@ -404,6 +554,149 @@ Lorem ipsum dolor sit amet, consectetur adipiscing elit.
         assert_eq!(front_matter.get("count").unwrap().as_int(), Some(42));
     }
 
+    #[test]
+    fn test_read_front_matter_document_skips_body() {
+        // The body is not UTF-8 and the file exceeds the size limit; neither matters
+        let mut content = b"---\ntitle: Test\n...\n".to_vec();
+        content.extend(std::iter::repeat_n(0xFF, 4096));
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&content).unwrap();
+
+        let reader = FrontMatterReader::with_config(ReaderConfig {
+            max_file_size: Some(1024),
+            ..Default::default()
+        });
+        assert!(reader.read_file(file.path()).is_err());
+
+        let title = crate::core::KeyPath::parse("title").unwrap();
+        let doc = reader.read_front_matter_document(file.path()).unwrap();
+        assert_eq!(doc.get(&title).unwrap().as_string(), Some("Test"));
+        assert_eq!(doc.source(), Some("title: Test\n"));
+        assert_eq!(doc.body(), "");
+
+        // Bare JSON objects end at their closing brace
+        let file = create_test_file("{\n  \"title\": \"Json\"\n}\nBody\n");
+        let doc = reader.read_front_matter_document(file.path()).unwrap();
+        assert_eq!(doc.format(), FrontMatterFormat::Json { fenced: false });
+        assert_eq!(doc.get(&title).unwrap().as_string(), Some("Json"));
+
+        // No or unclosed front matter
+        for content in ["# Just a body\n", "---\ntitle: Test\nBody"] {
+            let file = create_test_file(content);
+            let doc = reader.read_front_matter_document(file.path()).unwrap();
+            assert!(!doc.has_front_matter());
+        }
+    }
+
+    #[test]
+    fn test_read_front_matter_document_unterminated() {
+        let reader = FrontMatterReader::new();
+
+        // An object that never balances is scanned once, not once per line
+        let mut content = String::from("{\n");
+        for i in 0..40_000 {
+            content.push_str(&format!("\"key{}\": {{ \"nested\": [1, 2,\n", i));
+        }
+        let file = create_test_file(&content);
+        let start = std::time::Instant::now();
+        let doc = reader.read_front_matter_document(file.path()).unwrap();
+        assert!(!doc.has_front_matter());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+
+        // Blocks still open past the size limit are errors
+        let limited = FrontMatterReader::with_config(ReaderConfig {
+            max_file_size: Some(1024),
+            ..Default::default()
+        });
+        let err = limited
+            .read_front_matter_document(file.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unterminated front matter"), "{}", err);
+
+        let file = create_test_file(&format!("---\n{}", "title: x\n".repeat(1000)));
+        assert!(limited.read_front_matter_document(file.path()).is_err());
+        assert!(!reader
+            .read_front_matter_document(file.path())
+            .unwrap()
+            .has_front_matter());
+    }
+
+    #[test]
+    fn test_split_keeps_source_and_body_exact() {
+        let content = "---\n# comment\nzeta: 1\nalpha: 'two'\n---\n\n# Body\n";
+        let doc = FrontMatterReader::new()
+            .parse_content(content, None)
+            .unwrap();
+
+        assert_eq!(doc.source(), Some("# comment\nzeta: 1\nalpha: 'two'\n"));
+        assert_eq!(doc.body(), "\n# Body\n");
+
+        // An unclosed block is not front matter
+        let unclosed = "---\ntitle: Test\nBody";
+        let doc = FrontMatterReader::new()
+            .parse_content(unclosed, None)
+            .unwrap();
+        assert!(!doc.has_front_matter());
+        assert_eq!(doc.body(), unclosed);
+    }
+
+    #[test]
+    fn test_read_toml_front_matter() {
+        let content = "+++\ntitle = \"Hugo Post\"\ntags = [\"go\", \"hugo\"]\n\n[params]\nweight = 3\n+++\nBody\n";
+        let doc = FrontMatterReader::new()
+            .parse_content(content, None)
+            .unwrap();
+
+        assert_eq!(doc.format(), FrontMatterFormat::Toml);
+        assert_eq!(doc.body(), "Body\n");
+        let weight = doc
+            .get(&crate::core::KeyPath::parse("params.weight").unwrap())
+            .unwrap();
+        assert_eq!(weight.as_int(), Some(3));
+
+        // `---` does not close a TOML block
+        let unclosed = "+++\ntitle = \"x\"\n---\nBody";
+        let doc = FrontMatterReader::new()
+            .parse_content(unclosed, None)
+            .unwrap();
+        assert!(!doc.has_front_matter());
+    }
+
+    #[test]
+    fn test_read_json_front_matter() {
+        let reader = FrontMatterReader::new();
+        let title = crate::core::KeyPath::parse("title").unwrap();
+
+        let fenced = ";;;\n{\"title\": \"Fenced\", \"tags\": [\"a\"]}\n;;;\nBody\n";
+        let doc = reader.parse_content(fenced, None).unwrap();
+        assert_eq!(doc.format(), FrontMatterFormat::Json { fenced: true });
+        assert_eq!(doc.get(&title).unwrap().as_string(), Some("Fenced"));
+        assert_eq!(doc.body(), "Body\n");
+
+        let bare = "{\n  \"title\": \"Bare {braces} \\\"quoted\\\"\"\n}\n\nBody\n";
+        let doc = reader.parse_content(bare, None).unwrap();
+        assert_eq!(doc.format(), FrontMatterFormat::Json { fenced: false });
+        assert_eq!(
+            doc.get(&title).unwrap().as_string(),
+            Some("Bare {braces} \"quoted\"")
+        );
+        assert_eq!(doc.body(), "\nBody\n");
+
+        // A leading shortcode is body text, not front matter
+        let shortcode = "{{< figure src=\"a.png\" >}}\nBody\n";
+        let doc = reader.parse_content(shortcode, None).unwrap();
+        assert!(!doc.has_front_matter());
+        assert_eq!(doc.body(), shortcode);
+
+        // Invalid JSON inside fences is an error
+        let invalid = ";;;\n{title: nope}\n;;;\nBody\n";
+        assert!(matches!(
+            reader.parse_content(invalid, None).unwrap_err(),
+            MatterOfError::InvalidFrontMatter { .. }
+        ));
+    }
+
     #[test]
     fn test_is_markdown_file() {
         assert!(FrontMatterReader::is_markdown_file("test.md"));
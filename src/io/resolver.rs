@@ -21,7 +21,7 @@ pub struct ResolverConfig {
     pub include_extensions: Vec<String>,
     /// File extensions to exclude
     pub exclude_extensions: Vec<String>,
-    /// Patterns to exclude (glob-style)
+    /// Patterns to exclude (glob-style, see [`FileResolver::accepts`])
     pub exclude_patterns: Vec<String>,
     /// Only include files that exist
     pub only_existing: bool,
@@ -82,7 +82,8 @@ impl FileResolver {
             let path = path.as_ref();
             let files = self.resolve_single_path(path)?;
 
-            for file in files {
+            // Filter based on configuration, relative to the path given
+            for file in self.filter_files(path, files)? {
                 // Avoid duplicates
                 if seen_paths.insert(file.path.clone()) {
                     resolved_files.push(file);
@@ -90,9 +91,6 @@ impl FileResolver {
             }
         }
 
-        // Filter based on configuration
-        resolved_files = self.filter_files(resolved_files)?;
-
         // Sort for consistent ordering
         resolved_files.sort_by(|a, b| a.path.cmp(&b.path));
 
@@ -163,7 +161,7 @@ impl FileResolver {
     }
 
     /// Filter files based on configuration
-    fn filter_files(&self, files: Vec<ResolvedFile>) -> Result<Vec<ResolvedFile>> {
+    fn filter_files(&self, root: &Path, files: Vec<ResolvedFile>) -> Result<Vec<ResolvedFile>> {
         let mut filtered = Vec::new();
 
         for file in files {
@@ -172,36 +170,7 @@ impl FileResolver {
                 continue;
             }
 
-            // Check include extensions
-            if !self.config.include_extensions.is_empty() {
-                let ext = file
-                    .path
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_lowercase())
-                    .unwrap_or_default();
-
-                if !self.config.include_extensions.contains(&ext) {
-                    continue;
-                }
-            }
-
-            // Check exclude extensions
-            if !self.config.exclude_extensions.is_empty() {
-                let ext = file
-                    .path
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_lowercase())
-                    .unwrap_or_default();
-
-                if self.config.exclude_extensions.contains(&ext) {
-                    continue;
-                }
-            }
-
-            // Check exclude patterns (simple glob-like matching)
-            if self.should_exclude_by_pattern(&file.path)? {
+            if !self.passes_filters(root, &file.path)? {
                 continue;
             }
 
@@ -211,10 +180,87 @@ impl FileResolver {
         Ok(filtered)
     }
 
-    /// Check if a file should be excluded by pattern matching
-    fn should_exclude_by_pattern(&self, path: &Path) -> Result<bool> {
+    /// Whether a file found from `root` passes the extension and exclude-pattern filters
+    fn passes_filters(&self, root: &Path, path: &Path) -> Result<bool> {
+        let ext = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default();
+
+        // Check include extensions
+        if !self.config.include_extensions.is_empty()
+            && !self.config.include_extensions.contains(&ext)
+        {
+            return Ok(false);
+        }
+
+        // Check exclude extensions
+        if self.config.exclude_extensions.contains(&ext) {
+            return Ok(false);
+        }
+
+        // Check exclude patterns (simple glob-like matching)
+        Ok(!self.should_exclude_by_pattern(root, path)?)
+    }
+
+    /// Whether resolving `root` would pick up `path`, existing or not
+    ///
+    /// Used to tell which changed files matter when watching `root`.
+    ///
+    /// An exclude pattern, where `*` matches any run of characters and `?`
+    /// one character, excludes the file when it matches its whole path, its
+    /// path relative to `root` or a leading directory of that relative path,
+    /// so `drafts` and `drafts/*` both exclude `content/drafts/post.md` when
+    /// resolving `content`. A pattern without `/` also matches the name of
+    /// the file or of any directory below `root`, like `node_modules`.
+    pub fn accepts(&self, root: &Path, path: &Path) -> Result<bool> {
+        if path == root {
+            return self.passes_filters(root, path);
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            return Ok(false);
+        };
+
+        let depth = relative.components().count();
+        if depth > self.config.max_depth.unwrap_or(usize::MAX) {
+            return Ok(false);
+        }
+        if !self.config.include_hidden && self.is_hidden_file(path) {
+            return Ok(false);
+        }
+        self.passes_filters(root, path)
+    }
+
+    /// Check if a file found from `root` should be excluded by pattern matching
+    fn should_exclude_by_pattern(&self, root: &Path, path: &Path) -> Result<bool> {
+        if self.config.exclude_patterns.is_empty() {
+            return Ok(false);
+        }
+
+        // The whole path, the path below root and its leading directories
+        let mut candidates = vec![path_string(path)];
+        let relative = match path.strip_prefix(root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => Path::new(path.file_name().unwrap_or_default()),
+        };
+        candidates.extend(
+            relative
+                .ancestors()
+                .map(path_string)
+                .filter(|p| !p.is_empty()),
+        );
+        let names: Vec<_> = relative
+            .components()
+            .map(|name| name.as_os_str().to_string_lossy())
+            .collect();
+
         for pattern in &self.config.exclude_patterns {
-            if self.matches_pattern(path, pattern)? {
+            let regex = Self::pattern_regex(pattern)?;
+            if candidates.iter().any(|candidate| regex.is_match(candidate)) {
+                return Ok(true);
+            }
+            if !pattern.contains('/') && names.iter().any(|name| regex.is_match(name)) {
                 return Ok(true);
             }
         }
@@ -222,17 +268,19 @@ impl FileResolver {
     }
 
     /// Simple pattern matching (supports * and ? wildcards)
-    fn matches_pattern(&self, path: &Path, pattern: &str) -> Result<bool> {
-        let path_str = path.to_string_lossy();
-
+    fn pattern_regex(pattern: &str) -> Result<regex::Regex> {
         // Convert simple glob pattern to regex
-        let regex_pattern = pattern
-            .replace(".", r"\.")
-            .replace("*", ".*")
-            .replace("?", ".");
+        let mut regex_pattern = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => regex_pattern.push_str(".*"),
+                '?' => regex_pattern.push('.'),
+                c => regex_pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex_pattern.push('$');
 
-        let regex = regex::Regex::new(&format!("^{}$", regex_pattern))?;
-        Ok(regex.is_match(&path_str))
+        Ok(regex::Regex::new(&regex_pattern)?)
     }
 
     /// Check if a file is a markdown file based on extension
@@ -273,6 +321,11 @@ impl FileResolver {
     }
 }
 
+/// A path as matched by exclude patterns, with `/` separators
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
 impl Default for FileResolver {
     fn default() -> Self {
         Self::new()
@@ -482,6 +535,30 @@ mod tests {
         assert!(filenames.contains(&"test2.markdown"));
     }
 
+    #[test]
+    fn test_exclude_patterns_relative_to_root() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_files(temp_dir.path()).unwrap();
+        let resolve = |pattern: &str| {
+            let resolver = FileResolver::with_config(ResolverConfig {
+                exclude_patterns: vec![pattern.to_string()],
+                ..Default::default()
+            });
+            let resolved = resolver.resolve_paths(&[temp_dir.path()]).unwrap();
+            resolved.len()
+        };
+
+        // test1.md, test2.markdown and subdir/nested.md without exclusions
+        assert_eq!(resolve("nothing"), 3);
+        assert_eq!(resolve("subdir"), 2);
+        assert_eq!(resolve("subdir/**"), 2);
+        assert_eq!(resolve("subdir/nested.md"), 2);
+        assert_eq!(resolve("*.markdown"), 2);
+        assert_eq!(resolve("nested.md"), 2);
+        // Patterns with a `/` are anchored at the root
+        assert_eq!(resolve("dir/nested.md"), 3);
+    }
+
     #[test]
     fn test_include_extensions() {
         let temp_dir = TempDir::new().unwrap();
@@ -568,4 +645,28 @@ mod tests {
         assert_eq!(resolved.len(), 1);
         assert_eq!(resolved[0].path, file_path);
     }
+
+    #[test]
+    fn test_accepts_changed_paths() {
+        let root = Path::new("content");
+        let resolver = FileResolver::with_config(ResolverConfig {
+            max_depth: Some(2),
+            exclude_patterns: vec!["*/drafts/*".to_string()],
+            ..Default::default()
+        });
+
+        let accepts = |path: &str| resolver.accepts(root, Path::new(path)).unwrap();
+        assert!(accepts("content/post.md"));
+        assert!(accepts("content/2024/post.markdown"));
+        assert!(!accepts("content/2024/01/post.md")); // too deep
+        assert!(!accepts("content/.post.md.swp"));
+        assert!(!accepts("content/.hidden.md"));
+        assert!(!accepts("content/notes.txt"));
+        assert!(!accepts("content/drafts/post.md"));
+        assert!(!accepts("elsewhere/post.md"));
+        // A file given directly is its own root
+        assert!(resolver
+            .accepts(Path::new("post.md"), Path::new("post.md"))
+            .unwrap());
+    }
 }
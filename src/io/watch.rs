@@ -0,0 +1,200 @@
+//! Watching files for changes
+//!
+//! [`FileWatcher`] watches the paths a command was given (directories
+//! recursively) through the platform's file notifications, inotify on Linux.
+//! Bursts of events, such as an editor saving through a temporary file, are
+//! debounced into one [`WatchBatch`], and only the files that resolving the
+//! same paths with the same [`FileResolver`] would pick up are reported.
+
+use crate::error::{MatterOfError, Result};
+use crate::io::FileResolver;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Quiet time after the last event before a batch is reported
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Files that changed during one burst of events
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchBatch {
+    /// Files created or modified, sorted
+    pub changed: Vec<PathBuf>,
+    /// Files deleted or moved away, sorted
+    pub removed: Vec<PathBuf>,
+}
+
+impl WatchBatch {
+    /// Whether no file changed
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A watched path, as given and as reported by the platform
+#[derive(Debug)]
+struct WatchRoot {
+    given: PathBuf,
+    canonical: PathBuf,
+}
+
+impl WatchRoot {
+    /// `path` as it would be spelled when resolving the given path
+    fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(&self.canonical).ok()?;
+        if relative.as_os_str().is_empty() {
+            Some(self.given.clone())
+        } else {
+            Some(self.given.join(relative))
+        }
+    }
+}
+
+/// Reports changes to the files below a set of paths
+pub struct FileWatcher {
+    roots: Vec<WatchRoot>,
+    resolver: FileResolver,
+    debounce: Duration,
+    events: Receiver<notify::Result<Event>>,
+    // Dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    /// Start watching `paths`, filtering changes through `resolver`
+    pub fn new<P: AsRef<Path>>(paths: &[P], resolver: FileResolver) -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+
+        let mut roots = Vec::new();
+        for path in paths {
+            let given = path.as_ref().to_path_buf();
+            let canonical = given
+                .canonicalize()
+                .map_err(|_| MatterOfError::file_not_found(&given))?;
+
+            // A file is watched through its directory, so that it is still
+            // followed when an editor replaces it by renaming a new file
+            let (target, mode) = if canonical.is_dir() {
+                (canonical.as_path(), RecursiveMode::Recursive)
+            } else {
+                let parent = canonical.parent().unwrap_or(Path::new("/"));
+                (parent, RecursiveMode::NonRecursive)
+            };
+            watcher.watch(target, mode).map_err(watch_error)?;
+            roots.push(WatchRoot { given, canonical });
+        }
+
+        Ok(Self {
+            roots,
+            resolver,
+            debounce: DEFAULT_DEBOUNCE,
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Set how long events must stop before a batch is reported
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Wait for the next batch of changes to watched files
+    ///
+    /// Events for files the resolver would not pick up are skipped without
+    /// returning an empty batch.
+    pub fn next_batch(&self) -> Result<WatchBatch> {
+        let mut paths = BTreeSet::new();
+        loop {
+            let event = if paths.is_empty() {
+                self.events.recv().map_err(|_| disconnected())?
+            } else {
+                match self.events.recv_timeout(self.debounce) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return Err(disconnected()),
+                }
+            };
+
+            let event = event.map_err(watch_error)?;
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                if let Some(path) = self.watched(path)? {
+                    paths.insert(path);
+                }
+            }
+        }
+
+        let (changed, removed) = paths.into_iter().partition(|path| path.is_file());
+        Ok(WatchBatch { changed, removed })
+    }
+
+    /// The watched file an event path stands for, if any
+    fn watched(&self, path: &Path) -> Result<Option<PathBuf>> {
+        for root in &self.roots {
+            let Some(path) = root.resolve(path) else {
+                continue;
+            };
+            if self.resolver.accepts(&root.given, &path)? {
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn watch_error(error: notify::Error) -> MatterOfError {
+    match error.kind {
+        notify::ErrorKind::Io(error) => MatterOfError::Io(error),
+        notify::ErrorKind::PathNotFound => match error.paths.first() {
+            Some(path) => MatterOfError::file_not_found(path),
+            None => MatterOfError::path_resolution("Watched path not found"),
+        },
+        _ => MatterOfError::path_resolution(format!("Cannot watch files: {}", error)),
+    }
+}
+
+fn disconnected() -> MatterOfError {
+    MatterOfError::path_resolution("File watcher stopped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::thread;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watch_reports_filtered_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = temp_dir.path().join("content");
+        fs::create_dir(&content).unwrap();
+        let old = content.join("old.md");
+        fs::write(&old, "---\ntitle: Old\n---\n").unwrap();
+
+        let watcher = FileWatcher::new(&[&content], FileResolver::new())
+            .unwrap()
+            .with_debounce(Duration::from_millis(300));
+
+        let post = content.join("post.md");
+        fs::write(&post, "---\ntitle: One\n---\n").unwrap();
+        fs::write(content.join("notes.txt"), "ignored").unwrap();
+        fs::write(&post, "---\ntitle: Two\n---\n").unwrap();
+        fs::remove_file(&old).unwrap();
+
+        // Let the filesystem deliver everything before collecting
+        let start = Instant::now();
+        thread::sleep(Duration::from_millis(50));
+        let batch = watcher.next_batch().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(batch.changed, vec![post]);
+        assert_eq!(batch.removed, vec![old]);
+    }
+}
@@ -5,6 +5,9 @@
 
 use crate::core::Document;
 use crate::error::{MatterOfError, Result};
+use crate::io::journal::JournalRecorder;
+use serde::Deserialize;
+use similar::{Algorithm, TextDiff};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -25,10 +28,18 @@ pub struct WriterConfig {
     pub preserve_permissions: bool,
     /// Line ending style
     pub line_endings: LineEndings,
+    /// Patch the original front matter text instead of re-serializing it,
+    /// keeping key order, comments and scalar styles of untouched keys
+    pub preserve_formatting: bool,
+    /// Indentation used when front matter is rendered from scratch
+    pub indent: usize,
+    /// Lines of context around each change in generated diffs
+    pub diff_context: usize,
 }
 
 /// Line ending styles
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LineEndings {
     /// Unix-style line endings (\n)
     Unix,
@@ -47,6 +58,9 @@ impl Default for WriterConfig {
             atomic_writes: true,
             preserve_permissions: true,
             line_endings: LineEndings::Preserve,
+            preserve_formatting: true,
+            indent: 2,
+            diff_context: 3,
         }
     }
 }
@@ -54,6 +68,8 @@ impl Default for WriterConfig {
 /// Front matter writer
 pub struct FrontMatterWriter {
     config: WriterConfig,
+    /// Records every file written, for undo
+    journal: Option<JournalRecorder>,
 }
 
 /// Write operation options for individual operations
@@ -104,66 +120,331 @@ pub struct WriteResult {
     pub diff: Option<String>,
 }
 
+/// Results of a batch of writes, in the order the files were written
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Source path and write result for each file
+    pub results: Vec<(PathBuf, WriteResult)>,
+}
 
-impl FrontMatterWriter {
-    /// Create a new writer with default configuration
+impl BatchReport {
+    /// Create an empty report
     pub fn new() -> Self {
-        Self {
-            config: WriterConfig::default(),
-        }
+        Self::default()
     }
 
-    /// Create a new writer with custom configuration
-    pub fn with_config(config: WriterConfig) -> Self {
-        Self { config }
+    /// Record the result of writing `path`
+    pub fn push(&mut self, path: impl Into<PathBuf>, result: WriteResult) {
+        self.results.push((path.into(), result));
     }
 
-    /// Write a document to a file
-    pub fn write_file<P: AsRef<Path>>(
-        &self,
+    /// Results of the files that were (or, for dry runs, would be) modified
+    pub fn modified(&self) -> impl Iterator<Item = &(PathBuf, WriteResult)> {
+        self.results.iter().filter(|(_, result)| result.modified)
+    }
+
+    /// Number of modified files
+    pub fn modified_count(&self) -> usize {
+        self.modified().count()
+    }
+
+    /// Backup files created by the batch
+    pub fn backup_paths(&self) -> impl Iterator<Item = &Path> {
+        self.results
+            .iter()
+            .filter_map(|(_, result)| result.backup_path.as_deref())
+    }
+
+    /// Number of files in the report
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Whether the report is empty
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
+
+/// Rendered content of a document ready to be written
+struct PreparedWrite {
+    new_content: String,
+    original_content: Option<String>,
+    content_changed: bool,
+    diff: Option<String>,
+}
+
+/// A batch of writes that is committed all-or-nothing
+///
+/// [`Transaction::stage`] renders each document into a temporary file next to
+/// its target without touching the target itself. [`Transaction::commit`] then
+/// renames every temporary file into place; if any rename fails, the files
+/// already committed are restored and created backups are removed. Dropping a
+/// transaction without committing discards everything staged.
+pub struct Transaction<'a> {
+    writer: &'a FrontMatterWriter,
+    staged: Vec<StagedWrite>,
+    report: BatchReport,
+}
+
+/// A write waiting in a transaction
+struct StagedWrite {
+    /// Index of the result in the batch report
+    index: usize,
+    /// Where the content goes; `None` for stdout
+    target: Option<PathBuf>,
+    /// Temporary file holding the new content
+    temp_file: Option<NamedTempFile>,
+    /// New content, kept for stdout output
+    content: String,
+    /// Target content before the commit, for rollback
+    original: Option<String>,
+    options: WriteOptions,
+}
+
+/// A write that has been committed and may need to be undone
+struct CommittedWrite {
+    target: PathBuf,
+    content: String,
+    original: Option<String>,
+    backup_path: Option<PathBuf>,
+}
+
+impl Transaction<'_> {
+    /// Stage a document for writing
+    ///
+    /// Dry runs and unchanged documents are recorded in the report right
+    /// away; nothing is written before [`Transaction::commit`].
+    pub fn stage<P: AsRef<Path>>(
+        &mut self,
         document: &Document,
         path: P,
         options: Option<WriteOptions>,
-    ) -> Result<WriteResult> {
+    ) -> Result<()> {
         let path = path.as_ref();
         let options = options.unwrap_or_default();
+        let prepared = self.writer.prepare(document, path, &options)?;
 
-        // Generate the new content
-        let new_content = self.format_document(document)?;
+        let output = options.output.as_ref().unwrap_or(&OutputOptions::InPlace);
+        let target = FrontMatterWriter::target_path(path, output)?;
+        let modified = match output {
+            OutputOptions::InPlace | OutputOptions::Stdout => prepared.content_changed,
+            OutputOptions::File(_) | OutputOptions::Directory(_) => true,
+        };
 
-        // Read original content for comparison
-        let original_content = if path.exists() {
-            Some(fs::read_to_string(path).map_err(MatterOfError::Io)?)
-        } else {
-            None
+        let result = WriteResult {
+            modified,
+            output_path: if options.dry_run {
+                Some(path.to_path_buf())
+            } else {
+                target.clone()
+            },
+            backup_path: None,
+            diff: prepared.diff,
         };
+        if options.dry_run || !modified {
+            self.report.push(path, result);
+            return Ok(());
+        }
 
-        // Check if content has actually changed
-        let content_changed = match &original_content {
-            Some(original) => {
-                self.normalize_content(original) != self.normalize_content(&new_content)
+        let (temp_file, original) = match &target {
+            Some(target) => {
+                let original = match output {
+                    OutputOptions::InPlace => prepared.original_content,
+                    _ if target.exists() => {
+                        Some(fs::read_to_string(target).map_err(MatterOfError::Io)?)
+                    }
+                    _ => None,
+                };
+                (
+                    Some(self.stage_temp_file(target, &prepared.new_content)?),
+                    original,
+                )
             }
-            None => !new_content.trim().is_empty(),
+            None => (None, None),
         };
 
-        // Generate diff if requested or for dry run
-        let diff = if options.dry_run || original_content.is_some() {
-            self.generate_diff(
-                original_content.as_deref().unwrap_or(""),
-                &new_content,
-                path,
-            )
+        self.staged.push(StagedWrite {
+            index: self.report.len(),
+            target,
+            temp_file,
+            content: prepared.new_content,
+            original,
+            options,
+        });
+        self.report.push(path, result);
+        Ok(())
+    }
+
+    /// Number of writes waiting for the commit
+    pub fn staged_count(&self) -> usize {
+        self.staged.len()
+    }
+
+    /// Report of everything staged so far
+    pub fn report(&self) -> &BatchReport {
+        &self.report
+    }
+
+    /// Move every staged file into place, or none of them
+    pub fn commit(mut self) -> Result<BatchReport> {
+        let mut committed = Vec::new();
+
+        for staged in std::mem::take(&mut self.staged) {
+            let index = staged.index;
+            match self.commit_one(staged) {
+                Ok(Some(write)) => {
+                    self.report.results[index].1.backup_path = write.backup_path.clone();
+                    committed.push(write);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    let mut errors = Self::roll_back(committed);
+                    if errors.is_empty() {
+                        return Err(error);
+                    }
+                    errors.insert(0, error);
+                    return Err(MatterOfError::multiple(errors));
+                }
+            }
+        }
+
+        // Journal the batch only once all of it is in place
+        if let Some(journal) = &self.writer.journal {
+            for write in &committed {
+                journal.record(&write.target, write.original.as_deref(), &write.content)?;
+            }
+        }
+
+        Ok(self.report)
+    }
+
+    /// Discard everything staged
+    pub fn rollback(self) {
+        // Staged temporary files are removed when dropped
+    }
+
+    /// Write the new content to a temporary file next to the target
+    fn stage_temp_file(&self, target: &Path, content: &str) -> Result<NamedTempFile> {
+        let parent_dir = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent_dir).map_err(MatterOfError::Io)?;
+
+        let mut temp_file = NamedTempFile::new_in(parent_dir).map_err(MatterOfError::Io)?;
+        temp_file
+            .write_all(content.as_bytes())
+            .map_err(MatterOfError::Io)?;
+        temp_file.flush().map_err(MatterOfError::Io)?;
+
+        if self.writer.config.preserve_permissions && target.exists() {
+            let permissions = fs::metadata(target)
+                .map_err(MatterOfError::Io)?
+                .permissions();
+            fs::set_permissions(temp_file.path(), permissions).map_err(MatterOfError::Io)?;
+        }
+
+        Ok(temp_file)
+    }
+
+    /// Commit a single staged write
+    fn commit_one(&self, staged: StagedWrite) -> Result<Option<CommittedWrite>> {
+        let (Some(target), Some(temp_file)) = (staged.target, staged.temp_file) else {
+            println!("{}", staged.content);
+            return Ok(None);
+        };
+
+        let backup_path = if self.writer.should_create_backup(&staged.options) && target.exists() {
+            Some(self.writer.create_backup(&target, &staged.options)?)
         } else {
             None
         };
 
+        if let Err(e) = temp_file.persist(&target) {
+            if let Some(backup_path) = &backup_path {
+                let _ = fs::remove_file(backup_path);
+            }
+            return Err(MatterOfError::Io(std::io::Error::other(format!(
+                "Failed to persist temporary file for {}: {}",
+                target.display(),
+                e
+            ))));
+        }
+
+        Ok(Some(CommittedWrite {
+            target,
+            content: staged.content,
+            original: staged.original,
+            backup_path,
+        }))
+    }
+
+    /// Undo committed writes in reverse order, returning any failures
+    fn roll_back(committed: Vec<CommittedWrite>) -> Vec<MatterOfError> {
+        let mut errors = Vec::new();
+        for write in committed.into_iter().rev() {
+            let restored = match &write.original {
+                Some(original) => fs::write(&write.target, original),
+                None => fs::remove_file(&write.target),
+            };
+            if let Err(e) = restored {
+                errors.push(MatterOfError::Io(std::io::Error::new(
+                    e.kind(),
+                    format!("Rollback of {} failed: {}", write.target.display(), e),
+                )));
+            }
+            if let Some(backup_path) = &write.backup_path {
+                let _ = fs::remove_file(backup_path);
+            }
+        }
+        errors
+    }
+}
+
+impl FrontMatterWriter {
+    /// Create a new writer with default configuration
+    pub fn new() -> Self {
+        Self::with_config(WriterConfig::default())
+    }
+
+    /// Create a new writer with custom configuration
+    pub fn with_config(config: WriterConfig) -> Self {
+        Self {
+            config,
+            journal: None,
+        }
+    }
+
+    /// Record every file this writer modifies in an undo journal
+    pub fn with_journal(mut self, journal: JournalRecorder) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Journal recording this writer's modifications, if any
+    pub fn journal(&self) -> Option<&JournalRecorder> {
+        self.journal.as_ref()
+    }
+
+    /// Write a document to a file
+    pub fn write_file<P: AsRef<Path>>(
+        &self,
+        document: &Document,
+        path: P,
+        options: Option<WriteOptions>,
+    ) -> Result<WriteResult> {
+        let path = path.as_ref();
+        let options = options.unwrap_or_default();
+        let prepared = self.prepare(document, path, &options)?;
+
         // Handle dry run
         if options.dry_run {
             return Ok(WriteResult {
-                modified: content_changed,
+                modified: prepared.content_changed,
                 output_path: Some(path.to_path_buf()),
                 backup_path: None,
-                diff,
+                diff: prepared.diff,
             });
         }
 
@@ -172,32 +453,168 @@ impl FrontMatterWriter {
 
         match output_destination {
             OutputOptions::Stdout => {
-                if content_changed {
-                    println!("{}", new_content);
+                if prepared.content_changed {
+                    println!("{}", prepared.new_content);
                 }
                 Ok(WriteResult {
-                    modified: content_changed,
+                    modified: prepared.content_changed,
                     output_path: None,
                     backup_path: None,
-                    diff,
+                    diff: prepared.diff,
                 })
             }
             OutputOptions::InPlace => self.write_to_file(
                 path,
-                &new_content,
-                &original_content,
+                &prepared.new_content,
+                &prepared.original_content,
                 &options,
-                content_changed,
+                prepared.content_changed,
             ),
-            OutputOptions::File(target_path) => {
-                self.write_to_file(target_path, &new_content, &None, &options, true)
+            OutputOptions::File(_) | OutputOptions::Directory(_) => {
+                let target_path = Self::target_path(path, output_destination)?
+                    .expect("file outputs have a target path");
+                self.write_to_file(&target_path, &prepared.new_content, &None, &options, true)
             }
+        }
+    }
+
+    /// Write a document to a new file, failing if the file already exists
+    ///
+    /// The file appears with its full content at once and is never written
+    /// over, even by a file created concurrently. Dry runs and stdout output
+    /// behave as for [`FrontMatterWriter::write_file`].
+    pub fn create_file<P: AsRef<Path>>(
+        &self,
+        document: &Document,
+        path: P,
+        options: Option<WriteOptions>,
+    ) -> Result<WriteResult> {
+        let path = path.as_ref();
+        let options = options.unwrap_or_default();
+        let output = options.output.as_ref().unwrap_or(&OutputOptions::InPlace);
+        let target = Self::target_path(path, output)?;
+        if let Some(target) = target.as_deref().filter(|target| target.exists()) {
+            return Err(already_exists(target));
+        }
+
+        let prepared = self.prepare(document, path, &options)?;
+        let mut result = WriteResult {
+            modified: true,
+            output_path: target.clone(),
+            backup_path: None,
+            diff: prepared.diff,
+        };
+        if options.dry_run {
+            result.output_path = Some(path.to_path_buf());
+            return Ok(result);
+        }
+        let Some(target) = target else {
+            println!("{}", prepared.new_content);
+            return Ok(result);
+        };
+
+        let parent_dir = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent_dir).map_err(MatterOfError::Io)?;
+
+        if self.config.atomic_writes {
+            let mut temp_file = NamedTempFile::new_in(parent_dir).map_err(MatterOfError::Io)?;
+            temp_file
+                .write_all(prepared.new_content.as_bytes())
+                .map_err(MatterOfError::Io)?;
+            temp_file.flush().map_err(MatterOfError::Io)?;
+            temp_file.persist_noclobber(&target).map_err(|e| {
+                if e.error.kind() == std::io::ErrorKind::AlreadyExists {
+                    already_exists(&target)
+                } else {
+                    MatterOfError::Io(std::io::Error::other(format!(
+                        "Failed to persist temporary file: {}",
+                        e
+                    )))
+                }
+            })?;
+        } else {
+            let mut file = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&target)
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::AlreadyExists => already_exists(&target),
+                    _ => MatterOfError::Io(e),
+                })?;
+            file.write_all(prepared.new_content.as_bytes())
+                .map_err(MatterOfError::Io)?;
+        }
+
+        if let Some(journal) = &self.journal {
+            journal.record(&target, None, &prepared.new_content)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Start a transaction that writes a batch of documents all-or-nothing
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            writer: self,
+            staged: Vec::new(),
+            report: BatchReport::new(),
+        }
+    }
+
+    /// Render a document and compare it with the current file content
+    fn prepare(
+        &self,
+        document: &Document,
+        path: &Path,
+        options: &WriteOptions,
+    ) -> Result<PreparedWrite> {
+        // Generate the new content
+        let new_content = self.format_document(document)?;
+
+        // Read original content for comparison
+        let original_content = if path.exists() {
+            Some(fs::read_to_string(path).map_err(MatterOfError::Io)?)
+        } else {
+            None
+        };
+
+        // Check if content has actually changed
+        let content_changed = match &original_content {
+            Some(original) => {
+                self.normalize_content(original) != self.normalize_content(&new_content)
+            }
+            None => !new_content.trim().is_empty(),
+        };
+
+        // Generate diff if requested or for dry run
+        let diff = if options.dry_run || original_content.is_some() {
+            self.generate_diff(original_content.as_deref(), &new_content, path)
+        } else {
+            None
+        };
+
+        Ok(PreparedWrite {
+            new_content,
+            original_content,
+            content_changed,
+            diff,
+        })
+    }
+
+    /// Resolve where the content for `path` goes (`None` for stdout)
+    fn target_path(path: &Path, output: &OutputOptions) -> Result<Option<PathBuf>> {
+        match output {
+            OutputOptions::Stdout => Ok(None),
+            OutputOptions::InPlace => Ok(Some(path.to_path_buf())),
+            OutputOptions::File(target_path) => Ok(Some(target_path.clone())),
             OutputOptions::Directory(target_dir) => {
                 let filename = path.file_name().ok_or_else(|| {
                     MatterOfError::path_resolution("Could not extract filename".to_string())
                 })?;
-                let target_path = target_dir.join(filename);
-                self.write_to_file(&target_path, &new_content, &None, &options, true)
+                Ok(Some(target_dir.join(filename)))
             }
         }
     }
@@ -222,6 +639,16 @@ impl FrontMatterWriter {
             return Ok(result);
         }
 
+        // Keep what the file held before for the journal
+        let journal_original = match (&self.journal, original_content) {
+            (None, _) => None,
+            (Some(_), Some(original)) => Some(original.clone()),
+            (Some(_), None) if path.exists() => {
+                Some(fs::read_to_string(path).map_err(MatterOfError::Io)?)
+            }
+            (Some(_), None) => None,
+        };
+
         // Create backup if requested and file exists
         if self.should_create_backup(options) && path.exists() {
             result.backup_path = Some(self.create_backup(path, options)?);
@@ -248,26 +675,44 @@ impl FrontMatterWriter {
             }
         }
 
+        if let Some(journal) = &self.journal {
+            journal.record(path, journal_original.as_deref(), content)?;
+        }
+
         Ok(result)
     }
 
     /// Format a document into string content
     fn format_document(&self, document: &Document) -> Result<String> {
-        let yaml_content = if let Some(fm) = document.front_matter() {
-            if fm.is_empty() {
-                None
-            } else {
-                let yaml_value = document.to_yaml_value();
-                let yaml_str = serde_yaml::to_string(&yaml_value)?;
-                Some(yaml_str.trim().to_string())
-            }
-        } else {
-            None
+        let yaml_content = match document.front_matter() {
+            Some(fm) if !fm.is_empty() => Some(self.format_front_matter(document)?),
+            _ => None,
+        };
+
+        let newline = match document.source() {
+            Some(source) if source.contains("\r\n") => "\r\n",
+            _ => "\n",
         };
 
         let formatted = match yaml_content {
-            Some(yaml) => {
-                format!("---\n{}\n---\n{}", yaml, document.body())
+            Some(mut yaml) => {
+                if newline == "\r\n" {
+                    yaml = yaml.replace("\r\n", "\n").replace('\n', "\r\n");
+                }
+                if !yaml.ends_with('\n') {
+                    yaml.push_str(newline);
+                }
+                match document.format().delimiter() {
+                    Some(delimiter) => format!(
+                        "{delim}{nl}{}{delim}{nl}{}",
+                        yaml,
+                        document.body(),
+                        delim = delimiter,
+                        nl = newline
+                    ),
+                    // Bare JSON objects sit directly on top of the body
+                    None => format!("{}{}", yaml, document.body()),
+                }
             }
             None => document.body().to_string(),
         };
@@ -275,6 +720,21 @@ impl FrontMatterWriter {
         Ok(self.normalize_line_endings(&formatted))
     }
 
+    /// Render the front matter text, patching the original text when possible
+    fn format_front_matter(&self, document: &Document) -> Result<String> {
+        let yaml_value = document.to_yaml_value();
+        let format = document.format();
+
+        if let Some(source) = document
+            .source()
+            .filter(|_| self.config.preserve_formatting)
+        {
+            return format.patch(source, &yaml_value);
+        }
+
+        format.render(&yaml_value, self.config.indent)
+    }
+
     /// Normalize content for comparison (handle line endings, trailing whitespace)
     fn normalize_content(&self, content: &str) -> String {
         content
@@ -296,71 +756,42 @@ impl FrontMatterWriter {
         }
     }
 
-    /// Generate unified diff between old and new content
-    fn generate_diff(&self, old_content: &str, new_content: &str, path: &Path) -> Option<String> {
-        if old_content == new_content {
+    /// Generate a unified diff between old and new content
+    ///
+    /// Lines are matched with the patience algorithm and changes are grouped
+    /// into hunks with `diff_context` lines of context, in the `a/` and `b/`
+    /// header style of git, so the output can be applied with `git apply` or
+    /// `patch -p1` from the current directory. A missing original file is
+    /// diffed against `/dev/null`.
+    fn generate_diff(
+        &self,
+        old_content: Option<&str>,
+        new_content: &str,
+        path: &Path,
+    ) -> Option<String> {
+        let old_text = old_content.unwrap_or("");
+        if old_text == new_content {
             return None;
         }
 
-        let old_lines: Vec<&str> = old_content.lines().collect();
-        let new_lines: Vec<&str> = new_content.lines().collect();
-
-        // Simple unified diff implementation
-        let mut diff_lines = Vec::new();
-        diff_lines.push(format!("--- {}", path.display()));
-        diff_lines.push(format!("+++ {}", path.display()));
-
-        // Find common prefix and suffix to minimize diff size
-        let common_prefix = old_lines
-            .iter()
-            .zip(new_lines.iter())
-            .take_while(|(a, b)| a == b)
-            .count();
-
-        let old_suffix = &old_lines[common_prefix..];
-        let new_suffix = &new_lines[common_prefix..];
-
-        let common_suffix_len = old_suffix
-            .iter()
-            .rev()
-            .zip(new_suffix.iter().rev())
-            .take_while(|(a, b)| a == b)
-            .count();
-
-        let old_middle = if common_suffix_len > 0 {
-            &old_suffix[..old_suffix.len() - common_suffix_len]
-        } else {
-            old_suffix
+        let display = diff_path(path);
+        let old_header = match old_content {
+            Some(_) => format!("a/{}", display),
+            None => "/dev/null".to_string(),
         };
 
-        let new_middle = if common_suffix_len > 0 {
-            &new_suffix[..new_suffix.len() - common_suffix_len]
-        } else {
-            new_suffix
-        };
-
-        if !old_middle.is_empty() || !new_middle.is_empty() {
-            diff_lines.push(format!(
-                "@@ -{},{} +{},{} @@",
-                common_prefix + 1,
-                old_middle.len(),
-                common_prefix + 1,
-                new_middle.len()
-            ));
-
-            for line in old_middle {
-                diff_lines.push(format!("-{}", line));
-            }
-            for line in new_middle {
-                diff_lines.push(format!("+{}", line));
-            }
-        }
-
-        if diff_lines.len() > 2 {
-            Some(diff_lines.join("\n"))
-        } else {
-            None
-        }
+        let diff = TextDiff::configure()
+            .algorithm(Algorithm::Patience)
+            .diff_lines(old_text, new_content);
+        let unified = diff
+            .unified_diff()
+            .context_radius(self.config.diff_context)
+            .header(&old_header, &format!("b/{}", display))
+            .to_string();
+
+        // Callers print diffs line by line, so drop the final newline
+        let unified = unified.strip_suffix('\n').unwrap_or(&unified);
+        (!unified.is_empty()).then(|| unified.to_string())
     }
 
     /// Check if backup should be created
@@ -464,6 +895,33 @@ impl FrontMatterWriter {
     }
 }
 
+/// Path of a file in diff headers, relative to the current directory when it is below it
+fn diff_path(path: &Path) -> String {
+    let relative = std::env::current_dir().ok().and_then(|cwd| {
+        path.strip_prefix(&cwd)
+            .ok()
+            .or_else(|| path.strip_prefix(cwd.canonicalize().ok()?).ok())
+            .map(Path::to_path_buf)
+    });
+    let display = relative
+        .as_deref()
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    // Other absolute paths lose their root so the headers keep a single slash
+    display
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .to_string()
+}
+
+fn already_exists(path: &Path) -> MatterOfError {
+    MatterOfError::Io(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    ))
+}
+
 impl Default for FrontMatterWriter {
     fn default() -> Self {
         Self::new()
@@ -523,12 +981,12 @@ pub mod convenience {
 mod tests {
     use super::*;
     use crate::core::FrontMatterValue;
-    use std::collections::BTreeMap;
+    use indexmap::IndexMap;
 
     use tempfile::TempDir;
 
     fn create_test_document() -> Document {
-        let mut fm = BTreeMap::new();
+        let mut fm = IndexMap::new();
         fm.insert(
             "title".to_string(),
             FrontMatterValue::string("Test Document"),
@@ -652,6 +1110,36 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[test]
+    fn test_create_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("posts/new.md");
+        let writer = FrontMatterWriter::new();
+        let document = create_test_document();
+
+        let preview = WriteOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = writer
+            .create_file(&document, &file_path, Some(preview))
+            .unwrap();
+        assert!(result.diff.unwrap().contains("+title: Test Document"));
+        assert!(!file_path.exists());
+
+        let result = writer.create_file(&document, &file_path, None).unwrap();
+        assert!(result.modified);
+        assert!(fs::read_to_string(&file_path)
+            .unwrap()
+            .contains("title: Test Document"));
+
+        // Existing files are never overwritten
+        fs::write(&file_path, "mine").unwrap();
+        let error = writer.create_file(&document, &file_path, None).unwrap_err();
+        assert!(error.to_string().contains("already exists"));
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), "mine");
+    }
+
     #[test]
     fn test_no_change_detection() {
         let document = create_test_document();
@@ -701,7 +1189,7 @@ mod tests {
         let new_content = "line1\nmodified line2\nline3";
         let path = Path::new("test.txt");
 
-        let diff = writer.generate_diff(old_content, new_content, path);
+        let diff = writer.generate_diff(Some(old_content), new_content, path);
         assert!(diff.is_some());
 
         let diff_content = diff.unwrap();
@@ -709,6 +1197,233 @@ mod tests {
         assert!(diff_content.contains("+modified line2"));
     }
 
+    #[test]
+    fn test_diff_hunks_with_context() {
+        let writer = FrontMatterWriter::with_config(WriterConfig {
+            diff_context: 1,
+            ..Default::default()
+        });
+        let old_content: String = (1..=10).map(|i| format!("line{}\n", i)).collect();
+        let new_content = old_content
+            .replace("line2\n", "changed2\n")
+            .replace("line9\n", "changed9\n");
+
+        let diff = writer
+            .generate_diff(Some(&old_content), &new_content, Path::new("./post.md"))
+            .unwrap();
+        assert_eq!(
+            diff,
+            "--- a/post.md\n+++ b/post.md\n\
+             @@ -1,3 +1,3 @@\n line1\n-line2\n+changed2\n line3\n\
+             @@ -8,3 +8,3 @@\n line8\n-line9\n+changed9\n line10"
+        );
+
+        // New files are diffed against /dev/null
+        let diff = writer
+            .generate_diff(None, "title\n", Path::new("new.md"))
+            .unwrap();
+        assert!(diff.starts_with("--- /dev/null\n+++ b/new.md\n@@ -0,0 +1 @@"));
+
+        // Absolute paths are made relative to the current directory
+        let path = std::env::current_dir().unwrap().join("posts/post.md");
+        let diff = writer.generate_diff(Some("a\n"), "b\n", &path).unwrap();
+        assert!(diff.starts_with("--- a/posts/post.md\n+++ b/posts/post.md\n"));
+        let diff = writer
+            .generate_diff(Some("a\n"), "b\n", Path::new("/elsewhere/x.md"))
+            .unwrap();
+        assert!(diff.starts_with("--- a/elsewhere/x.md\n+++ b/elsewhere/x.md\n"));
+    }
+
+    #[test]
+    fn test_preserve_formatting() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.md");
+        let original = "---\n# Site metadata\ntitle: \"Old\"  # keep me\ndraft: true\nauthor: 'Jane'\n---\nBody\n";
+        fs::write(&file_path, original).unwrap();
+
+        let mut document = crate::io::FrontMatterReader::new()
+            .read_file(&file_path)
+            .unwrap();
+        let mut fm = document.front_matter().unwrap().clone();
+        fm.insert("title".to_string(), FrontMatterValue::string("New"));
+        document.replace_front_matter(fm);
+
+        let writer = FrontMatterWriter::new();
+        writer.write_file(&document, &file_path, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "---\n# Site metadata\ntitle: \"New\"  # keep me\ndraft: true\nauthor: 'Jane'\n---\nBody\n"
+        );
+
+        // Without preservation the front matter is rendered from scratch,
+        // still following the original key order
+        let config = WriterConfig {
+            preserve_formatting: false,
+            ..Default::default()
+        };
+        let writer = FrontMatterWriter::with_config(config);
+        writer.write_file(&document, &file_path, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "---\ntitle: New\ndraft: true\nauthor: Jane\n---\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("post.md");
+        let original = "+++\ntitle = \"Old\" # keep me\ndate = 2024-01-01\n+++\nBody\n";
+        fs::write(&file_path, original).unwrap();
+
+        let mut document = crate::io::FrontMatterReader::new()
+            .read_file(&file_path)
+            .unwrap();
+        document
+            .set(
+                &crate::core::KeyPath::parse("title").unwrap(),
+                FrontMatterValue::string("New"),
+            )
+            .unwrap();
+
+        FrontMatterWriter::new()
+            .write_file(&document, &file_path, None)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(&file_path).unwrap(),
+            "+++\ntitle = \"New\" # keep me\ndate = 2024-01-01\n+++\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let title = crate::core::KeyPath::parse("title").unwrap();
+
+        for (original, expected) in [
+            (
+                "{\n  \"title\": \"Old\",\n  \"draft\": true\n}\n\nBody\n",
+                "{\n  \"title\": \"New\",\n  \"draft\": true\n}\n\nBody\n",
+            ),
+            (
+                ";;;\n{\"title\": \"Old\"}\n;;;\nBody\n",
+                ";;;\n{\"title\": \"New\"}\n;;;\nBody\n",
+            ),
+        ] {
+            let file_path = temp_dir.path().join("post.md");
+            fs::write(&file_path, original).unwrap();
+
+            let mut document = crate::io::FrontMatterReader::new()
+                .read_file(&file_path)
+                .unwrap();
+            document
+                .set(&title, FrontMatterValue::string("New"))
+                .unwrap();
+
+            FrontMatterWriter::new()
+                .write_file(&document, &file_path, None)
+                .unwrap();
+            assert_eq!(fs::read_to_string(&file_path).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_transaction_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("existing.md");
+        let created = temp_dir.path().join("new/created.md");
+        fs::write(&existing, "original content").unwrap();
+
+        let writer = FrontMatterWriter::new();
+        let document = create_test_document();
+        let options = WriteOptions {
+            backup: Some(BackupOptions {
+                enabled: true,
+                suffix: Some(".bak".to_string()),
+                directory: None,
+            }),
+            ..Default::default()
+        };
+
+        let mut transaction = writer.transaction();
+        transaction
+            .stage(&document, &existing, Some(options.clone()))
+            .unwrap();
+        transaction
+            .stage(&document, &created, Some(options))
+            .unwrap();
+        assert_eq!(transaction.staged_count(), 2);
+
+        // Nothing is written before the commit
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "original content");
+        assert!(!created.exists());
+
+        let report = transaction.commit().unwrap();
+        assert_eq!(report.modified_count(), 2);
+        assert!(fs::read_to_string(&existing)
+            .unwrap()
+            .contains("title: Test Document"));
+        assert!(created.exists());
+
+        let backups: Vec<_> = report.backup_paths().collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(backups[0]).unwrap(), "original content");
+
+        // Dropping an uncommitted transaction discards it
+        let mut transaction = writer.transaction();
+        transaction
+            .stage(&Document::empty(), &existing, None)
+            .unwrap();
+        transaction.rollback();
+        assert!(fs::read_to_string(&existing)
+            .unwrap()
+            .contains("title: Test Document"));
+    }
+
+    #[test]
+    fn test_transaction_rolls_back_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a/first.md");
+        let second = temp_dir.path().join("b/second.md");
+        let created = temp_dir.path().join("a/created.md");
+        fs::create_dir_all(first.parent().unwrap()).unwrap();
+        fs::create_dir_all(second.parent().unwrap()).unwrap();
+        fs::write(&first, "first").unwrap();
+        fs::write(&second, "second").unwrap();
+
+        let writer = FrontMatterWriter::new();
+        let document = create_test_document();
+        let mut transaction = writer.transaction();
+        for path in [&first, &created, &second] {
+            transaction.stage(&document, path, None).unwrap();
+        }
+
+        // Losing the staged file of the last write makes its commit fail
+        fs::remove_dir_all(second.parent().unwrap()).unwrap();
+
+        assert!(transaction.commit().is_err());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+        assert!(!created.exists());
+    }
+
+    #[test]
+    fn test_writes_are_journaled() {
+        let temp_dir = TempDir::new().unwrap();
+        let journal = crate::io::Journal::new(temp_dir.path().join("journal"));
+        let file_path = temp_dir.path().join("test.md");
+        fs::write(&file_path, "original content").unwrap();
+
+        let writer = FrontMatterWriter::new().with_journal(journal.start_run(Vec::new()).unwrap());
+        writer
+            .write_file(&create_test_document(), &file_path, None)
+            .unwrap();
+        assert_eq!(writer.journal().unwrap().recorded_count(), 1);
+
+        let run = journal.latest().unwrap().unwrap();
+        assert_eq!(run.files[0].original.as_deref(), Some("original content"));
+        assert!(run.files[0].is_unchanged());
+    }
+
     #[test]
     fn test_line_ending_normalization() {
         let config = WriterConfig {
@@ -8,7 +8,8 @@
 //!
 //! - **Type-safe front matter handling** with proper error types
 //! - **JSONPath query support** for powerful filtering and selection
-//! - **Atomic file operations** with backup support
+//! - **YAML (`---`), TOML (`+++`) and JSON (`;;;` or a leading `{`) front matter**, written back in the format it was read
+//! - **Atomic file operations** with backups, all-or-nothing batch transactions and an undo journal
 //! - **Batch processing** with file resolution and filtering
 //! - **Clean separation** between library and CLI concerns
 //! - **Comprehensive error handling** with detailed error information
@@ -145,14 +146,17 @@ pub use error::{ErrorSeverity, MatterOfError, Result};
 
 // Core types
 pub use core::{
-    CombineMode, Document, FrontMatterValue, JsonPathQuery, JsonPathQueryResult, KeyPath,
-    NormalizedPathUtils, Query, QueryResult, ValueType, ValueTypeCondition, YamlJsonConverter,
+    Aggregator, ChangeKind, CombineMode, Document, Extractor, ExtractorRegistry, FrontMatterChange,
+    FrontMatterDiff, FrontMatterFormat, FrontMatterValue, JsonPatcher, JsonPathQuery,
+    JsonPathQueryResult, KeyPath, NormalizedPathUtils, Query, QueryResult, ValueType,
+    ValueTypeCondition, YamlJsonConverter, YamlPatcher, YamlStyle,
 };
 
 // IO types
 pub use io::{
-    BackupOptions, FileResolver, FrontMatterReader, FrontMatterWriter, LineEndings, OutputOptions,
-    ReaderConfig, ResolvedFile, ResolverConfig, WriteOptions, WriteResult, WriterConfig,
+    BackupOptions, BatchReport, FileResolver, FrontMatterReader, FrontMatterWriter, Journal,
+    JournalRun, LineEndings, OutputOptions, ReaderConfig, ResolvedFile, ResolverConfig,
+    Transaction, UndoOptions, WriteOptions, WriteResult, WriterConfig,
 };
 
 // Internal modules
@@ -13,6 +13,7 @@ mod cli_bin;
 
 use crate::cli_bin::args::{Cli, Commands};
 use crate::cli_bin::commands::*;
+use crate::cli_bin::config::{apply_settings, load_settings};
 use matterof::error::{MatterOfError, Result};
 
 fn main() {
@@ -39,17 +40,46 @@ fn main() {
         std::env::args().collect::<Vec<_>>()
     );
 
+    // Fill what the command line left out from the environment and .matterof.toml
+    let mut command = cli.command;
+    let settings = std::env::current_dir()
+        .map_err(MatterOfError::Io)
+        .and_then(|cwd| load_settings(&cwd, cli.profile.as_deref()));
+    match settings {
+        Ok(settings) => apply_settings(&mut command, &settings),
+        Err(error) => {
+            handle_error(error);
+            process::exit(1);
+        }
+    }
+
     // Execute the command and handle errors
-    if let Err(error) = run_command(cli.command) {
-        handle_error(error);
-        process::exit(1);
+    let error_status = error_status(&command);
+    match run_command(command) {
+        Ok(0) => debug!("Command completed successfully"),
+        Ok(status) => {
+            debug!("Command completed with exit status {}", status);
+            process::exit(status);
+        }
+        Err(error) => {
+            handle_error(error);
+            process::exit(error_status);
+        }
     }
+}
 
-    debug!("Command completed successfully");
+/// Exit status of a command that failed
+///
+/// Like diff(1), `diff` exits with 1 when the files differ and 2 on errors.
+fn error_status(command: &Commands) -> i32 {
+    match command {
+        Commands::Diff(_) => 2,
+        _ => 1,
+    }
 }
 
-/// Run the appropriate command handler
-fn run_command(command: Commands) -> Result<()> {
+/// Run the appropriate command handler, returning the exit status
+fn run_command(command: Commands) -> Result<i32> {
     match command {
         Commands::Get(args) => {
             debug!("Running get command");
@@ -75,10 +105,30 @@ fn run_command(command: Commands) -> Result<()> {
             debug!("Running query command");
             query_command(args)
         }
+        Commands::Aggregate(args) => {
+            debug!("Running aggregate command");
+            aggregate_command(args)
+        }
+        Commands::Export(args) => {
+            debug!("Running export command");
+            export_command(args)
+        }
+        Commands::Import(args) => {
+            debug!("Running import command");
+            import_command(args)
+        }
+        Commands::Derive(args) => {
+            debug!("Running derive command");
+            derive_command(args)
+        }
         Commands::Init(args) => {
             debug!("Running init command");
             init_command(args)
         }
+        Commands::New(args) => {
+            debug!("Running new command");
+            new_command(args)
+        }
         Commands::Clean(args) => {
             debug!("Running clean command");
             clean_command(args)
@@ -91,6 +141,34 @@ fn run_command(command: Commands) -> Result<()> {
             debug!("Running format command");
             format_command(args)
         }
+        Commands::Convert(args) => {
+            debug!("Running convert command");
+            convert_command(args)
+        }
+        Commands::Schema(args) => {
+            debug!("Running schema command");
+            schema_command(args)
+        }
+        Commands::Index(args) => {
+            debug!("Running index command");
+            index_command(args)
+        }
+        Commands::Apply(args) => {
+            debug!("Running apply command");
+            apply_command(args)
+        }
+        Commands::Diff(args) => {
+            debug!("Running diff command");
+            diff_command(args)
+        }
+        Commands::Undo(args) => {
+            debug!("Running undo command");
+            undo_command(args)
+        }
+        Commands::History(args) => {
+            debug!("Running history command");
+            history_command(args)
+        }
     }
 }
 
@@ -173,10 +251,19 @@ fn handle_error(error: MatterOfError) {
 #[cfg(test)]
 mod tests {
     // Test module for main.rs
+    use super::*;
 
     #[test]
     fn test_main_compilation() {
         // This test just ensures the main module compiles correctly
         // More comprehensive tests would be in integration tests
     }
+
+    #[test]
+    fn test_error_status() {
+        let diff = Cli::try_parse_from(["matterof", "diff", "a.md", "b.md"]).unwrap();
+        assert_eq!(error_status(&diff.command), 2);
+        let get = Cli::try_parse_from(["matterof", "get", "a.md"]).unwrap();
+        assert_eq!(error_status(&get.command), 1);
+    }
 }
@@ -4,6 +4,13 @@
 //! testing the integration between all components and ensuring the library
 //! works correctly for real-world use cases.
 
+#![allow(
+    clippy::approx_constant,
+    clippy::len_zero,
+    clippy::unnecessary_unwrap,
+    clippy::useless_vec
+)]
+
 use matterof::*;
 use std::fs;
 use tempfile::TempDir;
@@ -382,6 +389,44 @@ count: 1
     assert!(diff.contains("+count: 2"));
 }
 
+#[test]
+fn test_format_conversion() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("post.md");
+    fs::write(
+        &file_path,
+        "---\ntitle: Hello\ntags: [a, b]\n---\n# Content\n",
+    )
+    .unwrap();
+
+    let mut document = FrontMatterReader::new().read_file(&file_path).unwrap();
+    document.convert_to(FrontMatterFormat::Toml).unwrap();
+
+    let writer = FrontMatterWriter::new();
+    let result = writer
+        .write_file(
+            &document,
+            &file_path,
+            Some(WriteOptions {
+                dry_run: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+    assert!(result.modified);
+    assert!(result.diff.unwrap().contains("+title = \"Hello\""));
+
+    writer.write_file(&document, &file_path, None).unwrap();
+    assert_eq!(
+        fs::read_to_string(&file_path).unwrap(),
+        "+++\ntitle = \"Hello\"\ntags = [\"a\", \"b\"]\n+++\n# Content\n"
+    );
+
+    let reread = FrontMatterReader::new().read_file(&file_path).unwrap();
+    assert_eq!(reread.format(), FrontMatterFormat::Toml);
+    assert_eq!(reread.front_matter(), document.front_matter());
+}
+
 #[test]
 fn test_value_type_conversions() {
     let mut document = Document::empty();